@@ -0,0 +1,279 @@
+use dnp3::app::control::*;
+use dnp3::app::Variation;
+use dnp3::decode::AppDecodeLevel;
+use dnp3::master::{Classes, CommandMode, EventClasses, TimeSyncProcedure};
+
+pub(crate) const HELP: &str = "commands:
+  class <0123>                          read the listed classes, e.g. 'class 123' for events
+  integrity                             read classes 1, 2, 3 and 0
+  read <gXvY> [<start> <stop>]          read all objects or a range of a variation
+  crob <index> <op> [<count> <on ms> <off ms>] [direct|sbo]
+                                        control relay output block where op is one of pulse-on,
+                                        pulse-off, latch-on, latch-off, close or trip
+  analog <index> <value> [g41v1|g41v2|g41v3|g41v4] [direct|sbo]
+                                        analog output, g41v1 or g41v3 by default
+  timesync [lan|non-lan]                synchronize the time of the outstation (lan by default)
+  restart <cold|warm>                   restart the outstation
+  link-status                           send a REQUEST_LINK_STATUS
+  decode <nothing|header|object-headers|object-values>
+                                        application layer decoding in the log
+  help                                  print this help
+  quit                                  exit the program
+
+commands are SELECT-before-OPERATE unless 'direct' is specified";
+
+/// a single command entered in the REPL or on the command line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Command {
+    Class(Classes),
+    Read(Variation, Option<(u16, u16)>),
+    Control(u16, CommandValue, CommandMode),
+    TimeSync(TimeSyncProcedure),
+    ColdRestart,
+    WarmRestart,
+    LinkStatus,
+    Decode(AppDecodeLevel),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// parse a command from whitespace-separated words
+    ///
+    /// returns `Ok(None)` for an empty line
+    pub(crate) fn parse(line: &str) -> Result<Option<Self>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return Ok(None),
+        };
+
+        let command = match (name, args) {
+            ("class", [classes]) => Command::Class(parse_classes(classes)?),
+            ("integrity", []) => Command::Class(Classes::all()),
+            ("read", [variation]) => Command::Read(parse_variation(variation)?, None),
+            ("read", [variation, start, stop]) => {
+                let start = parse_number(start)?;
+                let stop = parse_number(stop)?;
+                if stop < start {
+                    return Err(format!("invalid range: {} to {}", start, stop));
+                }
+                Command::Read(parse_variation(variation)?, Some((start, stop)))
+            }
+            ("crob", [index, op, rest @ ..]) => {
+                let (rest, mode) = parse_mode(rest)?;
+                let code = parse_control_code(op)?;
+                let crob = match rest {
+                    [] => Group12Var1::from_code(code),
+                    [count, on_time, off_time] => Group12Var1::new(
+                        code,
+                        parse_number(count)?,
+                        parse_number(on_time)?,
+                        parse_number(off_time)?,
+                    ),
+                    _ => return Err(format!("invalid arguments for '{}'", name)),
+                };
+                Command::Control(parse_number(index)?, CommandValue::G12V1(crob), mode)
+            }
+            ("analog", [index, value, rest @ ..]) => {
+                let (rest, mode) = parse_mode(rest)?;
+                let value = match rest {
+                    [] => parse_analog(value, None)?,
+                    [variation] => parse_analog(value, Some(parse_variation(variation)?))?,
+                    _ => return Err(format!("invalid arguments for '{}'", name)),
+                };
+                Command::Control(parse_number(index)?, value, mode)
+            }
+            ("timesync", []) | ("timesync", ["lan"]) => Command::TimeSync(TimeSyncProcedure::Lan),
+            ("timesync", ["non-lan"]) => Command::TimeSync(TimeSyncProcedure::NonLan),
+            ("restart", ["cold"]) => Command::ColdRestart,
+            ("restart", ["warm"]) => Command::WarmRestart,
+            ("link-status", []) => Command::LinkStatus,
+            ("decode", [level]) => Command::Decode(parse_decode_level(level)?),
+            ("help", []) => Command::Help,
+            ("quit", []) | ("exit", []) => Command::Quit,
+            ("class", _)
+            | ("integrity", _)
+            | ("read", _)
+            | ("crob", _)
+            | ("analog", _)
+            | ("timesync", _)
+            | ("restart", _)
+            | ("link-status", _)
+            | ("decode", _)
+            | ("help", _)
+            | ("quit", _)
+            | ("exit", _) => return Err(format!("invalid arguments for '{}'", name)),
+            _ => return Err(format!("unknown command: {}", name)),
+        };
+
+        Ok(Some(command))
+    }
+}
+
+pub(crate) fn parse_decode_level(value: &str) -> Result<AppDecodeLevel, String> {
+    match value {
+        "nothing" => Ok(AppDecodeLevel::Nothing),
+        "header" => Ok(AppDecodeLevel::Header),
+        "object-headers" => Ok(AppDecodeLevel::ObjectHeaders),
+        "object-values" => Ok(AppDecodeLevel::ObjectValues),
+        _ => Err(format!("unknown decode level: {}", value)),
+    }
+}
+
+pub(crate) fn parse_number<T>(value: &str) -> Result<T, String>
+where
+    T: std::str::FromStr,
+{
+    value
+        .parse()
+        .map_err(|_| format!("invalid number: {}", value))
+}
+
+fn parse_classes(value: &str) -> Result<Classes, String> {
+    let mut class0 = false;
+    let mut events = EventClasses::none();
+    for c in value.chars() {
+        match c {
+            '0' => class0 = true,
+            '1' => events.class1 = true,
+            '2' => events.class2 = true,
+            '3' => events.class3 = true,
+            _ => return Err(format!("invalid classes: {}", value)),
+        }
+    }
+    Ok(Classes::new(class0, events))
+}
+
+fn parse_variation(value: &str) -> Result<Variation, String> {
+    let invalid = || format!("invalid variation: {}", value);
+    let rest = value.strip_prefix('g').ok_or_else(invalid)?;
+    let mut parts = rest.splitn(2, 'v');
+    let group = parts
+        .next()
+        .and_then(|x| x.parse().ok())
+        .ok_or_else(invalid)?;
+    let var = parts
+        .next()
+        .and_then(|x| x.parse().ok())
+        .ok_or_else(invalid)?;
+    Variation::lookup(group, var).ok_or_else(|| format!("unknown variation: {}", value))
+}
+
+fn parse_mode<'a, 'b>(args: &'a [&'b str]) -> Result<(&'a [&'b str], CommandMode), String> {
+    match args.split_last() {
+        Some((&"direct", rest)) => Ok((rest, CommandMode::DirectOperate)),
+        Some((&"sbo", rest)) => Ok((rest, CommandMode::SelectBeforeOperate)),
+        _ => Ok((args, CommandMode::SelectBeforeOperate)),
+    }
+}
+
+fn parse_control_code(value: &str) -> Result<ControlCode, String> {
+    let code = match value {
+        "pulse-on" => ControlCode::from_op_type(OpType::PulseOn),
+        "pulse-off" => ControlCode::from_op_type(OpType::PulseOff),
+        "latch-on" => ControlCode::from_op_type(OpType::LatchOn),
+        "latch-off" => ControlCode::from_op_type(OpType::LatchOff),
+        "close" => ControlCode::from_tcc_and_op_type(TripCloseCode::Close, OpType::PulseOn),
+        "trip" => ControlCode::from_tcc_and_op_type(TripCloseCode::Trip, OpType::PulseOn),
+        _ => return Err(format!("unknown control operation: {}", value)),
+    };
+    Ok(code)
+}
+
+fn parse_analog(value: &str, variation: Option<Variation>) -> Result<CommandValue, String> {
+    let variation = match variation {
+        Some(x) => x,
+        // integers use the 32-bit variation and anything else single precision
+        None if value.parse::<i32>().is_ok() => Variation::Group41Var1,
+        None => Variation::Group41Var3,
+    };
+
+    let value = match variation {
+        Variation::Group41Var1 => CommandValue::G41V1(Group41Var1::new(parse_number(value)?)),
+        Variation::Group41Var2 => CommandValue::G41V2(Group41Var2::new(parse_number(value)?)),
+        Variation::Group41Var3 => CommandValue::G41V3(Group41Var3::new(parse_number(value)?)),
+        Variation::Group41Var4 => CommandValue::G41V4(Group41Var4::new(parse_number(value)?)),
+        _ => return Err(format!("not an analog output variation: {}", variation)),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Command {
+        Command::parse(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parses_reads() {
+        assert_eq!(
+            parse("class 0"),
+            Command::Class(Classes::new(true, EventClasses::none()))
+        );
+        assert_eq!(parse("integrity"), Command::Class(Classes::all()));
+        assert_eq!(
+            parse("read g30v1"),
+            Command::Read(Variation::Group30Var1, None)
+        );
+        assert_eq!(
+            parse(" read  g1v2 3 7 "),
+            Command::Read(Variation::Group1Var2, Some((3, 7)))
+        );
+        assert_eq!(Command::parse("   ").unwrap(), None);
+    }
+
+    #[test]
+    fn parses_controls() {
+        assert_eq!(
+            parse("crob 3 latch-on"),
+            Command::Control(
+                3,
+                CommandValue::G12V1(Group12Var1::from_op_type(OpType::LatchOn)),
+                CommandMode::SelectBeforeOperate
+            )
+        );
+        assert_eq!(
+            parse("crob 1 trip 2 500 250 direct"),
+            Command::Control(
+                1,
+                CommandValue::G12V1(Group12Var1::new(
+                    ControlCode::from_tcc_and_op_type(TripCloseCode::Trip, OpType::PulseOn),
+                    2,
+                    500,
+                    250
+                )),
+                CommandMode::DirectOperate
+            )
+        );
+        assert_eq!(
+            parse("analog 2 -17"),
+            Command::Control(
+                2,
+                CommandValue::G41V1(Group41Var1::new(-17)),
+                CommandMode::SelectBeforeOperate
+            )
+        );
+        assert_eq!(
+            parse("analog 2 1.5 g41v4 direct"),
+            Command::Control(
+                2,
+                CommandValue::G41V4(Group41Var4::new(1.5)),
+                CommandMode::DirectOperate
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_commands() {
+        assert!(Command::parse("foo").is_err());
+        assert!(Command::parse("read g99v99").is_err());
+        assert!(Command::parse("read g30v1 7 3").is_err());
+        assert!(Command::parse("class 4").is_err());
+        assert!(Command::parse("crob 3 blink").is_err());
+        assert!(Command::parse("analog 2 1.5 g30v1").is_err());
+        assert!(Command::parse("restart").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use dnp3::app::measurement::*;
+use dnp3::app::*;
+use dnp3::master::{HeaderInfo, ReadHandler, ReadType};
+
+/// prints the measurements received in every response to stdout
+pub(crate) struct PrintingReadHandler;
+
+impl PrintingReadHandler {
+    pub(crate) fn boxed() -> Box<dyn ReadHandler> {
+        Box::new(Self)
+    }
+}
+
+fn print_header(info: HeaderInfo) {
+    println!("{} ({}):", info.variation, info.qualifier);
+}
+
+fn print_value<T>(index: u16, value: T, flags: Flags, time: Option<Time>)
+where
+    T: std::fmt::Display,
+{
+    let time = match time {
+        Some(Time::Synchronized(x)) => x.to_string(),
+        Some(Time::NotSynchronized(x)) => format!("{} (unsynchronized)", x),
+        None => "none".to_string(),
+    };
+    println!(
+        "  [{}] value: {} flags: 0x{:02X} time: {}",
+        index, value, flags.value, time
+    );
+}
+
+impl ReadHandler for PrintingReadHandler {
+    fn begin_fragment(&mut self, read_type: ReadType, header: ResponseHeader) {
+        println!("{:?} ({} {})", read_type, header.iin.iin1, header.iin.iin2);
+    }
+
+    fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn handle_binary(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Binary, u16)>) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_double_bit_binary(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+    ) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_binary_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+    ) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_counter(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Counter, u16)>) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_frozen_counter(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+    ) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_analog(&mut self, info: HeaderInfo, iter: &mut dyn Iterator<Item = (Analog, u16)>) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_analog_output_status(
+        &mut self,
+        info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+    ) {
+        print_header(info);
+        for (x, index) in iter {
+            print_value(index, x.value, x.flags, x.time);
+        }
+    }
+
+    fn handle_octet_string<'a>(
+        &mut self,
+        info: HeaderInfo,
+        iter: &'a mut dyn Iterator<Item = (Bytes<'a>, u16)>,
+    ) {
+        print_header(info);
+        for (x, index) in iter {
+            let hex: Vec<String> = x.value.iter().map(|b| format!("{:02X}", b)).collect();
+            println!("  [{}] value: {}", index, hex.join(" "));
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Command-line DNP3 master for ad-hoc testing of outstations over TCP or serial
+
+use std::io::Write;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::watch;
+
+use dnp3::app::control::CommandValue;
+use dnp3::app::{ConnectStrategy, Listener, Timeout};
+use dnp3::decode::AppDecodeLevel;
+use dnp3::link::{EndpointAddress, LinkErrorMode};
+use dnp3::master::*;
+use dnp3::serial::*;
+use dnp3::tcp::*;
+
+use crate::command::{parse_decode_level, parse_number, Command, HELP};
+use crate::handler::PrintingReadHandler;
+
+mod command;
+mod handler;
+
+const USAGE: &str = "usage: dnp3-master [options] [-c <command>]...
+
+Connects to an outstation and runs the commands given with -c in order, or
+starts an interactive prompt if there are none. The commands given with -c stop
+at the first failure and the exit status is then non-zero. Enter 'help' at the
+prompt or use '-c help' to list the commands.
+
+options:
+  --tcp <host:port>             connect over TCP (default 127.0.0.1:20000)
+  --serial <path>               use a serial port instead of TCP
+  --baud <rate>                 serial baud rate (default 9600)
+  --data-bits <5|6|7|8>         serial data bits (default 8)
+  --parity <none|odd|even>      serial parity (default none)
+  --stop-bits <1|2>             serial stop bits (default 1)
+  --flow-control <none|software|hardware>
+                                serial flow control (default none)
+  --master <address>            link address of the master (default 1)
+  --outstation <address>        link address of the outstation (default 1024)
+  --timeout <ms>                response timeout (default 5000)
+  --connect-timeout <s>         time to wait for the connection before running
+                                the commands given with -c (default 10)
+  --decode <level>              application layer decoding in the log, one of
+                                nothing, header, object-headers or object-values
+                                (default nothing)
+  -c, --command <command>       command to run, may be repeated
+  -h, --help                    print this help";
+
+enum Transport {
+    Tcp(String),
+    Serial(String, SerialSettings),
+}
+
+struct Options {
+    transport: Transport,
+    master: u16,
+    outstation: u16,
+    response_timeout: u64,
+    connect_timeout: u64,
+    decode_level: AppDecodeLevel,
+    commands: Vec<Command>,
+}
+
+impl Options {
+    /// parse the options, returning `None` if the usage was requested
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut tcp = None;
+        let mut serial = None;
+        let mut settings = SerialSettings::default();
+        let mut options = Options {
+            transport: Transport::Tcp("127.0.0.1:20000".to_string()),
+            master: 1,
+            outstation: 1024,
+            response_timeout: 5000,
+            connect_timeout: 10,
+            decode_level: AppDecodeLevel::Nothing,
+            commands: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            if arg == "-h" || arg == "--help" {
+                return Ok(None);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for {}", arg))?;
+
+            match arg.as_str() {
+                "--tcp" => tcp = Some(value),
+                "--serial" => serial = Some(value),
+                "--baud" => settings.baud_rate = parse_number(&value)?,
+                "--data-bits" => {
+                    settings.data_bits = match value.as_str() {
+                        "5" => DataBits::Five,
+                        "6" => DataBits::Six,
+                        "7" => DataBits::Seven,
+                        "8" => DataBits::Eight,
+                        _ => return Err(format!("invalid data bits: {}", value)),
+                    }
+                }
+                "--parity" => {
+                    settings.parity = match value.as_str() {
+                        "none" => Parity::None,
+                        "odd" => Parity::Odd,
+                        "even" => Parity::Even,
+                        _ => return Err(format!("invalid parity: {}", value)),
+                    }
+                }
+                "--stop-bits" => {
+                    settings.stop_bits = match value.as_str() {
+                        "1" => StopBits::One,
+                        "2" => StopBits::Two,
+                        _ => return Err(format!("invalid stop bits: {}", value)),
+                    }
+                }
+                "--flow-control" => {
+                    settings.flow_control = match value.as_str() {
+                        "none" => FlowControl::None,
+                        "software" => FlowControl::Software,
+                        "hardware" => FlowControl::Hardware,
+                        _ => return Err(format!("invalid flow control: {}", value)),
+                    }
+                }
+                "--master" => options.master = parse_number(&value)?,
+                "--outstation" => options.outstation = parse_number(&value)?,
+                "--timeout" => options.response_timeout = parse_number(&value)?,
+                "--connect-timeout" => options.connect_timeout = parse_number(&value)?,
+                "--decode" => options.decode_level = parse_decode_level(&value)?,
+                "-c" | "--command" => match Command::parse(&value)? {
+                    Some(command) => options.commands.push(command),
+                    None => return Err("empty command".to_string()),
+                },
+                _ => return Err(format!("unknown option: {}", arg)),
+            }
+        }
+
+        options.transport = match (tcp, serial) {
+            (Some(_), Some(_)) => return Err("--tcp and --serial are exclusive".to_string()),
+            (Some(address), None) => Transport::Tcp(address),
+            (None, Some(path)) => Transport::Serial(path, settings),
+            (None, None) => options.transport,
+        };
+
+        Ok(Some(options))
+    }
+}
+
+/// tracks whether the channel is connected so that commands can wait for the connection
+struct ConnectionListener<T> {
+    tx: watch::Sender<bool>,
+    is_connected: fn(&T) -> bool,
+}
+
+impl<T> Listener<T> for ConnectionListener<T>
+where
+    T: std::fmt::Debug,
+{
+    fn update(&mut self, value: T) {
+        tracing::info!("{:?}", value);
+        let _ = self.tx.send((self.is_connected)(&value));
+    }
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // keep the log on stderr so that it doesn't mix with the measurements
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
+
+    if let Err(err) = run(options).await {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn run(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = MasterChannelConfig::new(EndpointAddress::from(options.master)?);
+    config.decode_level = options.decode_level.into();
+    config.response_timeout = Timeout::from_millis(options.response_timeout)?;
+
+    let (tx, mut rx) = watch::channel(false);
+    let mut channel = match &options.transport {
+        Transport::Tcp(address) => spawn_master_tcp_client(
+            LinkErrorMode::Close,
+            config,
+            EndpointList::new(address.clone(), &[]),
+            ConnectStrategy::default(),
+            Box::new(ConnectionListener {
+                tx,
+                is_connected: |x| *x == ClientState::Connected,
+            }),
+        ),
+        Transport::Serial(path, settings) => spawn_master_serial(
+            config,
+            path,
+            *settings,
+            Duration::from_secs(1),
+            Box::new(ConnectionListener {
+                tx,
+                is_connected: |x| *x == PortState::Open,
+            }),
+        ),
+    };
+
+    // the tool only performs the requests it is asked for
+    let mut association = channel
+        .add_association(
+            EndpointAddress::from(options.outstation)?,
+            AssociationConfig::quiet(),
+            PrintingReadHandler::boxed(),
+            DefaultAssociationHandler::boxed(),
+        )
+        .await?;
+
+    channel.enable().await?;
+
+    if options.commands.is_empty() {
+        return repl(&mut channel, &mut association).await;
+    }
+
+    let connected = async {
+        while !*rx.borrow() {
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
+    };
+    let timeout = Duration::from_secs(options.connect_timeout);
+    if tokio::time::timeout(timeout, connected).await.is_err() {
+        return Err(format!("not connected after {} s", options.connect_timeout).into());
+    }
+
+    for command in options.commands {
+        match command {
+            Command::Quit => break,
+            Command::Help => println!("{}", HELP),
+            command => execute(&mut channel, &mut association, command).await?,
+        }
+    }
+
+    Ok(())
+}
+
+async fn repl(
+    channel: &mut MasterChannel,
+    association: &mut AssociationHandle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+
+        let line = match lines.next_line().await? {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+
+        match Command::parse(&line) {
+            Ok(None) => {}
+            Ok(Some(Command::Quit)) => return Ok(()),
+            Ok(Some(Command::Help)) => println!("{}", HELP),
+            Ok(Some(command)) => {
+                if let Err(err) = execute(channel, association, command).await {
+                    println!("error: {}", err);
+                }
+            }
+            Err(err) => println!("{}", err),
+        }
+    }
+}
+
+async fn execute(
+    channel: &mut MasterChannel,
+    association: &mut AssociationHandle,
+    command: Command,
+) -> Result<(), String> {
+    match command {
+        Command::Class(classes) => association
+            .read(classes.to_request())
+            .await
+            .map_err(describe)?,
+        Command::Read(variation, None) => association
+            .read(ReadRequest::all_objects(variation))
+            .await
+            .map_err(describe)?,
+        Command::Read(variation, Some((start, stop))) => association
+            .read(ReadRequest::two_byte_range(variation, start, stop))
+            .await
+            .map_err(describe)?,
+        Command::Control(index, value, mode) => {
+            let headers = match value {
+                CommandValue::G12V1(x) => CommandBuilder::single_header_u16(x, index),
+                CommandValue::G41V1(x) => CommandBuilder::single_header_u16(x, index),
+                CommandValue::G41V2(x) => CommandBuilder::single_header_u16(x, index),
+                CommandValue::G41V3(x) => CommandBuilder::single_header_u16(x, index),
+                CommandValue::G41V4(x) => CommandBuilder::single_header_u16(x, index),
+            };
+            let report = association
+                .operate_with_report(mode, headers)
+                .await
+                .map_err(describe)?;
+            for item in report.items() {
+                println!("[{}] status: {:?}", item.index, item.status);
+            }
+            report.outcome.map_err(describe)?;
+        }
+        Command::TimeSync(procedure) => association
+            .synchronize_time(procedure)
+            .await
+            .map_err(describe)?,
+        Command::ColdRestart => {
+            let delay = association.cold_restart().await.map_err(describe)?;
+            println!("restart delay: {} ms", delay.as_millis());
+        }
+        Command::WarmRestart => {
+            let delay = association.warm_restart().await.map_err(describe)?;
+            println!("restart delay: {} ms", delay.as_millis());
+        }
+        Command::LinkStatus => {
+            let result = association.check_link_status().await.map_err(describe)?;
+            println!("link status: {:?}", result);
+        }
+        Command::Decode(level) => channel
+            .set_decode_level(level.into())
+            .await
+            .map_err(describe)?,
+        Command::Help | Command::Quit => {}
+    }
+
+    Ok(())
+}
+
+fn describe<E>(err: E) -> String
+where
+    E: std::fmt::Display,
+{
+    err.to_string()
+}
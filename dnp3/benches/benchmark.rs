@@ -1,4 +1,4 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::{Rng, SeedableRng};
@@ -10,7 +10,7 @@ use dnp3::link::*;
 use dnp3::master::*;
 use dnp3::outstation::database::*;
 use dnp3::outstation::*;
-use dnp3::tcp::*;
+use dnp3::stream::*;
 
 fn config() -> TestConfig {
     TestConfig {
@@ -21,8 +21,8 @@ fn config() -> TestConfig {
     }
 }
 
-// the ports used... the number of ports determines the number of parallel entries
-const PORT_RANGE_16: std::ops::Range<u16> = 50000..50016;
+// the number of master/outstation pairs communicating in parallel
+const NUM_PAIRS: usize = 16;
 
 struct TestInstance {
     runtime: tokio::runtime::Runtime,
@@ -30,13 +30,13 @@ struct TestInstance {
 }
 
 impl TestInstance {
-    fn create(ports: std::ops::Range<u16>) -> Self {
+    fn create(num_pairs: usize) -> Self {
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .build()
             .unwrap();
 
-        let mut harness = runtime.block_on(TestHarness::create(ports, config()));
+        let mut harness = runtime.block_on(TestHarness::create(num_pairs, config()));
 
         runtime.block_on(harness.wait_for_startup());
 
@@ -54,7 +54,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         .with_target(false)
         .init();
 
-    let mut instance = TestInstance::create(PORT_RANGE_16);
+    let mut instance = TestInstance::create(NUM_PAIRS);
     c.bench_function("16 sessions", |b| {
         b.iter(|| {
             instance.run_iteration();
@@ -78,10 +78,10 @@ struct TestHarness {
 }
 
 impl TestHarness {
-    async fn create(ports: std::ops::Range<u16>, config: TestConfig) -> Self {
+    async fn create(num_pairs: usize, config: TestConfig) -> Self {
         let mut pairs = Vec::new();
-        for port in ports {
-            pairs.push(Pair::spawn(port, config).await)
+        for _ in 0..num_pairs {
+            pairs.push(Pair::spawn(config).await)
         }
         Self { pairs }
     }
@@ -106,8 +106,6 @@ impl TestHarness {
 struct Pair {
     // measurements exchanged on each iteration
     values: Measurements,
-    // have to hold onto this to keep TCP server alive
-    _server: ServerHandle,
     // have to hold onto this to keep master alive
     _master: MasterChannel,
     // count of matching measurements received
@@ -117,7 +115,8 @@ struct Pair {
 }
 
 impl Pair {
-    const LOCALHOST: std::net::IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+    // the streams are never re-opened, so this is never used
+    const RETRY_DELAY: Duration = Duration::from_secs(1);
 
     fn update_values(&mut self) {
         self.outstation.transaction(|db| {
@@ -135,33 +134,31 @@ impl Pair {
         assert_eq!(self.rx.recv().await.unwrap(), self.values.len());
     }
 
-    async fn spawn(port: u16, config: TestConfig) -> Self {
-        let (server, outstation) = Self::spawn_outstation(port, config).await;
-        let (master, measurements, rx) = Self::spawn_master(port, config).await;
+    async fn spawn(config: TestConfig) -> Self {
+        let (master_stream, outstation_stream) = loopback(LoopbackConfig::default());
+        let outstation = Self::spawn_outstation(outstation_stream, config);
+        let (master, measurements, rx) = Self::spawn_master(master_stream, config).await;
 
         Self {
             values: measurements,
-            _server: server,
             rx,
             _master: master,
             outstation,
         }
     }
 
-    async fn spawn_outstation(port: u16, config: TestConfig) -> (ServerHandle, OutstationHandle) {
-        let mut server =
-            TcpServer::new(LinkErrorMode::Close, SocketAddr::new(Self::LOCALHOST, port));
-        let outstation = server
-            .add_outstation(
-                Self::get_outstation_config(config.outstation_level),
-                EventBufferConfig::all_types(100),
-                DefaultOutstationApplication::create(),
-                DefaultOutstationInformation::create(),
-                DefaultControlHandler::create(),
-                NullListener::create(),
-                AddressFilter::Any,
-            )
-            .unwrap();
+    fn spawn_outstation(stream: LoopbackStream, config: TestConfig) -> OutstationHandle {
+        let outstation = spawn_outstation_custom(
+            single_stream(stream),
+            Self::RETRY_DELAY,
+            LinkErrorMode::Close,
+            Self::get_outstation_config(config.outstation_level),
+            EventBufferConfig::all_types(100),
+            DefaultOutstationApplication::create(),
+            DefaultOutstationInformation::create(),
+            DefaultControlHandler::create(),
+            NullListener::create(),
+        );
 
         // set up the database
         outstation.transaction(|db| {
@@ -195,24 +192,22 @@ impl Pair {
             }
         });
 
-        let server_handle = server.bind().await.unwrap();
-
-        (server_handle, outstation)
+        outstation
     }
 
     async fn spawn_master(
-        port: u16,
+        stream: LoopbackStream,
         config: TestConfig,
     ) -> (
         MasterChannel,
         Measurements,
         tokio::sync::mpsc::Receiver<usize>,
     ) {
-        let mut master = dnp3::tcp::spawn_master_tcp_client(
-            LinkErrorMode::Close,
+        let mut master = spawn_master_custom(
             Self::get_master_config(config.master_level),
-            EndpointList::single(format!("127.0.0.1:{}", port)),
-            ConnectStrategy::default(),
+            LinkErrorMode::Close,
+            single_stream(stream),
+            Self::RETRY_DELAY,
             NullListener::create(),
         );
 
@@ -67,6 +67,7 @@ class EnumModule(enums: List[EnumModel]) extends Module {
     def enumDefinition : Iterator[String] = {
       model.comments.map(commented).iterator ++
         "#[derive(Copy, Clone, Debug, PartialEq)]".eol ++
+        serdeDerive ++
         bracket(s"pub enum ${model.name}") {
           if(model.captureUnknownValues) {
             values ++ Iterator(commented("captures any value not defined in the enumeration"), "Unknown(u8),")
@@ -76,6 +76,7 @@ object FixedSizeVariationModule extends Module {
 
     commented(gv.fullDesc).eol ++
     "#[derive(Copy, Clone, Debug, PartialEq)]".eol ++
+    (if(visibility(gv).isEmpty) serdeDerive else Iterator.empty) ++
     bracket(s"pub${visibility(gv)} struct ${gv.name}") {
       gv.fields.flatMap(f => field(f)).iterator
     }
@@ -18,6 +18,7 @@ object VariationEnumModule extends Module {
 
       "/// All variations supported by the library".eol ++
       "#[derive(Copy, Clone, Debug, PartialEq)]".eol ++
+      serdeDerive ++
       bracket("pub enum Variation") {
         ObjectGroup.allVariations.iterator.flatMap {
           v => {
@@ -50,7 +51,8 @@ object VariationEnumModule extends Module {
         }
       }
 
-      bracket("pub(crate) fn lookup(group: u8, var: u8) -> Option<Variation>") {
+      commented("look up a variation from its group and variation numbers").eol ++
+      bracket("pub fn lookup(group: u8, var: u8) -> Option<Variation>") {
         bracket("match group") {
           ObjectGroup.all.iterator.flatMap(matchVariation) ++ "_ => None,".eol
         }
@@ -69,7 +71,8 @@ object VariationEnumModule extends Module {
         }
       }
 
-      bracket("pub(crate) fn to_group_and_var(self) -> (u8, u8)") {
+      commented("group and variation numbers of the variation").eol ++
+      bracket("pub fn to_group_and_var(self) -> (u8, u8)") {
         bracket("match self") {
           ObjectGroup.allVariations.iterator.flatMap(matcher)
         }
@@ -13,6 +13,14 @@ package object render {
 
   def commented(s: String): String = s"/// ${s}"
 
+  // optional serde derives for public types, enabled by the "serialization" feature
+  def serdeDerive: Iterator[String] = Iterator(
+    "#[cfg_attr(",
+    "    feature = \"serialization\",",
+    "    derive(serde::Serialize, serde::Deserialize)",
+    ")]"
+  )
+
   def quoted(s: String): String = s""""${s}""""
 
   def spaced(groups: Iterator[Iterator[String]]): Iterator[String] = {
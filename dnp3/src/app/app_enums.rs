@@ -14,6 +14,10 @@ use crate::util::cursor::{WriteCursor, WriteError};
 
 /// Application object header types
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum QualifierCode {
     ///  8-bit start stop (value == 0x00)
     Range8,
@@ -71,6 +75,10 @@ impl QualifierCode {
 
 /// Application layer function code enumeration
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum FunctionCode {
     ///  Master sends this to an outstation to confirm the receipt of an Application Layer fragment (value == 0)
     Confirm,
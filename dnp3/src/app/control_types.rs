@@ -1,7 +1,12 @@
 use crate::app::control_enums::{OpType, TripCloseCode};
+use crate::app::variations::{Group12Var1, Group41Var1, Group41Var2, Group41Var3, Group41Var4};
 
 /// Control code field used within g12v1
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct ControlCode {
     /// This field is used in conjunction with the `op_type` field to specify a control operation
     pub tcc: TripCloseCode,
@@ -132,3 +137,64 @@ mod test {
         );
     }
 }
+
+/// Value of a control object of any type supported in control requests
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum CommandValue {
+    /// Control relay output block
+    G12V1(Group12Var1),
+    /// Analog output - 32-bit integer
+    G41V1(Group41Var1),
+    /// Analog output - 16-bit integer
+    G41V2(Group41Var2),
+    /// Analog output - single-precision floating point
+    G41V3(Group41Var3),
+    /// Analog output - double-precision floating point
+    G41V4(Group41Var4),
+}
+
+impl From<Group12Var1> for CommandValue {
+    fn from(x: Group12Var1) -> Self {
+        CommandValue::G12V1(x)
+    }
+}
+
+impl From<Group41Var1> for CommandValue {
+    fn from(x: Group41Var1) -> Self {
+        CommandValue::G41V1(x)
+    }
+}
+
+impl From<Group41Var2> for CommandValue {
+    fn from(x: Group41Var2) -> Self {
+        CommandValue::G41V2(x)
+    }
+}
+
+impl From<Group41Var3> for CommandValue {
+    fn from(x: Group41Var3) -> Self {
+        CommandValue::G41V3(x)
+    }
+}
+
+impl From<Group41Var4> for CommandValue {
+    fn from(x: Group41Var4) -> Self {
+        CommandValue::G41V4(x)
+    }
+}
+
+impl std::fmt::Display for CommandValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CommandValue::G12V1(x) => write!(f, "g12v1 {}", x),
+            CommandValue::G41V1(x) => write!(f, "g41v1 {}", x),
+            CommandValue::G41V2(x) => write!(f, "g41v2 {}", x),
+            CommandValue::G41V3(x) => write!(f, "g41v3 {}", x),
+            CommandValue::G41V4(x) => write!(f, "g41v4 {}", x),
+        }
+    }
+}
@@ -10,6 +10,10 @@ use crate::util::cursor::{ReadCursor, ReadError, WriteCursor, WriteError};
 
 /// Control field in the application-layer header
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct ControlField {
     /// FIR bit - set if the first fragment in a multi-fragmented response
     pub fir: bool,
@@ -136,6 +140,10 @@ impl ControlField {
 
 /// Internal Indications Byte #1
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Iin1 {
     /// underlying value for IIN1
     pub value: u8,
@@ -143,6 +151,10 @@ pub struct Iin1 {
 
 /// Internal Indications Byte #2
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Iin2 {
     /// underlying value for IIN2
     pub value: u8,
@@ -150,6 +162,10 @@ pub struct Iin2 {
 
 /// Internal Indications (2 bytes)
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Iin {
     /// IIN byte #1
     pub iin1: Iin1,
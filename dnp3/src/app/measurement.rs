@@ -7,6 +7,10 @@ use crate::util::bit::Bitfield;
 
 /// Enumeration modeling two stables states and an in-transit state
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum DoubleBit {
     /// Transitioning between end conditions
     Intermediate,
@@ -20,6 +24,10 @@ pub enum DoubleBit {
 
 /// A DNP3 time value that may be Synchronized or NotSynchronized
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Time {
     /// The timestamp is UTC synchronized at the remote device
     Synchronized(Timestamp),
@@ -50,6 +58,10 @@ impl Time {
 /// should refer to the standard to determine what flag values
 /// correspond to each type.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Flags {
     /// underlying bitmask
     pub value: u8,
@@ -129,6 +141,10 @@ impl Time {
 
 /// Measurement type corresponding to groups 1 and 2
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Binary {
     /// value of the type
     pub value: bool,
@@ -151,6 +167,10 @@ impl Binary {
 
 /// Measurement type corresponding to groups 3 and 4
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct DoubleBitBinary {
     /// value of the type
     pub value: DoubleBit,
@@ -173,6 +193,10 @@ impl DoubleBitBinary {
 
 /// Measurement type corresponding to groups 10 and 11
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct BinaryOutputStatus {
     /// value of the type
     pub value: bool,
@@ -195,6 +219,10 @@ impl BinaryOutputStatus {
 
 /// Measurement type corresponding to groups 20 and 22
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Counter {
     /// value of the type
     pub value: u32,
@@ -217,6 +245,10 @@ impl Counter {
 
 /// Measurement type corresponding to groups 21 and 23
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct FrozenCounter {
     /// value of the type
     pub value: u32,
@@ -239,6 +271,10 @@ impl FrozenCounter {
 
 /// Measurement type corresponding to groups 30 and 32
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Analog {
     /// value of the type
     pub value: f64,
@@ -541,6 +577,10 @@ pub(crate) trait AnalogConversions {
 
 /// Measurement type corresponding to groups 40 and 42
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct AnalogOutputStatus {
     /// value of the type
     pub value: f64,
@@ -570,6 +610,11 @@ impl AnalogOutputStatus {
 /// C-style string.
 #[allow(missing_copy_implementations)]
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<u8>", into = "Vec<u8>")
+)]
 pub struct OctetString {
     value: [u8; Self::MAX_SIZE],
     len: u8,
@@ -618,6 +663,20 @@ impl OctetString {
     }
 }
 
+impl std::convert::TryFrom<Vec<u8>> for OctetString {
+    type Error = OctetStringError;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<OctetString> for Vec<u8> {
+    fn from(value: OctetString) -> Self {
+        value.value().to_vec()
+    }
+}
+
 /// Errors when creating an octet string
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum OctetStringError {
@@ -628,6 +687,17 @@ pub enum OctetStringError {
     MoreThan255Octets,
 }
 
+impl std::fmt::Display for OctetStringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ZeroLength => f.write_str("octet strings may not be zero length"),
+            Self::MoreThan255Octets => f.write_str("octet strings may not exceed 255 octets"),
+        }
+    }
+}
+
+impl std::error::Error for OctetStringError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
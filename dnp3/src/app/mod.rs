@@ -13,7 +13,7 @@ pub use variations::Variation;
 /// Types used for making binary and analog output control requests
 pub mod control {
     pub use super::control_enums::*;
-    pub use super::control_types::ControlCode;
+    pub use super::control_types::{CommandValue, ControlCode};
     pub use super::variations::{Group12Var1, Group41Var1, Group41Var2, Group41Var3, Group41Var4};
 }
 
@@ -170,6 +170,13 @@ impl<'a> ParsedFragment<'a> {
     pub(crate) fn parse(fragment: &'a [u8]) -> Result<Self, HeaderParseError> {
         Self::parse_no_logging(fragment)
     }
+
+    /// iterate over the object headers, including any that precede an error in the objects
+    pub(crate) fn iter_headers(&self) -> HeaderIterator<'a> {
+        HeaderIterator {
+            parser: ObjectParser::one_pass(self.function, self.raw_objects),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -8,6 +8,10 @@ use crate::util::cursor::ReadError;
 
 /// errors that occur when parsing an application layer header
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum HeaderParseError {
     /// unknown function code
     UnknownFunction(Sequence, u8),
@@ -17,6 +21,10 @@ pub enum HeaderParseError {
 
 /// errors that occur when parsing object headers
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum ObjectParseError {
     /// unknown group and variation
     UnknownGroupVariation(u8, u8),
@@ -1,5 +1,9 @@
 /// Application-layer sequence number
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Sequence {
     value: u8,
 }
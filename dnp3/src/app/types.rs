@@ -10,6 +10,10 @@ use crate::util::cursor::{WriteCursor, WriteError};
 
 /// Wrapper around a u64 count of milliseconds since Unix epoch UTC
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Timestamp {
     value: u64,
 }
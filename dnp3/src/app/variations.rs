@@ -18,6 +18,10 @@ use crate::app::measurement::*;
 
 /// All variations supported by the library
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum Variation {
     /// Binary Input - Any Variation
     Group1Var0,
@@ -208,7 +212,8 @@ pub enum Variation {
 }
 
 impl Variation {
-    pub(crate) fn lookup(group: u8, var: u8) -> Option<Variation> {
+    /// look up a variation from its group and variation numbers
+    pub fn lookup(group: u8, var: u8) -> Option<Variation> {
         match group {
             1 => match var {
                 0 => Some(Variation::Group1Var0),
@@ -370,7 +375,8 @@ impl Variation {
         }
     }
     
-    pub(crate) fn to_group_and_var(self) -> (u8, u8) {
+    /// group and variation numbers of the variation
+    pub fn to_group_and_var(self) -> (u8, u8) {
         match self {
             Variation::Group1Var0 => (1, 0),
             Variation::Group1Var1 => (1, 1),
@@ -702,6 +708,10 @@ pub(crate) struct Group42Var1 {
 
 /// Analog Output - Double-precision
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Group41Var4 {
     /// value field of the variation
     pub value: f64,
@@ -711,6 +721,10 @@ pub struct Group41Var4 {
 
 /// Analog Output - Single-precision
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Group41Var3 {
     /// value field of the variation
     pub value: f32,
@@ -720,6 +734,10 @@ pub struct Group41Var3 {
 
 /// Analog Output - 16-bit With Flag
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Group41Var2 {
     /// value field of the variation
     pub value: i16,
@@ -729,6 +747,10 @@ pub struct Group41Var2 {
 
 /// Analog Output - 32-bit With Flag
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Group41Var1 {
     /// value field of the variation
     pub value: i32,
@@ -1070,6 +1092,10 @@ pub(crate) struct Group20Var1 {
 
 /// Binary Command - Control Relay Output Block
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct Group12Var1 {
     /// code field of the variation
     pub code: ControlCode,
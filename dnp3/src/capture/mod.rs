@@ -0,0 +1,135 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+pub use pcap::*;
+
+mod pcap;
+
+/// Direction of data captured at the physical layer
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum CaptureDirection {
+    /// data received from the remote device
+    Rx,
+    /// data transmitted to the remote device
+    Tx,
+}
+
+/// Data read from or written to the physical layer
+#[derive(Copy, Clone, Debug)]
+pub struct CaptureRecord<'a> {
+    /// time at which the data was read or written
+    pub timestamp: SystemTime,
+    /// direction of the data
+    pub direction: CaptureDirection,
+    /// local address of the connection, only available for TCP
+    pub local: Option<SocketAddr>,
+    /// remote address of the connection, only available for TCP
+    pub remote: Option<SocketAddr>,
+    /// number of bytes previously transmitted on the same connection
+    pub sent: u64,
+    /// number of bytes previously received on the same connection
+    pub received: u64,
+    /// the bytes that were read or written
+    pub data: &'a [u8],
+}
+
+/// Receives every physical layer read and write of a master channel or outstation
+///
+/// The sink is invoked from the communication task and should not block for long periods.
+pub trait CaptureSink: Send + 'static {
+    /// record data read from or written to the physical layer
+    fn record(&mut self, record: &CaptureRecord);
+}
+
+pub(crate) type SharedCaptureSink = Arc<Mutex<Box<dyn CaptureSink>>>;
+
+/// sinks installed on a master or outstation, shared with the physical layer of the current connection
+///
+/// The capture sink and the decode event sink are installed independently, so both can be active at once.
+#[derive(Clone, Default)]
+pub(crate) struct CaptureSlot {
+    sinks: Arc<Mutex<InstalledSinks>>,
+}
+
+#[derive(Default)]
+struct InstalledSinks {
+    capture: Option<SharedCaptureSink>,
+    events: Option<SharedCaptureSink>,
+}
+
+impl CaptureSlot {
+    pub(crate) fn set(&self, sink: Option<SharedCaptureSink>) {
+        self.sinks.lock().unwrap().capture = sink;
+    }
+
+    pub(crate) fn set_events(&self, sink: Option<SharedCaptureSink>) {
+        self.sinks.lock().unwrap().events = sink;
+    }
+
+    pub(crate) fn get(&self) -> Vec<SharedCaptureSink> {
+        let sinks = self.sinks.lock().unwrap();
+        sinks
+            .capture
+            .iter()
+            .chain(sinks.events.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn record(&self, record: &CaptureRecord) {
+        for sink in self.get() {
+            sink.lock().unwrap().record(record);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct CountingSink(Arc<Mutex<usize>>);
+
+    impl CaptureSink for CountingSink {
+        fn record(&mut self, _: &CaptureRecord) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    fn counting_sink() -> (SharedCaptureSink, Arc<Mutex<usize>>) {
+        let count = Arc::new(Mutex::new(0));
+        let sink: Box<dyn CaptureSink> = Box::new(CountingSink(count.clone()));
+        (Arc::new(Mutex::new(sink)), count)
+    }
+
+    #[test]
+    fn records_to_the_capture_and_decode_event_sinks() {
+        let record = CaptureRecord {
+            timestamp: SystemTime::now(),
+            direction: CaptureDirection::Rx,
+            local: None,
+            remote: None,
+            sent: 0,
+            received: 0,
+            data: &[0x05, 0x64],
+        };
+
+        let slot = CaptureSlot::default();
+        let (capture, capture_count) = counting_sink();
+        let (events, events_count) = counting_sink();
+        slot.set(Some(capture));
+        slot.set_events(Some(events));
+        slot.record(&record);
+
+        // removing one sink leaves the other installed
+        slot.set(None);
+        slot.record(&record);
+
+        assert_eq!(*capture_count.lock().unwrap(), 1);
+        assert_eq!(*events_count.lock().unwrap(), 2);
+    }
+}
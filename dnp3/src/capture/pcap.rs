@@ -0,0 +1,405 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::capture::{CaptureDirection, CaptureRecord, CaptureSink};
+
+/// Link type of the packets written by a [PcapFileSink]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PcapLinkType {
+    /// Wrap the data in synthesized IPv4/IPv6 and TCP headers so that Wireshark dissects it as DNP3 over TCP
+    ///
+    /// The addresses of TCP connections are used as is. Data from other physical layers
+    /// (serial, custom streams) is captured as a connection between 127.0.0.1:20000 (local)
+    /// and 127.0.0.2:20000 (remote).
+    Tcp,
+    /// Write the data as is using the provided DLT value (e.g. 147 for `DLT_USER0`)
+    ///
+    /// The direction of each packet is recorded in its `epb_flags` option.
+    User(u16),
+}
+
+/// [CaptureSink] that writes every read and write to a pcapng file, rotating it when it reaches a maximum size
+///
+/// When the file at `path` would exceed `max_size` bytes, it is renamed to `path.1`,
+/// `path.1` is renamed to `path.2`, and so on. At most `max_files` rotated files are kept.
+/// Each file starts with its own section header so that it can be opened independently.
+///
+/// Writes are buffered. The buffer is flushed when the file is rotated and when the sink is dropped.
+pub struct PcapFileSink {
+    path: PathBuf,
+    link_type: PcapLinkType,
+    max_size: u64,
+    max_files: usize,
+    file: BufWriter<File>,
+    size: u64,
+}
+
+const LINKTYPE_RAW: u16 = 101;
+// keeps the length of synthesized IP packets within the 16-bit length fields
+const MAX_SEGMENT_SIZE: usize = 65000;
+
+impl PcapFileSink {
+    /// Open (or create) the capture file at `path`, appending a new section to it if it already exists
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        link_type: PcapLinkType,
+        max_size: u64,
+        max_files: usize,
+    ) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = Self::open(&path)?;
+        let size = file.metadata()?.len();
+        let mut sink = Self {
+            path,
+            link_type,
+            max_size,
+            max_files,
+            file: BufWriter::new(file),
+            size,
+        };
+        sink.write_header()?;
+        Ok(sink)
+    }
+
+    fn open(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        // the buffered data belongs to the file being rotated out
+        self.file.flush()?;
+        if self.max_files == 0 {
+            self.file = BufWriter::new(File::create(&self.path)?);
+        } else {
+            // shift the existing files, discarding the oldest
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = BufWriter::new(Self::open(&self.path)?);
+        }
+        self.size = 0;
+        self.write_header()
+    }
+
+    fn write_block(&mut self, block: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(block)?;
+        self.size += block.len() as u64;
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        let link_type = match self.link_type {
+            PcapLinkType::Tcp => LINKTYPE_RAW,
+            PcapLinkType::User(x) => x,
+        };
+        self.write_block(&section_header_block())?;
+        self.write_block(&interface_description_block(link_type))
+    }
+
+    fn write_record(&mut self, record: &CaptureRecord) -> std::io::Result<()> {
+        let mut blocks = Vec::new();
+        match self.link_type {
+            PcapLinkType::Tcp => {
+                let mut offset = 0;
+                for chunk in record.data.chunks(MAX_SEGMENT_SIZE) {
+                    let packet = tcp_packet(record, offset, chunk);
+                    blocks.push(enhanced_packet_block(
+                        record.timestamp,
+                        record.direction,
+                        &packet,
+                    ));
+                    offset += chunk.len() as u64;
+                }
+            }
+            PcapLinkType::User(_) => blocks.push(enhanced_packet_block(
+                record.timestamp,
+                record.direction,
+                record.data,
+            )),
+        }
+
+        let len: u64 = blocks.iter().map(|x| x.len() as u64).sum();
+        if self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        for block in blocks {
+            self.write_block(&block)?;
+        }
+        Ok(())
+    }
+}
+
+impl CaptureSink for PcapFileSink {
+    fn record(&mut self, record: &CaptureRecord) {
+        if let Err(err) = self.write_record(record) {
+            tracing::error!(
+                "unable to write capture record to {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+impl Drop for PcapFileSink {
+    fn drop(&mut self) {
+        if let Err(err) = self.file.flush() {
+            tracing::error!(
+                "unable to flush capture file {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    let length = (12 + body.len()) as u32;
+    let mut block = Vec::with_capacity(length as usize);
+    block.extend_from_slice(&block_type.to_le_bytes());
+    block.extend_from_slice(&length.to_le_bytes());
+    block.extend_from_slice(body);
+    block.extend_from_slice(&length.to_le_bytes());
+    block
+}
+
+fn section_header_block() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0x1A2B_3C4Du32.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    // section length is not specified
+    body.extend_from_slice(&(-1i64).to_le_bytes());
+    block(0x0A0D_0D0A, &body)
+}
+
+fn interface_description_block(link_type: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&link_type.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    // no snapshot length limit
+    body.extend_from_slice(&0u32.to_le_bytes());
+    block(0x0000_0001, &body)
+}
+
+fn enhanced_packet_block(
+    timestamp: SystemTime,
+    direction: CaptureDirection,
+    data: &[u8],
+) -> Vec<u8> {
+    // default resolution of the interface is microseconds
+    let micros = timestamp
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_micros() as u64;
+    let flags: u32 = match direction {
+        CaptureDirection::Rx => 0b01,
+        CaptureDirection::Tx => 0b10,
+    };
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_le_bytes());
+    body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+    body.extend_from_slice(&(micros as u32).to_le_bytes());
+    body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    body.extend_from_slice(data);
+    body.resize(body.len() + (4 - data.len() % 4) % 4, 0);
+    // epb_flags option followed by opt_endofopt
+    body.extend_from_slice(&2u16.to_le_bytes());
+    body.extend_from_slice(&4u16.to_le_bytes());
+    body.extend_from_slice(&flags.to_le_bytes());
+    body.extend_from_slice(&[0; 4]);
+    block(0x0000_0006, &body)
+}
+
+/// synthesize an IP packet carrying a TCP segment with `data` at `offset` bytes into the record
+fn tcp_packet(record: &CaptureRecord, offset: u64, data: &[u8]) -> Vec<u8> {
+    let local = record
+        .local
+        .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 20000));
+    let remote = record
+        .remote
+        .unwrap_or_else(|| SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)), 20000));
+    let (source, destination, seq, ack) = match record.direction {
+        CaptureDirection::Tx => (local, remote, record.sent + offset, record.received),
+        CaptureDirection::Rx => (remote, local, record.received + offset, record.sent),
+    };
+
+    let mut segment = Vec::with_capacity(20 + data.len());
+    segment.extend_from_slice(&source.port().to_be_bytes());
+    segment.extend_from_slice(&destination.port().to_be_bytes());
+    // initial sequence numbers of zero, as if the SYN had consumed one
+    segment.extend_from_slice(&((seq + 1) as u32).to_be_bytes());
+    segment.extend_from_slice(&((ack + 1) as u32).to_be_bytes());
+    // 20 byte header, PSH + ACK
+    segment.extend_from_slice(&[0x50, 0x18]);
+    segment.extend_from_slice(&0xFFFFu16.to_be_bytes());
+    segment.extend_from_slice(&[0; 4]);
+    segment.extend_from_slice(data);
+
+    match (source.ip(), destination.ip()) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            let mut pseudo = Vec::with_capacity(12);
+            pseudo.extend_from_slice(&source.octets());
+            pseudo.extend_from_slice(&destination.octets());
+            pseudo.extend_from_slice(&[0, 6]);
+            pseudo.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+            let sum = checksum(&[&pseudo, &segment]);
+            segment[16..18].copy_from_slice(&sum.to_be_bytes());
+
+            let mut packet = Vec::with_capacity(20 + segment.len());
+            packet.extend_from_slice(&[0x45, 0]);
+            packet.extend_from_slice(&((20 + segment.len()) as u16).to_be_bytes());
+            // identification, don't fragment, TTL 64, TCP
+            packet.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+            packet.extend_from_slice(&source.octets());
+            packet.extend_from_slice(&destination.octets());
+            let sum = checksum(&[&packet]);
+            packet[10..12].copy_from_slice(&sum.to_be_bytes());
+            packet.extend_from_slice(&segment);
+            packet
+        }
+        (source, destination) => {
+            let source = to_ipv6(source).octets();
+            let destination = to_ipv6(destination).octets();
+            let mut pseudo = Vec::with_capacity(40);
+            pseudo.extend_from_slice(&source);
+            pseudo.extend_from_slice(&destination);
+            pseudo.extend_from_slice(&(segment.len() as u32).to_be_bytes());
+            pseudo.extend_from_slice(&[0, 0, 0, 6]);
+            let sum = checksum(&[&pseudo, &segment]);
+            segment[16..18].copy_from_slice(&sum.to_be_bytes());
+
+            let mut packet = Vec::with_capacity(40 + segment.len());
+            packet.extend_from_slice(&[0x60, 0, 0, 0]);
+            packet.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+            // TCP, hop limit 64
+            packet.extend_from_slice(&[6, 64]);
+            packet.extend_from_slice(&source);
+            packet.extend_from_slice(&destination);
+            packet.extend_from_slice(&segment);
+            packet
+        }
+    }
+}
+
+fn to_ipv6(addr: IpAddr) -> std::net::Ipv6Addr {
+    match addr {
+        IpAddr::V4(x) => x.to_ipv6_mapped(),
+        IpAddr::V6(x) => x,
+    }
+}
+
+/// internet checksum over the concatenation of the provided slices
+fn checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum: u32 = 0;
+    let mut bytes = parts.iter().flat_map(|x| x.iter().copied());
+    while let Some(high) = bytes.next() {
+        let low = bytes.next().unwrap_or(0);
+        sum += u16::from_be_bytes([high, low]) as u32;
+    }
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(direction: CaptureDirection, data: &[u8]) -> CaptureRecord<'_> {
+        CaptureRecord {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_micros(0x1_0000_0002),
+            direction,
+            local: None,
+            remote: None,
+            sent: 4,
+            received: 7,
+            data,
+        }
+    }
+
+    // split a file into (type, body) blocks, checking the redundant lengths
+    fn blocks(data: &[u8]) -> Vec<(u32, Vec<u8>)> {
+        let mut blocks = Vec::new();
+        let mut data = data;
+        while !data.is_empty() {
+            let block_type = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+            let length = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+            assert_eq!(length % 4, 0);
+            assert_eq!(&data[4..8], &data[length - 4..length]);
+            blocks.push((block_type, data[8..length - 4].to_vec()));
+            data = &data[length..];
+        }
+        blocks
+    }
+
+    #[test]
+    fn synthesizes_ipv4_tcp_packets() {
+        let packet = tcp_packet(
+            &record(CaptureDirection::Rx, &[0x05, 0x64]),
+            0,
+            &[0x05, 0x64],
+        );
+        assert_eq!(packet.len(), 42);
+        // IP header checksum validates
+        assert_eq!(checksum(&[&packet[0..20]]), 0);
+        // received from the remote address
+        assert_eq!(&packet[12..16], &[127, 0, 0, 2]);
+        assert_eq!(&packet[16..20], &[127, 0, 0, 1]);
+        // sequence follows the received bytes, acknowledging the sent bytes
+        assert_eq!(&packet[24..28], &8u32.to_be_bytes());
+        assert_eq!(&packet[28..32], &5u32.to_be_bytes());
+        assert_eq!(&packet[40..42], &[0x05, 0x64]);
+    }
+
+    #[test]
+    fn writes_pcapng_blocks_and_rotates_when_full() {
+        let dir = std::env::temp_dir().join(format!("dnp3-pcap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("capture.pcapng");
+
+        // header (28 + 20) plus two 48 byte packets
+        let mut sink = PcapFileSink::new(&path, PcapLinkType::User(147), 144, 1).unwrap();
+        for _ in 0..3 {
+            sink.record(&record(CaptureDirection::Tx, &[0xAA, 0xBB, 0xCC]));
+        }
+
+        // flushed when rotated
+        let rotated = blocks(&std::fs::read(sink.rotated_path(1)).unwrap());
+        let types: Vec<u32> = rotated.iter().map(|(x, _)| *x).collect();
+        assert_eq!(types, vec![0x0A0D_0D0A, 1, 6, 6]);
+        assert_eq!(&rotated[1].1[0..2], &147u16.to_le_bytes());
+
+        let packet = &rotated[2].1;
+        assert_eq!(&packet[4..8], &1u32.to_le_bytes());
+        assert_eq!(&packet[8..12], &2u32.to_le_bytes());
+        assert_eq!(&packet[12..16], &3u32.to_le_bytes());
+        assert_eq!(&packet[20..24], &[0xAA, 0xBB, 0xCC, 0x00]);
+        // outbound epb_flags
+        assert_eq!(&packet[24..32], &[2, 0, 4, 0, 2, 0, 0, 0]);
+
+        // flushed when dropped
+        drop(sink);
+        let current = blocks(&std::fs::read(&path).unwrap());
+        let types: Vec<u32> = current.iter().map(|(x, _)| *x).collect();
+        assert_eq!(types, vec![0x0A0D_0D0A, 1, 6]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
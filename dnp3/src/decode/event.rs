@@ -0,0 +1,222 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::capture::{CaptureDirection, CaptureRecord, CaptureSink, SharedCaptureSink};
+use crate::decode::{DecodedItem, Decoder};
+
+/// Structured record of an item decoded from the traffic of a master channel or outstation
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct DecodeEvent {
+    /// time at which the bytes completing the item were read or written
+    pub timestamp: SystemTime,
+    /// direction of the traffic
+    pub direction: CaptureDirection,
+    /// local address of the connection, only available for TCP
+    pub local: Option<SocketAddr>,
+    /// remote address of the connection, only available for TCP
+    pub remote: Option<SocketAddr>,
+    /// decoded frame, segment or fragment
+    pub item: DecodedItem,
+}
+
+/// Receives the structured events produced by a [DecodeEventCapture]
+///
+/// Unlike the output controlled by [DecodeLevel](crate::decode::DecodeLevel), the events don't
+/// need to be parsed from log messages. The traffic is parsed independently of the decode level,
+/// so events are produced even when nothing is logged. With the `serialization` feature, they can be
+/// serialized with serde, e.g. to JSON.
+pub trait DecodeEventSink: Send + 'static {
+    /// called for every frame, segment and fragment decoded from the traffic
+    fn on_event(&mut self, event: DecodeEvent);
+}
+
+impl DecodeEventSink for Box<dyn DecodeEventSink> {
+    fn on_event(&mut self, event: DecodeEvent) {
+        self.as_mut().on_event(event)
+    }
+}
+
+/// [CaptureSink] that decodes the captured traffic and forwards it to a [DecodeEventSink]
+///
+/// `MasterChannel::set_decode_event_sink` and `OutstationHandle::set_decode_event_sink` install one
+/// alongside any sink installed with `set_capture_sink`. It may also be combined with other sinks
+/// by user code. Each direction is decoded separately and the decoders are reset whenever a new
+/// connection starts.
+pub struct DecodeEventCapture<S>
+where
+    S: DecodeEventSink,
+{
+    sink: S,
+    rx: Decoder,
+    tx: Decoder,
+}
+
+impl<S> DecodeEventCapture<S>
+where
+    S: DecodeEventSink,
+{
+    /// Create a capture sink forwarding the decoded events to `sink`
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            rx: Decoder::new(),
+            tx: Decoder::new(),
+        }
+    }
+}
+
+/// wrap a decode event sink so that it can be installed alongside the capture sink
+pub(crate) fn shared_event_sink(sink: Box<dyn DecodeEventSink>) -> SharedCaptureSink {
+    let sink: Box<dyn CaptureSink> = Box::new(DecodeEventCapture::new(sink));
+    Arc::new(Mutex::new(sink))
+}
+
+impl<S> CaptureSink for DecodeEventCapture<S>
+where
+    S: DecodeEventSink,
+{
+    fn record(&mut self, record: &CaptureRecord) {
+        let (decoder, offset) = match record.direction {
+            CaptureDirection::Rx => (&mut self.rx, record.received),
+            CaptureDirection::Tx => (&mut self.tx, record.sent),
+        };
+
+        // the first bytes of a connection never continue a frame from the previous one
+        if offset == 0 {
+            decoder.reset();
+        }
+
+        for item in decoder.decode(record.data) {
+            self.sink.on_event(DecodeEvent {
+                timestamp: record.timestamp,
+                direction: record.direction,
+                local: record.local,
+                remote: record.remote,
+                item,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::{channel, Sender};
+
+    use crate::app::measurement::{Binary, Flags, Time};
+    use crate::app::{FunctionCode, Timestamp, Variation};
+    use crate::decode::{DecodedValues, Indexed};
+    use crate::link::header::{AnyAddress, Header};
+
+    impl DecodeEventSink for Sender<DecodeEvent> {
+        fn on_event(&mut self, event: DecodeEvent) {
+            self.send(event).unwrap();
+        }
+    }
+
+    // response containing a CTO followed by a g2v3 event for index 7
+    const RESPONSE: &[u8] = &[
+        0xC0, 0x81, 0x00, 0x00, 0x33, 0x01, 0x07, 0x01, 0xE8, 0x03, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x03, 0x17, 0x01, 0x07, 0x01, 0x0A, 0x00,
+    ];
+
+    // single frame sent from outstation 1024 to master 1
+    fn frame() -> Vec<u8> {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = crate::util::cursor::WriteCursor::new(&mut buffer);
+        let header =
+            Header::unconfirmed_user_data(false, AnyAddress::from(1), AnyAddress::from(1024));
+        crate::link::format::format_data_frame(
+            header,
+            crate::link::format::Payload::new(0xC0, RESPONSE),
+            &mut cursor,
+        )
+        .unwrap()
+        .frame
+        .to_vec()
+    }
+
+    fn record(data: &[u8], received: u64) -> CaptureRecord<'_> {
+        CaptureRecord {
+            timestamp: SystemTime::UNIX_EPOCH,
+            direction: CaptureDirection::Rx,
+            local: None,
+            remote: None,
+            sent: 0,
+            received,
+            data,
+        }
+    }
+
+    #[test]
+    fn forwards_typed_values_of_captured_response() {
+        let (tx, rx) = channel();
+        let mut capture = DecodeEventCapture::new(tx);
+
+        let frame = frame();
+        capture.record(&record(&frame[..20], 0));
+        assert!(rx.try_recv().is_err());
+        capture.record(&record(&frame[20..], 20));
+
+        let events: Vec<DecodeEvent> = rx.try_iter().collect();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|x| x.direction == CaptureDirection::Rx));
+
+        let fragment = match &events[2].item {
+            DecodedItem::Fragment(x) => x,
+            x => panic!("unexpected item: {:?}", x),
+        };
+        assert_eq!(fragment.function, FunctionCode::Response);
+        assert_eq!(fragment.headers.len(), 2);
+        assert_eq!(
+            fragment.headers[0].values,
+            DecodedValues::Time(vec![Time::Synchronized(Timestamp::new(1000))])
+        );
+        assert_eq!(fragment.headers[1].variation, Variation::Group2Var3);
+        assert_eq!(
+            fragment.headers[1].values,
+            DecodedValues::Binary(vec![Indexed {
+                index: 7,
+                value: Binary::new(
+                    false,
+                    Flags::ONLINE,
+                    Time::Synchronized(Timestamp::new(1010))
+                ),
+            }])
+        );
+    }
+
+    #[test]
+    fn resets_decoder_when_a_new_connection_starts() {
+        let (tx, rx) = channel();
+        let mut capture = DecodeEventCapture::new(tx);
+
+        // partial frame from a previous connection
+        let frame = frame();
+        capture.record(&record(&frame[..20], 0));
+        capture.record(&record(&frame, 0));
+
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn events_survive_a_json_round_trip() {
+        let (tx, rx) = channel();
+        let mut capture = DecodeEventCapture::new(tx);
+        capture.record(&record(&frame(), 0));
+
+        let events: Vec<DecodeEvent> = rx.try_iter().collect();
+        assert_eq!(events.len(), 3);
+
+        let json = serde_json::to_string(&events).unwrap();
+        let parsed: Vec<DecodeEvent> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, events);
+    }
+}
@@ -0,0 +1,595 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::capture::CaptureDirection;
+use crate::decode::{DecodedItem, Decoder};
+
+/// Stream of bytes within a capture file that an item was decoded from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum CaptureFlow {
+    /// one direction of a TCP connection
+    Tcp {
+        /// address of the sender
+        source: SocketAddr,
+        /// address of the receiver
+        destination: SocketAddr,
+    },
+    /// packets of the raw link type passed to [decode_capture_file], with the direction if recorded
+    Raw(Option<CaptureDirection>),
+}
+
+/// Item decoded from a capture file
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct CapturedItem {
+    /// timestamp of the packet that completed the item
+    pub timestamp: SystemTime,
+    /// stream the item was decoded from
+    pub flow: CaptureFlow,
+    /// the decoded item
+    pub item: DecodedItem,
+}
+
+/// Decode the DNP3 traffic in a pcap or pcapng file
+///
+/// Packets with Ethernet, Linux cooked (SLL) or raw IPv4/IPv6 link types are decoded as TCP, with
+/// each direction of each connection decoded separately. Packets whose link type matches `raw_link_type`
+/// (e.g. the DLT used with [PcapLinkType::User](crate::capture::PcapLinkType::User)) are decoded
+/// as raw DNP3 bytes. Packets of any other link type are ignored.
+pub fn decode_capture_file<P: AsRef<Path>>(
+    path: P,
+    raw_link_type: Option<u16>,
+) -> std::io::Result<Vec<CapturedItem>> {
+    let data = std::fs::read(path)?;
+    let mut decoder = CaptureDecoder::new(raw_link_type);
+    if data.len() >= 4 && data[0..4] == [0x0A, 0x0D, 0x0D, 0x0A] {
+        read_pcapng(&data, &mut decoder)?;
+    } else {
+        read_pcap(&data, &mut decoder)?;
+    }
+    Ok(decoder.items)
+}
+
+const LINKTYPE_ETHERNET: u16 = 1;
+const LINKTYPE_RAW: u16 = 101;
+const LINKTYPE_LINUX_SLL: u16 = 113;
+const LINKTYPE_IPV4: u16 = 228;
+const LINKTYPE_IPV6: u16 = 229;
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
+
+/// reads integers from a capture file in the byte order of the file or section
+#[derive(Copy, Clone)]
+struct Endian {
+    big: bool,
+}
+
+impl Endian {
+    fn u16(self, data: &[u8], pos: usize) -> std::io::Result<u16> {
+        let bytes = data
+            .get(pos..pos + 2)
+            .ok_or_else(|| invalid("truncated capture file"))?;
+        let bytes = [bytes[0], bytes[1]];
+        Ok(if self.big {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(self, data: &[u8], pos: usize) -> std::io::Result<u32> {
+        let bytes = data
+            .get(pos..pos + 4)
+            .ok_or_else(|| invalid("truncated capture file"))?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(if self.big {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+fn slice(data: &[u8], start: usize, length: usize) -> std::io::Result<&[u8]> {
+    data.get(start..start + length)
+        .ok_or_else(|| invalid("truncated capture file"))
+}
+
+fn read_pcap(data: &[u8], decoder: &mut CaptureDecoder) -> std::io::Result<()> {
+    let (endian, nanos) = match data.get(0..4) {
+        Some([0xD4, 0xC3, 0xB2, 0xA1]) => (Endian { big: false }, false),
+        Some([0xA1, 0xB2, 0xC3, 0xD4]) => (Endian { big: true }, false),
+        Some([0x4D, 0x3C, 0xB2, 0xA1]) => (Endian { big: false }, true),
+        Some([0xA1, 0xB2, 0x3C, 0x4D]) => (Endian { big: true }, true),
+        _ => return Err(invalid("not a pcap or pcapng file")),
+    };
+    let link_type = endian.u32(data, 20)? as u16;
+
+    let mut pos = 24;
+    while pos < data.len() {
+        let seconds = endian.u32(data, pos)? as u64;
+        let fraction = endian.u32(data, pos + 4)? as u64;
+        let length = endian.u32(data, pos + 8)? as usize;
+        let packet = slice(data, pos + 16, length)?;
+        let offset = if nanos {
+            Duration::from_nanos(fraction)
+        } else {
+            Duration::from_micros(fraction)
+        };
+        let timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(seconds) + offset;
+        decoder.on_packet(timestamp, link_type, None, packet);
+        pos += 16 + length;
+    }
+
+    Ok(())
+}
+
+/// link type and timestamp resolution of a pcapng interface
+#[derive(Copy, Clone)]
+struct Interface {
+    link_type: u16,
+    units_per_second: u64,
+}
+
+fn read_pcapng(data: &[u8], decoder: &mut CaptureDecoder) -> std::io::Result<()> {
+    let mut endian = Endian { big: false };
+    let mut interfaces: Vec<Interface> = Vec::new();
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let block_type = endian.u32(data, pos)?;
+        if block_type == 0x0A0D_0D0A {
+            // each section defines its byte order and interfaces
+            endian = match slice(data, pos + 8, 4)? {
+                [0x4D, 0x3C, 0x2B, 0x1A] => Endian { big: false },
+                [0x1A, 0x2B, 0x3C, 0x4D] => Endian { big: true },
+                _ => return Err(invalid("invalid pcapng byte-order magic")),
+            };
+            interfaces.clear();
+        }
+
+        let length = endian.u32(data, pos + 4)? as usize;
+        if length < 12 || length & 0b11 != 0 {
+            return Err(invalid("invalid pcapng block length"));
+        }
+        let body = slice(data, pos + 8, length - 12)?;
+
+        match block_type {
+            // interface description
+            0x0000_0001 => interfaces.push(Interface {
+                link_type: endian.u16(body, 0)?,
+                units_per_second: options(endian, body, 8)?
+                    .get(&9)
+                    .and_then(|x| x.first())
+                    .map(|x| resolution(*x))
+                    .unwrap_or(1_000_000),
+            }),
+            // enhanced packet
+            0x0000_0006 => {
+                let interface = interfaces
+                    .get(endian.u32(body, 0)? as usize)
+                    .ok_or_else(|| invalid("packet references an unknown interface"))?;
+                let ticks = ((endian.u32(body, 4)? as u64) << 32) | endian.u32(body, 8)? as u64;
+                let captured = endian.u32(body, 12)? as usize;
+                let packet = slice(body, 20, captured)?;
+                let direction = match options(endian, body, 20 + ((captured + 3) & !3))?.get(&2) {
+                    Some(flags) if flags.len() == 4 => match endian.u32(flags, 0)? & 0b11 {
+                        0b01 => Some(CaptureDirection::Rx),
+                        0b10 => Some(CaptureDirection::Tx),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let timestamp = SystemTime::UNIX_EPOCH
+                    .checked_add(
+                        Duration::from_secs(ticks / interface.units_per_second)
+                            + Duration::from_nanos(
+                                ((ticks % interface.units_per_second) as u128 * 1_000_000_000
+                                    / interface.units_per_second as u128)
+                                    as u64,
+                            ),
+                    )
+                    .ok_or_else(|| invalid("packet timestamp is out of range"))?;
+                decoder.on_packet(timestamp, interface.link_type, direction, packet);
+            }
+            // other blocks don't contain packets with timestamps
+            _ => {}
+        }
+
+        pos += length;
+    }
+
+    Ok(())
+}
+
+/// parse the options of a pcapng block body starting at `pos`
+fn options(endian: Endian, body: &[u8], pos: usize) -> std::io::Result<HashMap<u16, &[u8]>> {
+    let mut options = HashMap::new();
+    let mut pos = pos;
+    while pos + 4 <= body.len() {
+        let code = endian.u16(body, pos)?;
+        let length = endian.u16(body, pos + 2)? as usize;
+        if code == 0 {
+            break;
+        }
+        options.insert(code, slice(body, pos + 4, length)?);
+        pos += 4 + ((length + 3) & !3);
+    }
+    Ok(options)
+}
+
+/// number of timestamp units per second for an if_tsresol value
+fn resolution(value: u8) -> u64 {
+    let exponent = (value & 0x7F) as u32;
+    let base: u64 = if value & 0x80 == 0 { 10 } else { 2 };
+    base.checked_pow(exponent).unwrap_or(1_000_000)
+}
+
+/// state of one direction of a TCP connection
+struct TcpFlow {
+    decoder: Decoder,
+    next_seq: Option<u32>,
+}
+
+struct CaptureDecoder {
+    raw_link_type: Option<u16>,
+    raw: Decoder,
+    flows: HashMap<(SocketAddr, SocketAddr), TcpFlow>,
+    items: Vec<CapturedItem>,
+}
+
+impl CaptureDecoder {
+    fn new(raw_link_type: Option<u16>) -> Self {
+        Self {
+            raw_link_type,
+            raw: Decoder::new(),
+            flows: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, timestamp: SystemTime, flow: CaptureFlow, items: Vec<DecodedItem>) {
+        self.items
+            .extend(items.into_iter().map(|item| CapturedItem {
+                timestamp,
+                flow,
+                item,
+            }));
+    }
+
+    fn on_packet(
+        &mut self,
+        timestamp: SystemTime,
+        link_type: u16,
+        direction: Option<CaptureDirection>,
+        packet: &[u8],
+    ) {
+        if Some(link_type) == self.raw_link_type {
+            let items = self.raw.decode(packet);
+            self.push(timestamp, CaptureFlow::Raw(direction), items);
+            return;
+        }
+
+        let ip = match link_type {
+            LINKTYPE_ETHERNET => ethernet_payload(packet),
+            LINKTYPE_LINUX_SLL => packet.get(16..),
+            LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => Some(packet),
+            _ => None,
+        };
+
+        if let Some(segment) = ip.and_then(parse_ip) {
+            self.on_tcp_segment(timestamp, segment);
+        }
+    }
+
+    fn on_tcp_segment(&mut self, timestamp: SystemTime, segment: TcpSegment) {
+        let flow = self
+            .flows
+            .entry((segment.source, segment.destination))
+            .or_insert_with(|| TcpFlow {
+                decoder: Decoder::new(),
+                next_seq: None,
+            });
+
+        // the SYN flag consumes one sequence number
+        let seq = if segment.syn {
+            flow.decoder.reset();
+            segment.seq.wrapping_add(1)
+        } else {
+            segment.seq
+        };
+
+        let mut data = segment.payload;
+        if let Some(next) = flow.next_seq {
+            let offset = seq.wrapping_sub(next) as i32;
+            if offset < 0 {
+                // skip retransmitted data
+                let overlap = offset.wrapping_neg() as u32 as usize;
+                if overlap >= data.len() {
+                    return;
+                }
+                data = &data[overlap..];
+            } else if offset > 0 {
+                tracing::warn!(
+                    "{} bytes missing from capture of {} -> {}",
+                    offset,
+                    segment.source,
+                    segment.destination
+                );
+                flow.decoder.reset();
+            }
+        }
+        flow.next_seq = Some(seq.wrapping_add(segment.payload.len() as u32));
+
+        if data.is_empty() {
+            return;
+        }
+
+        let items = flow.decoder.decode(data);
+        let flow = CaptureFlow::Tcp {
+            source: segment.source,
+            destination: segment.destination,
+        };
+        self.push(timestamp, flow, items);
+    }
+}
+
+fn ethernet_payload(packet: &[u8]) -> Option<&[u8]> {
+    let mut pos = 12;
+    let mut ether_type = u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]);
+    // skip any VLAN tags
+    while ether_type == 0x8100 || ether_type == 0x88A8 {
+        pos += 4;
+        ether_type = u16::from_be_bytes([*packet.get(pos)?, *packet.get(pos + 1)?]);
+    }
+    match ether_type {
+        0x0800 | 0x86DD => packet.get(pos + 2..),
+        _ => None,
+    }
+}
+
+struct TcpSegment<'a> {
+    source: SocketAddr,
+    destination: SocketAddr,
+    seq: u32,
+    syn: bool,
+    payload: &'a [u8],
+}
+
+fn parse_ip(packet: &[u8]) -> Option<TcpSegment<'_>> {
+    let first = *packet.first()?;
+    let (source, destination, tcp) = match first >> 4 {
+        4 => {
+            let header_length = ((first & 0x0F) as usize) * 4;
+            let total_length = u16::from_be_bytes([*packet.get(2)?, *packet.get(3)?]) as usize;
+            if *packet.get(9)? != 6 {
+                return None;
+            }
+            let mut source = [0; 4];
+            source.copy_from_slice(packet.get(12..16)?);
+            let mut destination = [0; 4];
+            destination.copy_from_slice(packet.get(16..20)?);
+            // the length excludes any padding added by the link layer
+            let end = std::cmp::min(total_length, packet.len());
+            (
+                IpAddr::V4(Ipv4Addr::from(source)),
+                IpAddr::V4(Ipv4Addr::from(destination)),
+                packet.get(header_length..end)?,
+            )
+        }
+        6 => {
+            let payload_length = u16::from_be_bytes([*packet.get(4)?, *packet.get(5)?]) as usize;
+            // extension headers are not supported
+            if *packet.get(6)? != 6 {
+                return None;
+            }
+            let mut source = [0; 16];
+            source.copy_from_slice(packet.get(8..24)?);
+            let mut destination = [0; 16];
+            destination.copy_from_slice(packet.get(24..40)?);
+            let end = std::cmp::min(40 + payload_length, packet.len());
+            (
+                IpAddr::V6(Ipv6Addr::from(source)),
+                IpAddr::V6(Ipv6Addr::from(destination)),
+                packet.get(40..end)?,
+            )
+        }
+        _ => return None,
+    };
+
+    let source_port = u16::from_be_bytes([*tcp.first()?, *tcp.get(1)?]);
+    let destination_port = u16::from_be_bytes([*tcp.get(2)?, *tcp.get(3)?]);
+    let seq = u32::from_be_bytes([*tcp.get(4)?, *tcp.get(5)?, *tcp.get(6)?, *tcp.get(7)?]);
+    let data_offset = ((*tcp.get(12)? >> 4) as usize) * 4;
+    let syn = *tcp.get(13)? & 0x02 != 0;
+
+    Some(TcpSegment {
+        source: SocketAddr::new(source, source_port),
+        destination: SocketAddr::new(destination, destination_port),
+        seq,
+        syn,
+        payload: tcp.get(data_offset..)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{CaptureRecord, CaptureSink, PcapFileSink, PcapLinkType};
+    use crate::link::header::{AnyAddress, Header};
+
+    fn frame(app: &[u8]) -> Vec<u8> {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = crate::util::cursor::WriteCursor::new(&mut buffer);
+        let header =
+            Header::unconfirmed_user_data(true, AnyAddress::from(1024), AnyAddress::from(1));
+        crate::link::format::format_data_frame(
+            header,
+            crate::link::format::Payload::new(0xC0, app),
+            &mut cursor,
+        )
+        .unwrap()
+        .frame
+        .to_vec()
+    }
+
+    fn record(
+        direction: CaptureDirection,
+        sent: u64,
+        received: u64,
+        data: &[u8],
+    ) -> CaptureRecord<'_> {
+        CaptureRecord {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(10),
+            direction,
+            local: None,
+            remote: None,
+            sent,
+            received,
+            data,
+        }
+    }
+
+    fn fragments(items: &[CapturedItem]) -> Vec<(CaptureFlow, Vec<u8>)> {
+        items
+            .iter()
+            .filter_map(|x| match &x.item {
+                DecodedItem::Fragment(fragment) => Some((x.flow, fragment.data().to_vec())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decodes_files_written_by_the_pcap_sink() {
+        let dir = std::env::temp_dir().join(format!("dnp3-decode-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let request = frame(&[0xC0, 0x01, 0x3C, 0x01, 0x06]);
+        let response = frame(&[0xC0, 0x81, 0x00, 0x00]);
+
+        let tcp = dir.join("tcp.pcapng");
+        let mut sink = PcapFileSink::new(&tcp, PcapLinkType::Tcp, 1 << 20, 0).unwrap();
+        // the request is split across two writes
+        sink.record(&record(CaptureDirection::Tx, 0, 0, &request[..7]));
+        sink.record(&record(CaptureDirection::Tx, 7, 0, &request[7..]));
+        sink.record(&record(
+            CaptureDirection::Rx,
+            request.len() as u64,
+            0,
+            &response,
+        ));
+        drop(sink);
+
+        let local: SocketAddr = "127.0.0.1:20000".parse().unwrap();
+        let remote: SocketAddr = "127.0.0.2:20000".parse().unwrap();
+        let items = decode_capture_file(&tcp, None).unwrap();
+        assert_eq!(
+            fragments(&items),
+            vec![
+                (
+                    CaptureFlow::Tcp {
+                        source: local,
+                        destination: remote
+                    },
+                    vec![0xC0, 0x01, 0x3C, 0x01, 0x06]
+                ),
+                (
+                    CaptureFlow::Tcp {
+                        source: remote,
+                        destination: local
+                    },
+                    vec![0xC0, 0x81, 0x00, 0x00]
+                ),
+            ]
+        );
+        assert_eq!(
+            items[0].timestamp,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(10)
+        );
+
+        let raw = dir.join("raw.pcapng");
+        let mut sink = PcapFileSink::new(&raw, PcapLinkType::User(147), 1 << 20, 0).unwrap();
+        sink.record(&record(CaptureDirection::Rx, 0, 0, &response));
+        drop(sink);
+
+        assert!(decode_capture_file(&raw, None).unwrap().is_empty());
+        assert_eq!(
+            fragments(&decode_capture_file(&raw, Some(147)).unwrap()),
+            vec![(
+                CaptureFlow::Raw(Some(CaptureDirection::Rx)),
+                vec![0xC0, 0x81, 0x00, 0x00]
+            )]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ignores_truncated_ip_packets() {
+        // IPv4 header with protocol == TCP, followed by the first bytes of a TCP header
+        let ipv4: &[u8] = &[
+            0x45, 0x00, 0x00, 0x28, 0x00, 0x00, 0x00, 0x00, 0x40, 0x06, 0x00, 0x00, 127, 0, 0, 1,
+            127, 0, 0, 2, 0x4E, 0x20, 0x4E, 0x20,
+        ];
+        for length in 0..ipv4.len() {
+            assert!(parse_ip(&ipv4[..length]).is_none());
+        }
+
+        // IPv6 header with next header == TCP
+        let mut ipv6 = vec![0x60, 0x00, 0x00, 0x00, 0x00, 0x14, 0x06, 0x40];
+        ipv6.extend_from_slice(&[0; 32]);
+        for length in 0..ipv6.len() {
+            assert!(parse_ip(&ipv6[..length]).is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_pcapng_timestamps_that_overflow() {
+        fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+            let length = (12 + body.len() as u32).to_le_bytes();
+            let mut block = block_type.to_le_bytes().to_vec();
+            block.extend_from_slice(&length);
+            block.extend_from_slice(body);
+            block.extend_from_slice(&length);
+            block
+        }
+
+        let mut data = block(
+            0x0A0D_0D0A,
+            &[
+                0x4D, 0x3C, 0x2B, 0x1A, 0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+                0xFF, 0xFF,
+            ],
+        );
+        // timestamps are in seconds (if_tsresol = 0)
+        data.extend(block(
+            0x0000_0001,
+            &[
+                147, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x01, 0x00, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+        ));
+        // empty packet with the maximum timestamp
+        data.extend(block(
+            0x0000_0006,
+            &[
+                0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ],
+        ));
+
+        let err = read_pcapng(&data, &mut CaptureDecoder::new(Some(147))).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
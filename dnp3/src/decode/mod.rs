@@ -1,3 +1,13 @@
+pub use event::*;
+pub use file::*;
+pub use offline::*;
+pub use values::*;
+
+mod event;
+mod file;
+mod offline;
+mod values;
+
 /// Controls the decoding of transmitted and received data at the application, transport, and link layer
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct DecodeLevel {
@@ -47,7 +57,9 @@ pub enum LinkDecodeLevel {
 }
 
 /// Controls how data transmitted at the physical layer (TCP, serial, etc) is logged
-#[derive(Copy, Clone, Debug, PartialEq)]
+///
+/// Levels are ordered from the least to the most verbose.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PhysDecodeLevel {
     /// Log nothing
     Nothing,
@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+
+use crate::app::measurement::Time;
+use crate::app::parse::parser::{HeaderDetails, ObjectHeader, ParsedFragment};
+use crate::app::{
+    ControlField, FunctionCode, HeaderParseError, Iin, ObjectParseError, QualifierCode, Variation,
+};
+use crate::decode::{AppDecodeLevel, DecodedValues, LinkDecodeLevel, TransportDecodeLevel};
+use crate::link::display::LinkDisplay;
+use crate::link::header::{AnyAddress, FrameInfo, FrameType, Header};
+use crate::link::parser::{FramePayload, Parser};
+use crate::link::{EndpointAddress, LinkErrorMode, LinkFunction};
+use crate::transport::real::assembler::{Assembler, AssemblyState};
+use crate::transport::real::display::SegmentDisplay;
+use crate::util::cursor::ReadCursor;
+
+/// Item produced by a [Decoder]
+///
+/// A data frame produces a [DecodedItem::Frame] followed by a [DecodedItem::Segment], and
+/// the final segment of a fragment is then followed by a [DecodedItem::Fragment] or a
+/// [DecodedItem::MalformedFragment].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum DecodedItem {
+    /// link-layer frame with a valid header and body
+    Frame(LinkFrame),
+    /// transport segment contained in a data frame
+    Segment(TransportSegment),
+    /// application fragment reassembled from one or more segments
+    Fragment(AppFragment),
+    /// reassembled application fragment whose header could not be parsed
+    MalformedFragment(MalformedFragment),
+}
+
+/// Link-layer frame decoded from a byte stream
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct LinkFrame {
+    /// function code of the frame
+    pub function: LinkFunction,
+    /// DIR bit - set if the frame was sent by a master
+    pub master: bool,
+    /// frame count bit
+    pub fcb: bool,
+    /// frame count valid bit (or DFC for secondary frames)
+    pub fcv: bool,
+    /// destination address
+    pub destination: u16,
+    /// source address
+    pub source: u16,
+    /// user data of the frame with the CRCs removed
+    pub payload: Vec<u8>,
+}
+
+/// Transport segment decoded from the user data of a link frame
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct TransportSegment {
+    /// FIN bit - set if the final segment of a fragment
+    pub fin: bool,
+    /// FIR bit - set if the first segment of a fragment
+    pub fir: bool,
+    /// sequence number
+    pub seq: u8,
+    /// application data carried by the segment
+    pub payload: Vec<u8>,
+}
+
+/// Application fragment reassembled from transport segments
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct AppFragment {
+    /// link address of the device that sent the fragment
+    pub source: u16,
+    /// link address to which the fragment was sent
+    pub destination: u16,
+    /// application control field
+    pub control: ControlField,
+    /// application function code
+    pub function: FunctionCode,
+    /// internal indications, only present in responses
+    pub iin: Option<Iin>,
+    /// object headers that were parsed successfully
+    pub headers: Vec<DecodedHeader>,
+    /// error that stopped the parsing of the object headers, if any
+    pub error: Option<ObjectParseError>,
+    raw: Vec<u8>,
+}
+
+/// Reassembled application fragment whose header could not be parsed
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct MalformedFragment {
+    /// link address of the device that sent the fragment
+    pub source: u16,
+    /// link address to which the fragment was sent
+    pub destination: u16,
+    /// error encountered while parsing the header
+    pub error: HeaderParseError,
+    /// raw bytes of the fragment
+    pub data: Vec<u8>,
+}
+
+/// Object header within an [AppFragment]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct DecodedHeader {
+    /// group and variation of the objects
+    pub variation: Variation,
+    /// qualifier code of the header
+    pub qualifier: QualifierCode,
+    /// start and stop indices for range qualifiers
+    pub range: Option<(u16, u16)>,
+    /// number of objects for count qualifiers
+    pub count: Option<u16>,
+    /// values of the objects
+    pub values: DecodedValues,
+}
+
+impl LinkFrame {
+    /// Format the frame the same way it is logged with the provided decode level
+    pub fn format(&self, level: LinkDecodeLevel) -> String {
+        let header = Header::new(
+            crate::link::header::ControlField {
+                func: self.function,
+                master: self.master,
+                fcb: self.fcb,
+                fcv: self.fcv,
+            },
+            AnyAddress::from(self.destination),
+            AnyAddress::from(self.source),
+        );
+        LinkDisplay::new(header, &self.payload, level).to_string()
+    }
+}
+
+impl TransportSegment {
+    /// Format the segment the same way it is logged with the provided decode level
+    pub fn format(&self, level: TransportDecodeLevel) -> String {
+        let header = crate::transport::real::header::Header::new(
+            self.fin,
+            self.fir,
+            crate::transport::real::sequence::Sequence::new(self.seq),
+        );
+        SegmentDisplay::new(header, &self.payload, level).to_string()
+    }
+}
+
+impl AppFragment {
+    /// Raw bytes of the fragment
+    pub fn data(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Format the fragment the same way it is logged with the provided decode level
+    pub fn format(&self, level: AppDecodeLevel) -> String {
+        match ParsedFragment::parse(&self.raw) {
+            Ok(fragment) => fragment.display(level).to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+}
+
+impl DecodedHeader {
+    fn new(header: &ObjectHeader, cto: &mut Option<Time>) -> Self {
+        let (range, count) = match &header.details {
+            HeaderDetails::AllObjects(_) => (None, None),
+            HeaderDetails::OneByteStartStop(start, stop, _) => {
+                (Some((*start as u16, *stop as u16)), None)
+            }
+            HeaderDetails::TwoByteStartStop(start, stop, _) => (Some((*start, *stop)), None),
+            HeaderDetails::OneByteCount(count, _) => (None, Some(*count as u16)),
+            HeaderDetails::TwoByteCount(count, _) => (None, Some(*count)),
+            HeaderDetails::OneByteCountAndPrefix(count, _) => (None, Some(*count as u16)),
+            HeaderDetails::TwoByteCountAndPrefix(count, _) => (None, Some(*count)),
+        };
+        Self {
+            variation: header.variation,
+            qualifier: header.details.qualifier(),
+            range,
+            count,
+            values: DecodedValues::new(header, cto),
+        }
+    }
+}
+
+/// Decodes a recorded byte stream into link frames, transport segments and application fragments
+///
+/// A decoder should be used for the bytes sent in a single direction of a connection. Invalid
+/// bytes and frames are discarded the same way as [LinkErrorMode::Discard]. Fragments are
+/// reassembled separately for each pair of source and destination addresses so that captures
+/// of multi-drop links decode correctly. At most 32 partially assembled fragments are retained,
+/// discarding the least recently extended one when another pair of addresses begins a fragment.
+pub struct Decoder {
+    parser: Parser,
+    payload: FramePayload,
+    buffer: Vec<u8>,
+    // partially assembled fragments and the number of the frame that last extended them
+    assemblers: HashMap<(u16, u16), (u64, Assembler)>,
+    frame_count: u64,
+}
+
+impl Decoder {
+    // maximum size of the fragments that can be reassembled
+    const MAX_FRAGMENT_SIZE: usize = 65535;
+    // maximum number of fragments that are assembled concurrently
+    const MAX_ASSEMBLERS: usize = 32;
+
+    /// Create a decoder with no buffered data
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(LinkErrorMode::Discard),
+            payload: FramePayload::new(),
+            buffer: Vec::new(),
+            assemblers: HashMap::new(),
+            frame_count: 0,
+        }
+    }
+
+    /// Discard any partially decoded frame and fragments, e.g. after a gap in the capture
+    pub fn reset(&mut self) {
+        self.parser.reset();
+        self.buffer.clear();
+        self.assemblers.clear();
+    }
+
+    /// Decode the next bytes of the stream, returning the items that were completed by them
+    pub fn decode(&mut self, data: &[u8]) -> Vec<DecodedItem> {
+        let mut items = Vec::new();
+        self.buffer.extend_from_slice(data);
+
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let mut cursor = ReadCursor::new(&buffer);
+        // the parser discards errors, so it only stops once more bytes are required
+        while let Ok(Some(header)) = self.parser.parse(&mut cursor, &mut self.payload) {
+            self.on_frame(header, &mut items);
+        }
+        let consumed = buffer.len() - cursor.remaining();
+        buffer.drain(..consumed);
+        self.buffer = buffer;
+
+        items
+    }
+
+    fn on_frame(&mut self, header: Header, items: &mut Vec<DecodedItem>) {
+        let payload = self.payload.get();
+        items.push(DecodedItem::Frame(LinkFrame {
+            function: header.control.func,
+            master: header.control.master,
+            fcb: header.control.fcb,
+            fcv: header.control.fcv,
+            destination: header.destination.value(),
+            source: header.source.value(),
+            payload: payload.to_vec(),
+        }));
+
+        let (transport, data) = match (header.control.func, payload) {
+            (LinkFunction::PriConfirmedUserData, [transport, data @ ..])
+            | (LinkFunction::PriUnconfirmedUserData, [transport, data @ ..]) => (*transport, data),
+            _ => return,
+        };
+
+        let segment = crate::transport::real::header::Header::from_u8(transport);
+        items.push(DecodedItem::Segment(TransportSegment {
+            fin: segment.fin,
+            fir: segment.fir,
+            seq: segment.seq.value(),
+            payload: data.to_vec(),
+        }));
+
+        let source = header.source.value();
+        let destination = header.destination.value();
+        let broadcast = match header.destination {
+            AnyAddress::Broadcast(x) => Some(x),
+            _ => None,
+        };
+        let info = FrameInfo::new(EndpointAddress::raw(source), broadcast, FrameType::Data);
+        let key = (source, destination);
+        if !self.assemblers.contains_key(&key) && self.assemblers.len() >= Self::MAX_ASSEMBLERS {
+            Self::evict_oldest(&mut self.assemblers);
+        }
+
+        self.frame_count += 1;
+        let (last_frame, assembler) = self
+            .assemblers
+            .entry(key)
+            .or_insert_with(|| (0, Assembler::new(Self::MAX_FRAGMENT_SIZE)));
+        *last_frame = self.frame_count;
+
+        if let AssemblyState::ReadMore = assembler.assemble(info, segment, data) {
+            return;
+        }
+
+        if let Some(fragment) = assembler.pop() {
+            items.push(Self::on_fragment(source, destination, fragment.data));
+        }
+
+        // a completed fragment leaves nothing to assemble
+        self.assemblers.remove(&key);
+    }
+
+    fn evict_oldest(assemblers: &mut HashMap<(u16, u16), (u64, Assembler)>) {
+        let oldest = assemblers
+            .iter()
+            .min_by_key(|(_, (last_frame, _))| *last_frame)
+            .map(|(key, _)| *key);
+
+        if let Some((source, destination)) = oldest {
+            tracing::debug!(
+                "discarding partial fragment from {} to {}",
+                source,
+                destination
+            );
+            assemblers.remove(&(source, destination));
+        }
+    }
+
+    fn on_fragment(source: u16, destination: u16, data: &[u8]) -> DecodedItem {
+        match ParsedFragment::parse(data) {
+            Ok(fragment) => {
+                let mut cto = None;
+                DecodedItem::Fragment(AppFragment {
+                    source,
+                    destination,
+                    control: fragment.control,
+                    function: fragment.function,
+                    iin: fragment.iin,
+                    headers: fragment
+                        .iter_headers()
+                        .map(|x| DecodedHeader::new(&x, &mut cto))
+                        .collect(),
+                    error: fragment.objects.err(),
+                    raw: data.to_vec(),
+                })
+            }
+            Err(error) => DecodedItem::MalformedFragment(MalformedFragment {
+                source,
+                destination,
+                error,
+                data: data.to_vec(),
+            }),
+        }
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: &[u8] = &[0xC0, 0x01, 0x3C, 0x01, 0x06];
+
+    fn frame(source: u16, transport: u8, data: &[u8]) -> Vec<u8> {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = crate::util::cursor::WriteCursor::new(&mut buffer);
+        let header =
+            Header::unconfirmed_user_data(true, AnyAddress::from(1024), AnyAddress::from(source));
+        crate::link::format::format_data_frame(
+            header,
+            crate::link::format::Payload::new(transport, data),
+            &mut cursor,
+        )
+        .unwrap()
+        .frame
+        .to_vec()
+    }
+
+    // read request for class 0 from master 1 to outstation 1024 in a single frame
+    fn class_0_read() -> Vec<u8> {
+        frame(1, 0xC0, REQUEST)
+    }
+
+    #[test]
+    fn decodes_frame_segment_and_fragment_across_reads() {
+        let frame = class_0_read();
+        let mut decoder = Decoder::new();
+        // garbage before the frame is discarded
+        assert!(decoder.decode(&[0xAA, 0xBB]).is_empty());
+        assert!(decoder.decode(&frame[..9]).is_empty());
+        let items = decoder.decode(&frame[9..]);
+
+        assert_eq!(items.len(), 3);
+        match &items[0] {
+            DecodedItem::Frame(frame) => {
+                assert_eq!(frame.function, LinkFunction::PriUnconfirmedUserData);
+                assert!(frame.master);
+                assert_eq!(frame.destination, 1024);
+                assert_eq!(frame.source, 1);
+                assert_eq!(frame.payload[1..], *REQUEST);
+            }
+            x => panic!("unexpected item: {:?}", x),
+        }
+        match &items[1] {
+            DecodedItem::Segment(segment) => {
+                assert!(segment.fir && segment.fin);
+                assert_eq!(segment.seq, 0);
+                assert_eq!(segment.payload, REQUEST);
+            }
+            x => panic!("unexpected item: {:?}", x),
+        }
+        match &items[2] {
+            DecodedItem::Fragment(fragment) => {
+                assert_eq!(fragment.source, 1);
+                assert_eq!(fragment.function, FunctionCode::Read);
+                assert_eq!(fragment.iin, None);
+                assert_eq!(fragment.error, None);
+                assert_eq!(
+                    fragment.headers,
+                    vec![DecodedHeader {
+                        variation: Variation::Group60Var1,
+                        qualifier: QualifierCode::AllObjects,
+                        range: None,
+                        count: None,
+                        values: DecodedValues::None,
+                    }]
+                );
+                assert!(fragment
+                    .format(AppDecodeLevel::ObjectHeaders)
+                    .contains("g60v1"));
+            }
+            x => panic!("unexpected item: {:?}", x),
+        }
+    }
+
+    #[test]
+    fn discards_least_recently_extended_fragment_when_too_many_are_assembled() {
+        let mut decoder = Decoder::new();
+        // FIR without FIN from more sources than can be assembled
+        for source in 0..40 {
+            assert_eq!(decoder.decode(&frame(source, 0x40, &REQUEST[..2])).len(), 2);
+        }
+        assert_eq!(decoder.assemblers.len(), Decoder::MAX_ASSEMBLERS);
+
+        // the fragment of the first source was discarded
+        assert_eq!(decoder.decode(&frame(0, 0x81, &REQUEST[2..])).len(), 2);
+
+        let items = decoder.decode(&frame(39, 0x81, &REQUEST[2..]));
+        assert_eq!(items.len(), 3);
+        match &items[2] {
+            DecodedItem::Fragment(fragment) => {
+                assert_eq!(fragment.source, 39);
+                assert_eq!(fragment.raw, REQUEST);
+            }
+            x => panic!("unexpected item: {:?}", x),
+        }
+        assert!(!decoder.assemblers.contains_key(&(39, 1024)));
+    }
+}
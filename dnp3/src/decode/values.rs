@@ -0,0 +1,228 @@
+use crate::app::control::CommandValue;
+use crate::app::gen::count::CountVariation;
+use crate::app::gen::prefixed::PrefixedVariation;
+use crate::app::measurement::*;
+use crate::app::parse::parser::{HeaderDetails, ObjectHeader};
+use crate::app::parse::traits::{FixedSize, Index};
+use crate::app::Bytes;
+use crate::app::ResponseHeader;
+use crate::master::{HeaderInfo, ReadHandler, ReadType};
+
+/// Value of an object along with its index
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct Indexed<T> {
+    /// index of the object
+    pub index: u16,
+    /// value of the object
+    pub value: T,
+}
+
+/// Values of the objects in a [DecodedHeader](crate::decode::DecodedHeader)
+///
+/// Measurements with relative time are resolved using the common time of occurrence (g51)
+/// that precedes them in the same fragment, the same way the master does.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum DecodedValues {
+    /// header that doesn't carry values, e.g. in a READ request, or whose objects aren't decoded
+    None,
+    /// binary input values
+    Binary(Vec<Indexed<Binary>>),
+    /// double-bit binary input values
+    DoubleBitBinary(Vec<Indexed<DoubleBitBinary>>),
+    /// binary output status values
+    BinaryOutputStatus(Vec<Indexed<BinaryOutputStatus>>),
+    /// counter values
+    Counter(Vec<Indexed<Counter>>),
+    /// frozen counter values
+    FrozenCounter(Vec<Indexed<FrozenCounter>>),
+    /// analog input values
+    Analog(Vec<Indexed<Analog>>),
+    /// analog output status values
+    AnalogOutputStatus(Vec<Indexed<AnalogOutputStatus>>),
+    /// octet string values
+    OctetString(Vec<Indexed<Vec<u8>>>),
+    /// control objects (g12v1 and g41) of a command request or of the response echoing them
+    Command(Vec<Indexed<CommandValue>>),
+    /// time and date (g50v1) or common time of occurrence (g51) objects
+    Time(Vec<Time>),
+}
+
+impl<T> Indexed<T> {
+    fn new(index: u16, value: T) -> Self {
+        Self { index, value }
+    }
+}
+
+impl DecodedValues {
+    /// decode the values of a header, updating the common time of occurrence applied to
+    /// subsequent headers of the same fragment
+    pub(crate) fn new(header: &ObjectHeader, cto: &mut Option<Time>) -> Self {
+        let mut collector = ValueCollector {
+            values: DecodedValues::None,
+        };
+
+        match &header.details {
+            HeaderDetails::AllObjects(_) => {}
+            HeaderDetails::OneByteCount(_, var) => return Self::from_count(var, cto),
+            HeaderDetails::TwoByteCount(_, var) => return Self::from_count(var, cto),
+            HeaderDetails::OneByteStartStop(_, _, var) => {
+                var.extract_measurements_to(header.details.qualifier(), &mut collector);
+            }
+            HeaderDetails::TwoByteStartStop(_, _, var) => {
+                var.extract_measurements_to(header.details.qualifier(), &mut collector);
+            }
+            HeaderDetails::OneByteCountAndPrefix(_, var) => {
+                if let Some(commands) = Self::commands(var) {
+                    return commands;
+                }
+                var.extract_measurements_to(*cto, &mut collector);
+            }
+            HeaderDetails::TwoByteCountAndPrefix(_, var) => {
+                if let Some(commands) = Self::commands(var) {
+                    return commands;
+                }
+                var.extract_measurements_to(*cto, &mut collector);
+            }
+        }
+
+        collector.values
+    }
+
+    fn from_count(var: &CountVariation, cto: &mut Option<Time>) -> Self {
+        let times: Vec<Time> = match var {
+            CountVariation::Group50Var1(seq) => {
+                return DecodedValues::Time(
+                    seq.iter().map(|x| Time::Synchronized(x.time)).collect(),
+                )
+            }
+            CountVariation::Group51Var1(seq) => {
+                seq.iter().map(|x| Time::Synchronized(x.time)).collect()
+            }
+            CountVariation::Group51Var2(seq) => {
+                seq.iter().map(|x| Time::NotSynchronized(x.time)).collect()
+            }
+            _ => return DecodedValues::None,
+        };
+
+        // only a single CTO object is applied to the following headers
+        if let [time] = times.as_slice() {
+            *cto = Some(*time);
+        }
+
+        DecodedValues::Time(times)
+    }
+
+    fn commands<I>(var: &PrefixedVariation<I>) -> Option<Self>
+    where
+        I: FixedSize + Index + std::fmt::Display,
+    {
+        let values = match var {
+            PrefixedVariation::Group12Var1(seq) => seq
+                .iter()
+                .map(|x| Indexed::new(x.index.widen_to_u16(), x.value.into()))
+                .collect(),
+            PrefixedVariation::Group41Var1(seq) => seq
+                .iter()
+                .map(|x| Indexed::new(x.index.widen_to_u16(), x.value.into()))
+                .collect(),
+            PrefixedVariation::Group41Var2(seq) => seq
+                .iter()
+                .map(|x| Indexed::new(x.index.widen_to_u16(), x.value.into()))
+                .collect(),
+            PrefixedVariation::Group41Var3(seq) => seq
+                .iter()
+                .map(|x| Indexed::new(x.index.widen_to_u16(), x.value.into()))
+                .collect(),
+            PrefixedVariation::Group41Var4(seq) => seq
+                .iter()
+                .map(|x| Indexed::new(x.index.widen_to_u16(), x.value.into()))
+                .collect(),
+            _ => return None,
+        };
+
+        Some(DecodedValues::Command(values))
+    }
+}
+
+// reuses the measurement extraction of the master to collect the values of a single header
+struct ValueCollector {
+    values: DecodedValues,
+}
+
+fn collect<T>(iter: &mut dyn Iterator<Item = (T, u16)>) -> Vec<Indexed<T>> {
+    iter.map(|(value, index)| Indexed::new(index, value))
+        .collect()
+}
+
+impl ReadHandler for ValueCollector {
+    fn begin_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn handle_binary(&mut self, _info: HeaderInfo, iter: &mut dyn Iterator<Item = (Binary, u16)>) {
+        self.values = DecodedValues::Binary(collect(iter));
+    }
+
+    fn handle_double_bit_binary(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+    ) {
+        self.values = DecodedValues::DoubleBitBinary(collect(iter));
+    }
+
+    fn handle_binary_output_status(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+    ) {
+        self.values = DecodedValues::BinaryOutputStatus(collect(iter));
+    }
+
+    fn handle_counter(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (Counter, u16)>,
+    ) {
+        self.values = DecodedValues::Counter(collect(iter));
+    }
+
+    fn handle_frozen_counter(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+    ) {
+        self.values = DecodedValues::FrozenCounter(collect(iter));
+    }
+
+    fn handle_analog(&mut self, _info: HeaderInfo, iter: &mut dyn Iterator<Item = (Analog, u16)>) {
+        self.values = DecodedValues::Analog(collect(iter));
+    }
+
+    fn handle_analog_output_status(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+    ) {
+        self.values = DecodedValues::AnalogOutputStatus(collect(iter));
+    }
+
+    fn handle_octet_string<'a>(
+        &mut self,
+        _info: HeaderInfo,
+        iter: &'a mut dyn Iterator<Item = (Bytes<'a>, u16)>,
+    ) {
+        self.values = DecodedValues::OctetString(
+            iter.map(|(value, index)| Indexed::new(index, value.value.to_vec()))
+                .collect(),
+        );
+    }
+}
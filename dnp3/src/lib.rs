@@ -71,7 +71,9 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// application layer types shared by both the master and outstation APIs
 pub mod app;
-/// types used to control decoding in the log
+/// Capture of physical layer traffic to pcapng files or user-provided sinks
+pub mod capture;
+/// types used to control decoding in the log and to decode recorded traffic
 pub mod decode;
 /// Types specific to the link-layer
 pub mod link;
@@ -81,6 +83,8 @@ pub mod master;
 pub mod outstation;
 /// Entry points and types for serial
 pub mod serial;
+/// Entry points and types for user-provided streams
+pub mod stream;
 /// Entry points and types for TCP
 pub mod tcp;
 
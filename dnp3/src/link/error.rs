@@ -33,6 +33,10 @@ pub enum LinkError {
     Stdio(std::io::ErrorKind),
     BadFrame(FrameError),
     BadLogic(LogicError),
+    /// confirmed user data was not acknowledged by the remote station after all retries
+    Unconfirmed,
+    /// the remote station does not support the confirmed data link service
+    ConfirmNotSupported,
 }
 
 impl std::fmt::Display for LinkError {
@@ -41,6 +45,12 @@ impl std::fmt::Display for LinkError {
             LinkError::Stdio(kind) => write!(f, "{}", std::io::Error::from(*kind)),
             LinkError::BadFrame(err) => write!(f, "{}", err),
             LinkError::BadLogic(err) => write!(f, "{}", err),
+            LinkError::Unconfirmed => {
+                f.write_str("no link-layer confirmation received after all retries")
+            }
+            LinkError::ConfirmNotSupported => {
+                f.write_str("remote station does not support the confirmed data link service")
+            }
         }
     }
 }
@@ -1,14 +1,29 @@
+/// Link-layer function code, including the PRM bit
 #[derive(Copy, Clone, PartialEq, Debug)]
-pub(crate) enum Function {
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum Function {
+    /// RESET_LINK_STATES sent by a primary station
     PriResetLinkStates,
+    /// TEST_LINK_STATES sent by a primary station
     PriTestLinkStates,
+    /// CONFIRMED_USER_DATA sent by a primary station
     PriConfirmedUserData,
+    /// UNCONFIRMED_USER_DATA sent by a primary station
     PriUnconfirmedUserData,
+    /// REQUEST_LINK_STATUS sent by a primary station
     PriRequestLinkStatus,
+    /// ACK sent by a secondary station
     SecAck,
+    /// NACK sent by a secondary station
     SecNack,
+    /// LINK_STATUS sent by a secondary station
     SecLinkStatus,
+    /// NOT_SUPPORTED sent by a secondary station
     SecNotSupported,
+    /// unknown function code
     Unknown(u8),
 }
 
@@ -16,10 +16,14 @@ pub(crate) mod constants {
     pub(crate) const RESERVED_START: u16 = 0xFFF0;
 }
 
+/// Broadcast destination address and the confirmation behavior it requests from outstations
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub(crate) enum BroadcastConfirmMode {
+pub enum BroadcastConfirmMode {
+    /// 0xFFFF: outstations set IIN1.0 in their next response, confirmation is optional
     Optional,
+    /// 0xFFFE: outstations set IIN1.0 in their next response and request confirmation of it
     Mandatory,
+    /// 0xFFFD: outstations do not report the broadcast
     NotRequired,
 }
 
@@ -8,6 +8,7 @@ use crate::link::header::{
     AnyAddress, BroadcastConfirmMode, ControlField, FrameInfo, FrameType, Header,
 };
 use crate::link::parser::FramePayload;
+use crate::link::primary::PrimaryHandle;
 use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::Feature;
 use crate::util::phys::PhysLayer;
@@ -22,6 +23,7 @@ pub(crate) struct Layer {
     self_address: Feature,
     local_address: EndpointAddress,
     secondary_state: SecondaryState,
+    primary: Option<PrimaryHandle>,
     reader: super::reader::Reader,
     tx_buffer: [u8; super::constant::LINK_HEADER_LENGTH],
 }
@@ -43,12 +45,14 @@ impl Layer {
         endpoint_type: EndpointType,
         self_address: Feature,
         local_address: EndpointAddress,
+        primary: Option<PrimaryHandle>,
     ) -> Self {
         Self {
             endpoint_type,
             self_address,
             local_address,
             secondary_state: SecondaryState::NotReset,
+            primary,
             reader: super::reader::Reader::new(error_mode),
             tx_buffer: [0; super::constant::LINK_HEADER_LENGTH],
         }
@@ -56,6 +60,9 @@ impl Layer {
 
     pub(crate) fn reset(&mut self) {
         self.secondary_state = SecondaryState::NotReset;
+        if let Some(primary) = &self.primary {
+            primary.reset();
+        }
         self.reader.reset();
     }
 
@@ -66,12 +73,46 @@ impl Layer {
         payload: &mut FramePayload,
     ) -> Result<FrameInfo, LinkError> {
         loop {
-            if let Some(address) = self.read_one(io, level, payload).await? {
-                return Ok(address);
+            match self.transmit_primary(io, level).await? {
+                None => {
+                    if let Some(address) = self.read_one(io, level, payload).await? {
+                        return Ok(address);
+                    }
+                }
+                Some(deadline) => {
+                    crate::tokio::select! {
+                        _ = crate::tokio::time::sleep_until(deadline) => {
+                            // retransmit on the next iteration
+                        }
+                        x = self.read_one(io, level, payload) => {
+                            if let Some(address) = x? {
+                                return Ok(address);
+                            }
+                        }
+                    }
+                }
             }
         }
     }
 
+    // transmit any pending primary frame, returning the time at which it must be retransmitted
+    async fn transmit_primary(
+        &self,
+        io: &mut PhysLayer,
+        level: DecodeLevel,
+    ) -> Result<Option<crate::tokio::time::Instant>, LinkError> {
+        let primary = match &self.primary {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        if let Some(frame) = primary.poll_transmit(level)? {
+            io.write(&frame, level.physical).await?;
+        }
+
+        Ok(primary.deadline())
+    }
+
     fn get_header(&self, reply: Reply) -> Header {
         Header::new(
             ControlField::new(self.endpoint_type.dir_bit(), reply.function),
@@ -238,6 +279,29 @@ impl Layer {
                     Some(Reply::new(source, Function::SecLinkStatus)),
                 )
             }
+            Function::SecAck => {
+                match &self.primary {
+                    Some(primary) => primary.on_ack(source),
+                    None => tracing::warn!("ignoring ACK without confirmed data link service"),
+                }
+                (None, None)
+            }
+            Function::SecNack => {
+                match &self.primary {
+                    Some(primary) => primary.on_nack(source),
+                    None => tracing::warn!("ignoring NACK without confirmed data link service"),
+                }
+                (None, None)
+            }
+            Function::SecNotSupported => {
+                match &self.primary {
+                    Some(primary) => primary.on_not_supported(source),
+                    None => {
+                        tracing::warn!("ignoring NOT_SUPPORTED without confirmed data link service")
+                    }
+                }
+                (None, None)
+            }
             Function::SecLinkStatus => (
                 Some(FrameInfo::new(
                     source,
@@ -253,3 +317,119 @@ impl Layer {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::link::format::{format_data_frame, format_header_only, Payload};
+    use crate::link::test_data::ACK;
+    use crate::link::LinkConfirmConfig;
+    use crate::tokio::test::*;
+    use crate::util::cursor::WriteCursor;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    // the addresses of the ACK test frame
+    fn master() -> EndpointAddress {
+        EndpointAddress::from(1024).unwrap()
+    }
+
+    fn outstation() -> EndpointAddress {
+        EndpointAddress::from(1).unwrap()
+    }
+
+    fn reset_link() -> Vec<u8> {
+        let mut buffer = [0; super::super::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let header = Header::new(
+            ControlField::new(true, Function::PriResetLinkStates),
+            outstation().wrap(),
+            master().wrap(),
+        );
+        format_header_only(header, &mut cursor)
+            .unwrap()
+            .frame
+            .to_vec()
+    }
+
+    fn confirmed_data(fcb: bool) -> Vec<u8> {
+        let mut buffer = [0; super::super::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut control = ControlField::new(true, Function::PriConfirmedUserData);
+        control.fcb = fcb;
+        control.fcv = true;
+        let header = Header::new(control, outstation().wrap(), master().wrap());
+        format_data_frame(header, Payload::new(0xC0, &[0xC0, 0x01]), &mut cursor)
+            .unwrap()
+            .frame
+            .to_vec()
+    }
+
+    fn layer(max_retries: u8) -> (Layer, PrimaryHandle) {
+        let primary = PrimaryHandle::new(
+            EndpointType::Master,
+            master(),
+            LinkConfirmConfig::new(TIMEOUT, max_retries),
+        );
+        primary.enqueue(outstation(), 0xC0, &[0xC0, 0x01]);
+        let layer = Layer::new(
+            LinkErrorMode::Close,
+            EndpointType::Master,
+            Feature::Disabled,
+            master(),
+            Some(primary.clone()),
+        );
+        (layer, primary)
+    }
+
+    #[test]
+    fn retransmits_confirmed_data_until_acknowledged() {
+        let (mut layer, primary) = layer(1);
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::mock(io);
+        let mut payload = FramePayload::new();
+
+        io_handle.write(&reset_link());
+        io_handle.read(ACK.bytes);
+        io_handle.write(&confirmed_data(true));
+
+        let mut task = spawn(layer.read(&mut io, DecodeLevel::nothing(), &mut payload));
+        assert_pending!(task.poll());
+        assert!(io_handle.all_written());
+        assert!(io_handle.all_read());
+
+        // retransmitted with the same FCB once the confirm timeout elapses
+        io_handle.write(&confirmed_data(true));
+        crate::tokio::time::advance(TIMEOUT);
+        assert_pending!(task.poll());
+        assert!(io_handle.all_written());
+
+        io_handle.read(ACK.bytes);
+        assert_pending!(task.poll());
+        assert!(io_handle.all_read());
+        drop(task);
+
+        // nothing remains to be confirmed
+        assert_eq!(primary.deadline(), None);
+        assert_eq!(primary.poll_transmit(DecodeLevel::nothing()), Ok(None));
+    }
+
+    #[test]
+    fn returns_error_when_retries_are_exhausted() {
+        let (mut layer, _primary) = layer(0);
+        let (io, mut io_handle) = io::mock();
+        let mut io = PhysLayer::mock(io);
+        let mut payload = FramePayload::new();
+
+        io_handle.write(&reset_link());
+
+        let mut task = spawn(layer.read(&mut io, DecodeLevel::nothing(), &mut payload));
+        assert_pending!(task.poll());
+        assert!(io_handle.all_written());
+
+        crate::tokio::time::advance(TIMEOUT);
+        assert_eq!(assert_ready!(task.poll()), Err(LinkError::Unconfirmed));
+    }
+}
@@ -1,3 +1,6 @@
+pub use crate::link::function::Function as LinkFunction;
+pub use crate::link::header::BroadcastConfirmMode;
+
 use crate::link::header::AnyAddress;
 
 mod crc;
@@ -8,7 +11,9 @@ mod function;
 pub(crate) mod header;
 pub(crate) mod layer;
 pub(crate) mod parser;
+pub(crate) mod primary;
 pub(crate) mod reader;
+pub(crate) mod router;
 
 pub(crate) mod constant {
     pub(crate) const START1: u8 = 0x05;
@@ -49,6 +54,45 @@ pub enum LinkErrorMode {
     Close,
 }
 
+/// Configuration of the confirmed data link service
+///
+/// When enabled, user data is sent as `CONFIRMED_USER_DATA` frames. The local station resets the
+/// remote link with `RESET_LINK_STATES` as required, tracks the frame count bit (FCB), and
+/// retransmits each frame until the remote station acknowledges it. Broadcasts are always
+/// sent unconfirmed.
+///
+/// If a frame is still unacknowledged after all retries, or the remote station replies with
+/// `NOT_SUPPORTED`, the queued frames are discarded and a link error closes the session. A
+/// master task waiting on the frame fails with `TaskError::Link`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinkConfirmConfig {
+    /// time to wait for an acknowledgement before retransmitting a frame
+    pub timeout: std::time::Duration,
+    /// number of retransmissions after the initial attempt before the frame is discarded and the session is closed
+    pub max_retries: u8,
+}
+
+impl LinkConfirmConfig {
+    /// Default time to wait for an acknowledgement
+    pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+    /// Default number of retransmissions
+    pub const DEFAULT_MAX_RETRIES: u8 = 2;
+
+    /// Create a configuration with the specified timeout and number of retries
+    pub fn new(timeout: std::time::Duration, max_retries: u8) -> Self {
+        Self {
+            timeout,
+            max_retries,
+        }
+    }
+}
+
+impl Default for LinkConfirmConfig {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_TIMEOUT, Self::DEFAULT_MAX_RETRIES)
+    }
+}
+
 /// Represents a validated 16-bit endpoint address for a master or an outstation
 /// Certain special addresses are not allowed by the standard to be used
 /// as endpoint addresses.
@@ -0,0 +1,432 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use crate::app::EndpointType;
+use crate::decode::DecodeLevel;
+use crate::link::error::LinkError;
+use crate::link::format::{format_data_frame, format_header_only, Payload};
+use crate::link::function::Function;
+use crate::link::header::{ControlField, Header};
+use crate::link::{EndpointAddress, LinkConfirmConfig};
+use crate::tokio::time::Instant;
+use crate::util::cursor::WriteCursor;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum State {
+    NotReset,
+    Reset(bool), // the next fcb to transmit
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Outstanding {
+    Reset,
+    Data(bool),
+}
+
+struct Segment {
+    destination: EndpointAddress,
+    transport: u8,
+    data: Vec<u8>,
+}
+
+/// Primary station state of the confirmed data link service
+///
+/// Segments queued by the transport writer are transmitted one at a time. Each one is
+/// preceded by a RESET_LINK_STATES handshake if required, and retransmitted with the same FCB
+/// until it is acknowledged or the retries are exhausted. A failure discards the queued segments
+/// and is returned by the next call to `poll_transmit`.
+struct Primary {
+    is_master: bool,
+    local_address: EndpointAddress,
+    config: LinkConfirmConfig,
+    state: State,
+    segments: VecDeque<Segment>,
+    // frame awaiting an ACK and the time at which it is retransmitted
+    outstanding: Option<(Outstanding, Instant)>,
+    // retries remaining for the segment at the front of the queue
+    retries: u8,
+    // failure detected while processing a reply, not yet reported
+    failure: Option<LinkError>,
+    buffer: [u8; super::constant::MAX_LINK_FRAME_LENGTH],
+}
+
+impl Primary {
+    fn reset(&mut self) {
+        self.state = State::NotReset;
+        self.segments.clear();
+        self.outstanding = None;
+        self.failure = None;
+    }
+
+    fn abandon(&mut self, error: LinkError) -> LinkError {
+        tracing::warn!(
+            "discarding {} segment(s) after link confirmation failure: {}",
+            self.segments.len(),
+            error
+        );
+        self.reset();
+        error
+    }
+
+    fn enqueue(&mut self, destination: EndpointAddress, transport: u8, data: &[u8]) {
+        if self.segments.is_empty() {
+            self.retries = self.config.max_retries;
+        }
+        self.segments.push_back(Segment {
+            destination,
+            transport,
+            data: data.to_vec(),
+        });
+    }
+
+    fn poll_transmit(
+        &mut self,
+        now: Instant,
+        level: DecodeLevel,
+    ) -> Result<Option<Vec<u8>>, LinkError> {
+        if let Some(error) = self.failure.take() {
+            return Err(error);
+        }
+
+        let kind = match self.outstanding {
+            Some((_, deadline)) if now < deadline => return Ok(None),
+            Some((kind, _)) => {
+                if self.retries == 0 {
+                    return Err(self.abandon(LinkError::Unconfirmed));
+                }
+                self.retries -= 1;
+                tracing::warn!(
+                    "no link-layer ACK within {} ms, retrying",
+                    self.config.timeout.as_millis()
+                );
+                kind
+            }
+            None => {
+                if self.segments.is_empty() {
+                    return Ok(None);
+                }
+                match self.state {
+                    State::NotReset => Outstanding::Reset,
+                    State::Reset(fcb) => Outstanding::Data(fcb),
+                }
+            }
+        };
+
+        self.outstanding = Some((kind, now + self.config.timeout));
+        self.format(kind, level)
+    }
+
+    fn format(
+        &mut self,
+        kind: Outstanding,
+        level: DecodeLevel,
+    ) -> Result<Option<Vec<u8>>, LinkError> {
+        let segment = match self.segments.front() {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        let mut cursor = WriteCursor::new(&mut self.buffer);
+        let data = match kind {
+            Outstanding::Reset => {
+                let header = Header::new(
+                    ControlField::new(self.is_master, Function::PriResetLinkStates),
+                    segment.destination.wrap(),
+                    self.local_address.wrap(),
+                );
+                format_header_only(header, &mut cursor)?
+            }
+            Outstanding::Data(fcb) => {
+                let mut control = ControlField::new(self.is_master, Function::PriConfirmedUserData);
+                control.fcb = fcb;
+                control.fcv = true;
+                let header = Header::new(
+                    control,
+                    segment.destination.wrap(),
+                    self.local_address.wrap(),
+                );
+                format_data_frame(
+                    header,
+                    Payload::new(segment.transport, &segment.data),
+                    &mut cursor,
+                )?
+            }
+        };
+
+        if level.link.header_enabled() {
+            tracing::info!("LINK TX - {}", data.to_link_display(level.link));
+        }
+
+        Ok(Some(data.frame.to_vec()))
+    }
+
+    fn is_from_destination(&self, source: EndpointAddress) -> bool {
+        self.outstanding.is_some()
+            && matches!(self.segments.front(), Some(x) if x.destination == source)
+    }
+
+    fn on_ack(&mut self, source: EndpointAddress) {
+        if !self.is_from_destination(source) {
+            tracing::warn!("ignoring unexpected ACK from {}", source);
+            return;
+        }
+
+        match self.outstanding.take() {
+            Some((Outstanding::Reset, _)) => {
+                self.state = State::Reset(true);
+            }
+            Some((Outstanding::Data(fcb), _)) => {
+                self.state = State::Reset(!fcb);
+                self.segments.pop_front();
+                self.retries = self.config.max_retries;
+            }
+            None => {}
+        }
+    }
+
+    fn on_nack(&mut self, source: EndpointAddress) {
+        if !self.is_from_destination(source) {
+            tracing::warn!("ignoring unexpected NACK from {}", source);
+            return;
+        }
+
+        // the secondary station has lost its state, reset the link and then retransmit
+        self.outstanding = None;
+        self.state = State::NotReset;
+        if self.retries == 0 {
+            self.failure = Some(self.abandon(LinkError::Unconfirmed));
+        } else {
+            self.retries -= 1;
+        }
+    }
+
+    fn on_not_supported(&mut self, source: EndpointAddress) {
+        if !self.is_from_destination(source) {
+            tracing::warn!("ignoring unexpected NOT_SUPPORTED from {}", source);
+            return;
+        }
+
+        tracing::warn!(
+            "{} does not support the confirmed data link service",
+            source
+        );
+        self.failure = Some(self.abandon(LinkError::ConfirmNotSupported));
+    }
+}
+
+/// Shared between the transport writer, which queues segments, and the link layer,
+/// which transmits them and processes acknowledgements while reading
+#[derive(Clone)]
+pub(crate) struct PrimaryHandle {
+    inner: Arc<Mutex<Primary>>,
+}
+
+impl PrimaryHandle {
+    pub(crate) fn new(
+        endpoint_type: EndpointType,
+        local_address: EndpointAddress,
+        config: LinkConfirmConfig,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Primary {
+                is_master: endpoint_type.dir_bit(),
+                local_address,
+                config,
+                state: State::NotReset,
+                segments: VecDeque::new(),
+                outstanding: None,
+                retries: config.max_retries,
+                failure: None,
+                buffer: [0; super::constant::MAX_LINK_FRAME_LENGTH],
+            })),
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.inner.lock().unwrap().reset()
+    }
+
+    pub(crate) fn enqueue(&self, destination: EndpointAddress, transport: u8, data: &[u8]) {
+        self.inner
+            .lock()
+            .unwrap()
+            .enqueue(destination, transport, data)
+    }
+
+    /// returns the next frame to transmit, if any, or the failure to confirm a queued segment
+    pub(crate) fn poll_transmit(&self, level: DecodeLevel) -> Result<Option<Vec<u8>>, LinkError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .poll_transmit(Instant::now(), level)
+    }
+
+    /// time at which the outstanding frame must be retransmitted, if any
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.inner
+            .lock()
+            .unwrap()
+            .outstanding
+            .map(|(_, deadline)| deadline)
+    }
+
+    pub(crate) fn on_ack(&self, source: EndpointAddress) {
+        self.inner.lock().unwrap().on_ack(source)
+    }
+
+    pub(crate) fn on_nack(&self, source: EndpointAddress) {
+        self.inner.lock().unwrap().on_nack(source)
+    }
+
+    pub(crate) fn on_not_supported(&self, source: EndpointAddress) {
+        self.inner.lock().unwrap().on_not_supported(source)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn primary(max_retries: u8) -> Primary {
+        Primary {
+            is_master: true,
+            local_address: EndpointAddress::from(1).unwrap(),
+            config: LinkConfirmConfig::new(TIMEOUT, max_retries),
+            state: State::NotReset,
+            segments: VecDeque::new(),
+            outstanding: None,
+            retries: max_retries,
+            failure: None,
+            buffer: [0; super::super::constant::MAX_LINK_FRAME_LENGTH],
+        }
+    }
+
+    fn outstation() -> EndpointAddress {
+        EndpointAddress::from(1024).unwrap()
+    }
+
+    fn transmit(primary: &mut Primary, now: Instant) -> Option<ControlField> {
+        primary
+            .poll_transmit(now, DecodeLevel::nothing())
+            .unwrap()
+            .map(|frame| ControlField::from(frame[3]))
+    }
+
+    fn confirmed_data(fcb: bool) -> ControlField {
+        let mut control = ControlField::new(true, Function::PriConfirmedUserData);
+        control.fcb = fcb;
+        control.fcv = true;
+        control
+    }
+
+    #[test]
+    fn resets_link_then_alternates_fcb() {
+        let now = Instant::now();
+        let mut primary = primary(0);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+        primary.enqueue(outstation(), 0xC1, &[0x02]);
+
+        assert_eq!(
+            transmit(&mut primary, now),
+            Some(ControlField::new(true, Function::PriResetLinkStates))
+        );
+        // nothing else is sent until the outstanding frame is acknowledged
+        assert_eq!(transmit(&mut primary, now), None);
+
+        primary.on_ack(outstation());
+        assert_eq!(transmit(&mut primary, now), Some(confirmed_data(true)));
+        primary.on_ack(outstation());
+        assert_eq!(transmit(&mut primary, now), Some(confirmed_data(false)));
+        primary.on_ack(outstation());
+        assert_eq!(transmit(&mut primary, now), None);
+        assert_eq!(primary.state, State::Reset(true));
+    }
+
+    #[test]
+    fn retransmits_with_same_fcb_until_retries_are_exhausted() {
+        let now = Instant::now();
+        let mut primary = primary(1);
+        primary.state = State::Reset(false);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+
+        assert_eq!(transmit(&mut primary, now), Some(confirmed_data(false)));
+        assert_eq!(primary.outstanding.map(|(_, x)| x), Some(now + TIMEOUT));
+        assert_eq!(
+            transmit(&mut primary, now + TIMEOUT),
+            Some(confirmed_data(false))
+        );
+        // the segment is discarded and the link must be reset again
+        assert_eq!(
+            primary.poll_transmit(now + TIMEOUT * 2, DecodeLevel::nothing()),
+            Err(LinkError::Unconfirmed)
+        );
+        assert!(primary.segments.is_empty());
+        assert_eq!(primary.state, State::NotReset);
+    }
+
+    #[test]
+    fn reports_not_supported_on_next_transmit() {
+        let now = Instant::now();
+        let mut primary = primary(3);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+        primary.enqueue(outstation(), 0xC1, &[0x02]);
+
+        assert!(transmit(&mut primary, now).is_some());
+        primary.on_not_supported(outstation());
+        assert!(primary.segments.is_empty());
+        assert_eq!(
+            primary.poll_transmit(now, DecodeLevel::nothing()),
+            Err(LinkError::ConfirmNotSupported)
+        );
+        // the failure is only reported once
+        assert_eq!(transmit(&mut primary, now), None);
+    }
+
+    #[test]
+    fn reports_nack_after_all_retries() {
+        let now = Instant::now();
+        let mut primary = primary(0);
+        primary.state = State::Reset(true);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+
+        assert!(transmit(&mut primary, now).is_some());
+        primary.on_nack(outstation());
+        assert_eq!(
+            primary.poll_transmit(now, DecodeLevel::nothing()),
+            Err(LinkError::Unconfirmed)
+        );
+    }
+
+    #[test]
+    fn nack_resets_link_before_retransmitting() {
+        let now = Instant::now();
+        let mut primary = primary(1);
+        primary.state = State::Reset(true);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+
+        assert_eq!(transmit(&mut primary, now), Some(confirmed_data(true)));
+        primary.on_nack(outstation());
+        assert_eq!(
+            transmit(&mut primary, now),
+            Some(ControlField::new(true, Function::PriResetLinkStates))
+        );
+        primary.on_ack(outstation());
+        assert_eq!(transmit(&mut primary, now), Some(confirmed_data(true)));
+    }
+
+    #[test]
+    fn ignores_ack_from_other_stations() {
+        let now = Instant::now();
+        let mut primary = primary(0);
+        primary.enqueue(outstation(), 0xC0, &[0x01]);
+
+        assert!(transmit(&mut primary, now).is_some());
+        primary.on_ack(EndpointAddress::from(1025).unwrap());
+        assert_eq!(primary.state, State::NotReset);
+        assert!(primary.outstanding.is_some());
+    }
+}
@@ -82,6 +82,20 @@ impl Reader {
                         return Err(LinkError::Stdio(ErrorKind::UnexpectedEof));
                     }
 
+                    // a gap between characters terminates any partially received frame
+                    if io.take_frame_gap() {
+                        if self.end > self.begin {
+                            tracing::warn!(
+                                "discarding {} bytes after inter-character timeout",
+                                self.end - self.begin
+                            );
+                        }
+                        self.buffer.copy_within(self.end..self.end + count, 0);
+                        self.begin = 0;
+                        self.end = 0;
+                        self.parser.reset();
+                    }
+
                     self.end += count;
                 }
             }
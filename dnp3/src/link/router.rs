@@ -0,0 +1,627 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use crate::app::EndpointType;
+use crate::capture::{CaptureRecord, CaptureSink, CaptureSlot, SharedCaptureSink};
+use crate::decode::{DecodeLevel, PhysDecodeLevel};
+use crate::link::error::LinkError;
+use crate::link::format::{
+    format_data_frame, format_header_fixed_size, format_header_only, Payload,
+};
+use crate::link::function::Function;
+use crate::link::header::{AnyAddress, ControlField, Header};
+use crate::link::parser::FramePayload;
+use crate::link::reader::Reader;
+use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use crate::tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use crate::util::cursor::WriteCursor;
+use crate::util::phys::PhysLayer;
+
+// FIN bit of the transport header, i.e. the first byte of the frame payload
+const TRANSPORT_FIN_MASK: u8 = 0x80;
+
+/// Routes frames received on one physical layer to outstations by destination address
+///
+/// Each outstation runs over a [`RoutedStream`]. Frames transmitted by the outstations are
+/// written to the physical layer one at a time. Once an outstation starts transmitting a fragment
+/// that spans several transport segments, the frames of the other outstations are held until the
+/// final segment is written so that the segments of different fragments are never interleaved.
+pub(crate) struct Router {
+    routes: Vec<Route>,
+    decode_level: PhysDecodeLevel,
+    reply_not_supported: bool,
+    /// outstation in the middle of transmitting a multi-segment fragment
+    active: Option<EndpointAddress>,
+    /// frames held while another outstation is active
+    queue: VecDeque<Transmission>,
+}
+
+/// Destination of routed frames
+pub(crate) struct Route {
+    address: EndpointAddress,
+    self_address: bool,
+    // frames received on the physical layer for this outstation
+    incoming: UnboundedSender<Vec<u8>>,
+    // capture slot of the outstation, installed by its session on the routed stream
+    capture: Arc<Mutex<CaptureSlot>>,
+}
+
+/// Frame written by a routed outstation
+pub(crate) struct Transmission {
+    source: EndpointAddress,
+    /// `None` when the stream of the outstation is dropped
+    frame: Option<Vec<u8>>,
+}
+
+enum Event {
+    Received(Result<Header, LinkError>),
+    Transmit(Option<Transmission>),
+}
+
+impl Route {
+    /// create a route and the stream over which the outstation with the address runs
+    pub(crate) fn create(
+        address: EndpointAddress,
+        self_address: bool,
+        outgoing: UnboundedSender<Transmission>,
+    ) -> (Self, RoutedStream) {
+        let (incoming, rx) = crate::tokio::sync::mpsc::unbounded_channel();
+        let capture = Arc::new(Mutex::new(CaptureSlot::default()));
+        (
+            Self {
+                address,
+                self_address,
+                incoming,
+                capture: capture.clone(),
+            },
+            RoutedStream::new(address, rx, outgoing, capture),
+        )
+    }
+
+    pub(crate) fn address(&self) -> EndpointAddress {
+        self.address
+    }
+
+    fn matches(&self, destination: AnyAddress) -> bool {
+        match destination {
+            AnyAddress::Endpoint(x) => x == self.address,
+            AnyAddress::Broadcast(_) => true,
+            AnyAddress::SelfAddress => self.self_address,
+            AnyAddress::Reserved(_) => false,
+        }
+    }
+}
+
+impl Router {
+    /// The physical layer is logged at `decode_level`. The routed streams are never logged or
+    /// captured at the physical layer since they only carry copies of the frames. Instead, the
+    /// physical layer is captured once to each distinct sink installed by the routed outstations.
+    ///
+    /// When `reply_not_supported` is true, primary frames that require a response and are sent to
+    /// an unknown address are answered with `NOT_SUPPORTED` on behalf of that address. This is
+    /// appropriate on point-to-point links, but not on multi-drop lines shared with other devices.
+    pub(crate) fn new(decode_level: PhysDecodeLevel, reply_not_supported: bool) -> Self {
+        Self {
+            routes: Vec::new(),
+            decode_level,
+            reply_not_supported,
+            active: None,
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn add(&mut self, route: Route) {
+        self.routes.push(route);
+    }
+
+    /// run until an error occurs on the physical layer or all of the routed streams are dropped
+    pub(crate) async fn run(
+        &mut self,
+        io: &mut PhysLayer,
+        mut outgoing: UnboundedReceiver<Transmission>,
+    ) -> Result<(), LinkError> {
+        io.set_capture(self.capture_slot());
+        let mut reader = Reader::new(LinkErrorMode::Discard);
+        let mut payload = FramePayload::new();
+        let level = DecodeLevel {
+            physical: self.decode_level,
+            ..DecodeLevel::nothing()
+        };
+
+        loop {
+            let event = crate::tokio::select! {
+                x = reader.read(io, &mut payload, level) => Event::Received(x),
+                x = outgoing.recv() => Event::Transmit(x),
+            };
+
+            match event {
+                Event::Received(header) => {
+                    let header = header?;
+                    if self.route(header, payload.get())? == 0 {
+                        if let Some(reply) = self.get_not_supported_reply(header) {
+                            let mut buffer = [0; super::constant::LINK_HEADER_LENGTH];
+                            format_header_fixed_size(reply, &mut buffer);
+                            io.write(&buffer, self.decode_level).await?;
+                        }
+                    }
+                }
+                Event::Transmit(Some(transmission)) => {
+                    self.queue.push_back(transmission);
+                    self.transmit(io).await?;
+                }
+                Event::Transmit(None) => {
+                    tracing::info!("all routed outstations have shut down");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn capture_slot(&self) -> CaptureSlot {
+        let sink: Box<dyn CaptureSink> = Box::new(RoutedCaptureSink {
+            slots: self.routes.iter().map(|x| x.capture.clone()).collect(),
+        });
+        let slot = CaptureSlot::default();
+        slot.set(Some(Arc::new(Mutex::new(sink))));
+        slot
+    }
+
+    /// write the queued frames that may be sent while the active outstation, if any, finishes its fragment
+    async fn transmit(&mut self, io: &mut PhysLayer) -> Result<(), LinkError> {
+        while let Some(frame) = self.next_frame() {
+            io.write(&frame, self.decode_level).await?;
+        }
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<u8>> {
+        loop {
+            let active = self.active;
+            let position = self
+                .queue
+                .iter()
+                .position(|x| active.is_none() || active == Some(x.source))?;
+            let transmission = self.queue.remove(position)?;
+
+            match transmission.frame {
+                Some(frame) => {
+                    self.active = if continues_fragment(&frame) {
+                        Some(transmission.source)
+                    } else {
+                        None
+                    };
+                    return Some(frame);
+                }
+                None => {
+                    // the outstation shut down, possibly in the middle of a fragment
+                    if active == Some(transmission.source) {
+                        self.active = None;
+                    }
+                }
+            }
+        }
+    }
+
+    // returns the number of outstations to which the frame was delivered
+    fn route(&self, header: Header, payload: &[u8]) -> Result<usize, LinkError> {
+        let mut buffer = [0; super::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let frame = match payload {
+            [] => format_header_only(header, &mut cursor)?,
+            [transport, data @ ..] => {
+                format_data_frame(header, Payload::new(*transport, data), &mut cursor)?
+            }
+        };
+
+        let mut count = 0;
+        for route in self.routes.iter().filter(|x| x.matches(header.destination)) {
+            // the outstation may have shut down
+            if route.incoming.send(frame.frame.to_vec()).is_ok() {
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            tracing::debug!("no outstation for frame sent to {}", header.destination);
+        }
+
+        Ok(count)
+    }
+
+    fn get_not_supported_reply(&self, header: Header) -> Option<Header> {
+        if !self.reply_not_supported || !header.control.master {
+            return None;
+        }
+
+        let (destination, source) = match (header.destination, header.source) {
+            (AnyAddress::Endpoint(destination), AnyAddress::Endpoint(source)) => {
+                (destination, source)
+            }
+            _ => return None,
+        };
+
+        // only primary functions that expect a secondary response are answered
+        match header.control.func {
+            Function::PriResetLinkStates
+            | Function::PriTestLinkStates
+            | Function::PriConfirmedUserData
+            | Function::PriRequestLinkStatus => {
+                tracing::warn!(
+                    "replying NOT_SUPPORTED to {:?} sent to unknown address {}",
+                    header.control.func,
+                    destination
+                );
+                Some(Header::new(
+                    ControlField::new(
+                        EndpointType::Outstation.dir_bit(),
+                        Function::SecNotSupported,
+                    ),
+                    source.wrap(),
+                    destination.wrap(),
+                ))
+            }
+            _ => {
+                tracing::warn!("ignoring frame sent to unknown address {}", destination);
+                None
+            }
+        }
+    }
+}
+
+/// returns true if the frame carries a transport segment that is followed by more segments
+fn continues_fragment(frame: &[u8]) -> bool {
+    let control = match frame.get(3) {
+        Some(x) => ControlField::from(*x),
+        None => return false,
+    };
+
+    match control.func {
+        Function::PriConfirmedUserData | Function::PriUnconfirmedUserData => matches!(
+            frame.get(super::constant::LINK_HEADER_LENGTH),
+            Some(x) if x & TRANSPORT_FIN_MASK == 0
+        ),
+        _ => false,
+    }
+}
+
+/// Forwards the capture of the physical layer to the sinks of the routed outstations
+///
+/// Outstations that share a sink receive each record only once.
+struct RoutedCaptureSink {
+    slots: Vec<Arc<Mutex<CaptureSlot>>>,
+}
+
+impl CaptureSink for RoutedCaptureSink {
+    fn record(&mut self, record: &CaptureRecord) {
+        let mut recorded: Vec<SharedCaptureSink> = Vec::new();
+        for slot in self.slots.iter() {
+            for sink in slot.lock().unwrap().get() {
+                if !recorded.iter().any(|x| Arc::ptr_eq(x, &sink)) {
+                    sink.lock().unwrap().record(record);
+                    recorded.push(sink);
+                }
+            }
+        }
+    }
+}
+
+/// In-memory stream connecting a routed outstation to the router
+pub(crate) struct RoutedStream {
+    address: EndpointAddress,
+    incoming: UnboundedReceiver<Vec<u8>>,
+    outgoing: UnboundedSender<Transmission>,
+    capture: Arc<Mutex<CaptureSlot>>,
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl RoutedStream {
+    fn new(
+        address: EndpointAddress,
+        incoming: UnboundedReceiver<Vec<u8>>,
+        outgoing: UnboundedSender<Transmission>,
+        capture: Arc<Mutex<CaptureSlot>>,
+    ) -> Self {
+        Self {
+            address,
+            incoming,
+            outgoing,
+            capture,
+            pending: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl RoutedStream {
+    /// the router records the traffic of the physical layer to the sink installed in `capture`
+    pub(crate) fn set_capture(&self, capture: CaptureSlot) {
+        *self.capture.lock().unwrap() = capture;
+    }
+}
+
+impl Drop for RoutedStream {
+    fn drop(&mut self) {
+        // release the physical layer if the outstation stopped in the middle of a fragment
+        let _ = self.outgoing.send(Transmission {
+            source: self.address,
+            frame: None,
+        });
+    }
+}
+
+impl AsyncRead for RoutedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.position == self.pending.len() {
+            match self.incoming.poll_recv(cx) {
+                Poll::Pending => return Poll::Pending,
+                // the router has shut down, signal the end of the stream
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Ready(Some(frame)) => {
+                    self.pending = frame;
+                    self.position = 0;
+                }
+            }
+        }
+
+        let this = &mut *self;
+        let remaining = &this.pending[this.position..];
+        let count = std::cmp::min(remaining.len(), buf.remaining());
+        buf.put_slice(&remaining[..count]);
+        this.position += count;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for RoutedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        // each write from the link-layer is a complete frame which is transmitted as a unit
+        let transmission = Transmission {
+            source: self.address,
+            frame: Some(buf.to_vec()),
+        };
+        match self.outgoing.send(transmission) {
+            Ok(()) => Poll::Ready(Ok(buf.len())),
+            Err(_) => Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into())),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::link::header::BroadcastConfirmMode;
+    use crate::link::test_data::*;
+    use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::tokio::sync::mpsc::unbounded_channel;
+    use crate::tokio::test::*;
+
+    fn route(address: u16, self_address: bool) -> (Route, RoutedStream) {
+        let (outgoing, _) = unbounded_channel();
+        Route::create(
+            EndpointAddress::from(address).unwrap(),
+            self_address,
+            outgoing,
+        )
+    }
+
+    fn frames(stream: &mut RoutedStream) -> usize {
+        let mut count = 0;
+        while stream.incoming.try_recv().is_ok() {
+            count += 1;
+        }
+        count
+    }
+
+    // unconfirmed user data frame from `source` with the specified transport header
+    fn segment(source: u16, transport: u8) -> Transmission {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let header =
+            Header::unconfirmed_user_data(false, AnyAddress::from(1), AnyAddress::from(source));
+        let frame = format_data_frame(header, Payload::new(transport, &[0xC0, 0x81]), &mut cursor)
+            .unwrap()
+            .frame
+            .to_vec();
+        Transmission {
+            source: EndpointAddress::from(source).unwrap(),
+            frame: Some(frame),
+        }
+    }
+
+    fn next_source(router: &mut Router) -> Option<u16> {
+        let frame = router.next_frame()?;
+        Some(u16::from_le_bytes([frame[6], frame[7]]))
+    }
+
+    #[test]
+    fn routes_frames_by_destination() {
+        let (first, mut first_stream) = route(1, false);
+        let (second, mut second_stream) = route(2, true);
+        let mut router = Router::new(PhysDecodeLevel::Nothing, false);
+        router.add(first);
+        router.add(second);
+
+        // the test frame is sent to address 1
+        let frame = &UNCONFIRMED_USER_DATA;
+        assert_eq!(router.route(frame.header, frame.payload).unwrap(), 1);
+        assert_eq!(
+            first_stream.incoming.try_recv().unwrap(),
+            frame.bytes.to_vec()
+        );
+        assert_eq!(frames(&mut second_stream), 0);
+
+        let mut header = RESET_LINK.header;
+        header.destination = AnyAddress::Broadcast(BroadcastConfirmMode::Optional);
+        assert_eq!(router.route(header, &[]).unwrap(), 2);
+
+        header.destination = AnyAddress::SelfAddress;
+        assert_eq!(router.route(header, &[]).unwrap(), 1);
+        assert_eq!(frames(&mut first_stream), 1);
+        assert_eq!(frames(&mut second_stream), 2);
+
+        header.destination = AnyAddress::from(3);
+        assert_eq!(router.route(header, &[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn replies_not_supported_for_unknown_destinations_when_enabled() {
+        let router = |reply| Router::new(PhysDecodeLevel::Nothing, reply);
+        let mut header = RESET_LINK.header;
+        header.destination = AnyAddress::from(3);
+
+        assert_eq!(router(false).get_not_supported_reply(header), None);
+
+        let reply = router(true).get_not_supported_reply(header).unwrap();
+        assert_eq!(reply.control.func, Function::SecNotSupported);
+        assert!(!reply.control.master);
+        assert_eq!(reply.destination, RESET_LINK.header.source);
+        assert_eq!(reply.source, AnyAddress::from(3));
+
+        // unconfirmed user data does not expect a response
+        let mut header = UNCONFIRMED_USER_DATA.header;
+        header.destination = AnyAddress::from(3);
+        assert_eq!(router(true).get_not_supported_reply(header), None);
+    }
+
+    #[test]
+    fn holds_other_outstations_until_multi_segment_fragment_completes() {
+        let mut router = Router::new(PhysDecodeLevel::Nothing, false);
+
+        // outstation 10 starts a fragment with FIR only, outstation 20 sends a complete fragment
+        router.queue.push_back(segment(10, 0x40));
+        router.queue.push_back(segment(20, 0xC0));
+        router.queue.push_back(segment(10, 0x01));
+        router.queue.push_back(segment(10, 0x82));
+
+        assert_eq!(next_source(&mut router), Some(10));
+        assert_eq!(next_source(&mut router), Some(10));
+        assert_eq!(next_source(&mut router), Some(10));
+        assert_eq!(next_source(&mut router), Some(20));
+        assert_eq!(next_source(&mut router), None);
+    }
+
+    #[test]
+    fn releases_physical_layer_when_active_stream_is_dropped() {
+        let mut router = Router::new(PhysDecodeLevel::Nothing, false);
+        let (outgoing, mut rx) = unbounded_channel();
+        let (_, stream) = Route::create(EndpointAddress::from(10).unwrap(), false, outgoing);
+
+        router.queue.push_back(segment(10, 0x40));
+        router.queue.push_back(segment(20, 0xC0));
+        assert_eq!(next_source(&mut router), Some(10));
+        assert_eq!(next_source(&mut router), None);
+
+        drop(stream);
+        router.queue.push_back(rx.try_recv().unwrap());
+        assert_eq!(next_source(&mut router), Some(20));
+        assert_eq!(next_source(&mut router), None);
+    }
+
+    struct CountingSink(Arc<Mutex<usize>>);
+
+    impl CaptureSink for CountingSink {
+        fn record(&mut self, _: &CaptureRecord) {
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    fn counting_slot() -> (CaptureSlot, Arc<Mutex<usize>>) {
+        let count = Arc::new(Mutex::new(0));
+        let sink: Box<dyn CaptureSink> = Box::new(CountingSink(count.clone()));
+        let slot = CaptureSlot::default();
+        slot.set(Some(Arc::new(Mutex::new(sink))));
+        (slot, count)
+    }
+
+    #[test]
+    fn captures_physical_layer_once_per_installed_sink() {
+        let (first, first_stream) = route(1, false);
+        let (second, second_stream) = route(2, false);
+        let (third, third_stream) = route(3, false);
+        let (_, fourth_stream) = route(4, false);
+        let mut router = Router::new(PhysDecodeLevel::Nothing, false);
+        router.add(first);
+        router.add(second);
+        router.add(third);
+
+        // the first two outstations share a sink, the fourth stream is not routed
+        let (shared, shared_count) = counting_slot();
+        let (single, single_count) = counting_slot();
+        let (unrouted, unrouted_count) = counting_slot();
+        first_stream.set_capture(shared.clone());
+        second_stream.set_capture(shared);
+        third_stream.set_capture(single);
+        fourth_stream.set_capture(unrouted);
+
+        router.capture_slot().record(&CaptureRecord {
+            timestamp: std::time::SystemTime::now(),
+            direction: crate::capture::CaptureDirection::Rx,
+            local: None,
+            remote: None,
+            sent: 0,
+            received: 0,
+            data: ACK.bytes,
+        });
+
+        assert_eq!(*shared_count.lock().unwrap(), 1);
+        assert_eq!(*single_count.lock().unwrap(), 1);
+        assert_eq!(*unrouted_count.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn stream_reads_routed_frames_and_writes_whole_frames() {
+        let (incoming_tx, incoming_rx) = unbounded_channel();
+        let (outgoing_tx, mut outgoing_rx) = unbounded_channel();
+        let mut stream = RoutedStream::new(
+            EndpointAddress::from(1).unwrap(),
+            incoming_rx,
+            outgoing_tx,
+            Arc::default(),
+        );
+
+        incoming_tx.send(vec![0x05, 0x64, 0x05]).unwrap();
+        let mut buffer = [0; 2];
+        assert_eq!(
+            assert_ready!(spawn(stream.read(&mut buffer)).poll()).unwrap(),
+            2
+        );
+        assert_eq!(
+            assert_ready!(spawn(stream.read(&mut buffer)).poll()).unwrap(),
+            1
+        );
+        assert_eq!(buffer[0], 0x05);
+
+        assert_ready!(spawn(stream.write_all(ACK.bytes)).poll()).unwrap();
+        assert_eq!(
+            outgoing_rx.try_recv().unwrap().frame,
+            Some(ACK.bytes.to_vec())
+        );
+
+        // the stream ends once the router shuts down
+        drop(incoming_tx);
+        assert_eq!(
+            assert_ready!(spawn(stream.read(&mut buffer)).poll()).unwrap(),
+            0
+        );
+    }
+}
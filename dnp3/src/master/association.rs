@@ -271,6 +271,7 @@ impl LastUnsolFragment {
 pub(crate) struct Association {
     address: EndpointAddress,
     seq: Sequence,
+    request_count: u64,
     last_unsol_frag: Option<LastUnsolFragment>,
     request_queue: VecDeque<Task>,
     max_request_queue_size: usize,
@@ -294,6 +295,7 @@ impl Association {
         Self {
             address,
             seq: Sequence::default(),
+            request_count: 0,
             last_unsol_frag: None,
             request_queue: VecDeque::new(),
             max_request_queue_size: config.max_queued_user_requests,
@@ -368,9 +370,18 @@ impl Association {
     }
 
     pub(crate) fn increment_seq(&mut self) -> Sequence {
+        self.request_count += 1;
         self.seq.increment()
     }
 
+    /// number of requests sent to the outstation
+    ///
+    /// Unlike the 4-bit sequence number, this never wraps in practice so it can be used to
+    /// detect requests that were sent in between two others.
+    pub(crate) fn request_count(&self) -> u64 {
+        self.request_count
+    }
+
     pub(crate) fn is_integrity_complete(&self) -> bool {
         !self.config.startup_integrity_classes.any() || self.startup_integrity_done
     }
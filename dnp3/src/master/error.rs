@@ -122,6 +122,9 @@ pub enum CommandError {
     Task(TaskError),
     /// Failed b/c of an unexpected response to Select, Operate, or DirectOperate
     Response(CommandResponseError),
+    /// The OPERATE of a [Selection](crate::master::Selection) was not sent because another
+    /// request was sent to the outstation after the SELECT
+    SelectionLost,
 }
 
 impl std::fmt::Display for AssociationError {
@@ -180,6 +183,9 @@ impl std::fmt::Display for CommandError {
         match self {
             CommandError::Response(x) => std::fmt::Display::fmt(x, f),
             CommandError::Task(x) => std::fmt::Display::fmt(x, f),
+            CommandError::SelectionLost => f.write_str(
+                "another request was sent to the outstation between the SELECT and the OPERATE",
+            ),
         }
     }
 }
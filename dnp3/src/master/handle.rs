@@ -3,19 +3,24 @@ use std::time::{Duration, SystemTime};
 use crate::app::measurement::*;
 use crate::app::variations::Variation;
 use crate::app::*;
-use crate::decode::DecodeLevel;
-use crate::link::{EndpointAddress, LinkStatusResult};
+use crate::capture::{CaptureSink, SharedCaptureSink};
+use crate::decode::{DecodeEventSink, DecodeLevel};
+use crate::link::{BroadcastConfirmMode, EndpointAddress, LinkConfirmConfig, LinkStatusResult};
 use crate::master::association::AssociationConfig;
 use crate::master::error::{AssociationError, CommandError, PollError, TaskError, TimeSyncError};
 use crate::master::messages::{AssociationMsg, AssociationMsgType, MasterMsg, Message};
 use crate::master::poll::{PollHandle, PollMsg};
-use crate::master::request::{CommandHeaders, CommandMode, ReadRequest, TimeSyncProcedure};
+use crate::master::request::{
+    CommandHeaders, CommandMode, CommandReport, ReadRequest, TimeSyncProcedure,
+};
 use crate::master::session::MasterSession;
+use crate::master::tasks::broadcast::{BroadcastAction, BroadcastTask};
 use crate::master::tasks::command::CommandTask;
 use crate::master::tasks::read::SingleReadTask;
 use crate::master::tasks::restart::{RestartTask, RestartType};
 use crate::master::tasks::time::TimeSyncTask;
 use crate::master::tasks::Task;
+use crate::outstation::FreezeType;
 use crate::util::channel::Sender;
 
 /// Handle to a master communication channel. This handle controls
@@ -35,6 +40,29 @@ pub struct AssociationHandle {
     master: MasterChannel,
 }
 
+/// Controls that were successfully selected using [AssociationHandle::select](crate::master::AssociationHandle::select)
+///
+/// The selection is abandoned when it's dropped without being operated, either directly or with
+/// [Selection::cancel]. Outstations don't support explicitly releasing a selection, so it remains
+/// selected until the outstation's select timeout expires.
+#[derive(Debug)]
+pub struct Selection {
+    association: AssociationHandle,
+    headers: CommandHeaders,
+    report: CommandReport,
+    selected_at: crate::tokio::time::Instant,
+}
+
+/// Handle used to send requests to every outstation on a master channel using a broadcast address
+///
+/// Outstations never respond to broadcast requests, so each operation completes as soon as
+/// the request has been sent.
+#[derive(Clone, Debug)]
+pub struct BroadcastHandle {
+    mode: BroadcastConfirmMode,
+    master: MasterChannel,
+}
+
 /// Configuration for a MasterChannel
 #[derive(Copy, Clone, Debug)]
 pub struct MasterChannelConfig {
@@ -52,6 +80,8 @@ pub struct MasterChannelConfig {
     ///
     /// Must be at least 2048.
     pub rx_buffer_size: usize,
+    /// Optional confirmed data link service, typically only used on serial links
+    pub link_confirm: Option<LinkConfirmConfig>,
 }
 
 impl MasterChannelConfig {
@@ -63,6 +93,7 @@ impl MasterChannelConfig {
             response_timeout: Timeout::default(),
             tx_buffer_size: MasterSession::DEFAULT_TX_BUFFER_SIZE,
             rx_buffer_size: MasterSession::DEFAULT_RX_BUFFER_SIZE,
+            link_confirm: None,
         }
     }
 }
@@ -101,6 +132,34 @@ impl MasterChannel {
         rx.await?
     }
 
+    /// Install a sink that receives every physical layer read and write of this master, or remove it with `None`
+    ///
+    /// Replaces any previously installed sink
+    pub async fn set_capture_sink(
+        &mut self,
+        sink: Option<Box<dyn CaptureSink>>,
+    ) -> Result<(), Shutdown> {
+        let sink: Option<SharedCaptureSink> =
+            sink.map(|x| std::sync::Arc::new(std::sync::Mutex::new(x)));
+        self.send_master_message(MasterMsg::SetCaptureSink(sink))
+            .await?;
+        Ok(())
+    }
+
+    /// Install a sink that receives the structured events decoded from the traffic of this master, or remove it with `None`
+    ///
+    /// Replaces any previously installed decode event sink. It is independent of the sink installed with
+    /// [`set_capture_sink`](Self::set_capture_sink), so a capture and decode events may be active at once.
+    pub async fn set_decode_event_sink(
+        &mut self,
+        sink: Option<Box<dyn DecodeEventSink>>,
+    ) -> Result<(), Shutdown> {
+        let sink = sink.map(crate::decode::shared_event_sink);
+        self.send_master_message(MasterMsg::SetDecodeEventSink(sink))
+            .await?;
+        Ok(())
+    }
+
     /// Create a new association:
     /// * `address` is the DNP3 link-layer address of the outstation
     /// * `config` controls the behavior of the master for this outstation
@@ -125,6 +184,15 @@ impl MasterChannel {
             .map(|_| (AssociationHandle::new(address, self.clone())))
     }
 
+    /// Create a handle used to broadcast requests to every outstation on the channel
+    /// * `mode` selects the broadcast address and the confirmation behavior it requests
+    pub fn broadcast(&self, mode: BroadcastConfirmMode) -> BroadcastHandle {
+        BroadcastHandle {
+            mode,
+            master: self.clone(),
+        }
+    }
+
     /// Remove an association
     /// * `address` is the DNP3 link-layer address of the outstation
     pub async fn remove_association(
@@ -216,10 +284,41 @@ impl AssociationHandle {
         mode: CommandMode,
         headers: CommandHeaders,
     ) -> Result<(), CommandError> {
-        let (tx, rx) = crate::tokio::sync::oneshot::channel::<Result<(), CommandError>>();
-        let task = CommandTask::from_mode(mode, headers, Promise::OneShot(tx));
-        self.send_task(task.wrap().wrap()).await?;
-        rx.await?
+        let report = self.operate_with_report(mode, headers).await?;
+        report.outcome.map_err(|err| err.into())
+    }
+
+    /// Perform an asynchronous operate request and report the result of every control
+    ///
+    /// Unlike [operate](crate::master::AssociationHandle::operate), the statuses and values
+    /// echoed by the outstation are returned even if some controls were not successful. The
+    /// overall outcome of the operation is available in [CommandReport::outcome](crate::master::CommandReport::outcome),
+    /// so an error is only returned if the task fails before a response is received.
+    pub async fn operate_with_report(
+        &mut self,
+        mode: CommandMode,
+        headers: CommandHeaders,
+    ) -> Result<CommandReport, CommandError> {
+        self.run_command(|promise| CommandTask::from_mode(mode, headers, promise))
+            .await
+    }
+
+    /// Perform a SELECT and return a [Selection](crate::master::Selection) that can be operated later
+    ///
+    /// This allows the user to confirm the command between the SELECT and the OPERATE. The
+    /// selection must be operated before the outstation's select timeout expires and before
+    /// any other request is made to the outstation.
+    pub async fn select(&mut self, headers: CommandHeaders) -> Result<Selection, CommandError> {
+        let report = self
+            .run_command(|promise| CommandTask::select(headers.clone(), promise))
+            .await?;
+        report.outcome?;
+        Ok(Selection {
+            association: self.clone(),
+            headers,
+            report,
+            selected_at: crate::tokio::time::Instant::now(),
+        })
     }
 
     /// Perform a WARM_RESTART operation
@@ -265,6 +364,17 @@ impl AssociationHandle {
         rx.await?
     }
 
+    async fn run_command<F>(&mut self, create: F) -> Result<CommandReport, CommandError>
+    where
+        F: FnOnce(Promise<Result<CommandReport, CommandError>>) -> CommandTask,
+    {
+        let (tx, rx) =
+            crate::tokio::sync::oneshot::channel::<Result<CommandReport, CommandError>>();
+        let task = create(Promise::OneShot(tx));
+        self.send_task(task.wrap().wrap()).await?;
+        rx.await?
+    }
+
     async fn send_task(&mut self, task: Task) -> Result<(), Shutdown> {
         self.master
             .send_association_message(self.address, AssociationMsgType::QueueTask(task))
@@ -278,6 +388,83 @@ impl AssociationHandle {
     }
 }
 
+impl Selection {
+    /// report of the SELECT response
+    pub fn report(&self) -> &CommandReport {
+        &self.report
+    }
+
+    /// amount of time that has elapsed since the SELECT response was received
+    pub fn elapsed(&self) -> Duration {
+        crate::tokio::time::Instant::now().saturating_duration_since(self.selected_at)
+    }
+
+    /// OPERATE the selected controls using the same headers as the SELECT
+    ///
+    /// Fails with [CommandError::SelectionLost] without sending anything if another request
+    /// was sent to the outstation after the SELECT, since the outstation would reject it.
+    pub async fn operate(self) -> Result<(), CommandError> {
+        let report = self.operate_with_report().await?;
+        report.outcome.map_err(|err| err.into())
+    }
+
+    /// OPERATE the selected controls and report the result of every control
+    pub async fn operate_with_report(mut self) -> Result<CommandReport, CommandError> {
+        let select_request = self.report.request;
+        let headers = self.headers.clone();
+        self.association
+            .run_command(|promise| CommandTask::operate_selected(select_request, headers, promise))
+            .await
+    }
+
+    /// abandon the selection without operating it
+    ///
+    /// This is equivalent to dropping the `Selection` and only makes the intent explicit. No request is
+    /// sent to the outstation, so the controls remain selected until its select timeout expires.
+    pub fn cancel(self) {}
+}
+
+impl BroadcastHandle {
+    /// retrieve the broadcast confirmation mode used by the handle
+    pub fn mode(&self) -> BroadcastConfirmMode {
+        self.mode
+    }
+
+    /// Broadcast a DIRECT_OPERATE_NO_RESPONSE request
+    pub async fn operate(&mut self, headers: CommandHeaders) -> Result<(), TaskError> {
+        self.send(BroadcastAction::DirectOperate(headers)).await
+    }
+
+    /// Broadcast a WRITE of the absolute time (g50v1)
+    ///
+    /// Outstations don't respond to broadcasts, so the propagation delay cannot be measured
+    /// and should be accounted for in the supplied time.
+    pub async fn synchronize_time(&mut self, time: Timestamp) -> Result<(), TaskError> {
+        self.send(BroadcastAction::WriteTime(time)).await
+    }
+
+    /// Broadcast an IMMEDIATE_FREEZE_NO_RESPONSE or FREEZE_CLEAR_NO_RESPONSE request
+    /// * `freeze_type` determines the function code that is used
+    /// * `headers` specify which counters are frozen, e.g. `ReadRequest::all_objects(Variation::Group20Var0)`
+    pub async fn freeze(
+        &mut self,
+        freeze_type: FreezeType,
+        headers: ReadRequest,
+    ) -> Result<(), TaskError> {
+        self.send(BroadcastAction::Freeze(freeze_type, headers))
+            .await
+    }
+
+    async fn send(&mut self, action: BroadcastAction) -> Result<(), TaskError> {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel::<Result<(), TaskError>>();
+        let task = BroadcastTask::new(self.mode, action, Promise::OneShot(tx));
+        self.master
+            .send_master_message(MasterMsg::Broadcast(task))
+            .await?;
+        rx.await?
+    }
+}
+
 /// A generic callback type that must be invoked once and only once.
 /// The user can select to implement it using FnOnce or a
 /// one-shot reply channel
@@ -1,10 +1,12 @@
 use crate::app::Shutdown;
+use crate::capture::SharedCaptureSink;
 use crate::decode::DecodeLevel;
 use crate::link::EndpointAddress;
 use crate::master::error::PollError;
 use crate::master::error::{AssociationError, TaskError};
 use crate::master::handle::Promise;
 use crate::master::poll::PollMsg;
+use crate::master::tasks::broadcast::BroadcastTask;
 use crate::master::tasks::Task;
 use crate::master::{AssociationConfig, AssociationHandler, ReadHandler};
 
@@ -33,6 +35,12 @@ pub(crate) enum MasterMsg {
     SetDecodeLevel(DecodeLevel),
     /// Get the decoding level
     GetDecodeLevel(Promise<Result<DecodeLevel, Shutdown>>),
+    /// Install or remove the capture sink
+    SetCaptureSink(Option<SharedCaptureSink>),
+    /// Install or remove the decode event sink
+    SetDecodeEventSink(Option<SharedCaptureSink>),
+    /// Send a request to a broadcast address
+    Broadcast(BroadcastTask),
 }
 
 pub(crate) struct AssociationMsg {
@@ -1,6 +1,6 @@
 use std::ops::BitAnd;
 
-use crate::app::control::CommandStatus;
+use crate::app::control::{CommandStatus, CommandValue};
 use crate::app::format::write::HeaderWriter;
 use crate::app::gen::prefixed::PrefixedVariation;
 use crate::app::parse::count::CountSequence;
@@ -8,6 +8,7 @@ use crate::app::parse::parser::{HeaderCollection, HeaderDetails};
 use crate::app::parse::prefix::Prefix;
 use crate::app::parse::traits::{FixedSizeVariation, Index};
 use crate::app::variations::*;
+use crate::app::FunctionCode;
 use crate::master::error::CommandResponseError;
 use crate::util::cursor::WriteError;
 
@@ -18,6 +19,10 @@ pub enum CommandMode {
     DirectOperate,
     /// Master will use the `SELECT` function code followed by `OPERATE` in two pass request/response
     SelectBeforeOperate,
+    /// Master will use the `DIRECT_OPERATE_NO_RESPONSE` function code
+    ///
+    /// The outstation does not respond, so the operation completes as soon as the request is sent
+    DirectOperateNoAck,
 }
 
 /// Controls which time synchronization procedure is used
@@ -305,7 +310,7 @@ impl ReadRequest {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub(crate) enum CommandHeader {
     G12V1U8(Vec<(Group12Var1, u8)>),
     G41V1U8(Vec<(Group41Var1, u8)>),
@@ -321,6 +326,7 @@ pub(crate) enum CommandHeader {
 
 pub(crate) trait Command {
     fn status(&self) -> CommandStatus;
+    fn to_value(&self) -> CommandValue;
     fn to_header_u8(&self, index: u8) -> CommandHeader;
     fn to_header_u16(&self, index: u16) -> CommandHeader;
 }
@@ -330,6 +336,10 @@ impl Command for Group12Var1 {
         self.status
     }
 
+    fn to_value(&self) -> CommandValue {
+        CommandValue::G12V1(*self)
+    }
+
     fn to_header_u8(&self, index: u8) -> CommandHeader {
         CommandHeader::G12V1U8(vec![(*self, index)])
     }
@@ -344,6 +354,10 @@ impl Command for Group41Var1 {
         self.status
     }
 
+    fn to_value(&self) -> CommandValue {
+        CommandValue::G41V1(*self)
+    }
+
     fn to_header_u8(&self, index: u8) -> CommandHeader {
         CommandHeader::G41V1U8(vec![(*self, index)])
     }
@@ -357,6 +371,9 @@ impl Command for Group41Var2 {
     fn status(&self) -> CommandStatus {
         self.status
     }
+    fn to_value(&self) -> CommandValue {
+        CommandValue::G41V2(*self)
+    }
     fn to_header_u8(&self, index: u8) -> CommandHeader {
         CommandHeader::G41V2U8(vec![(*self, index)])
     }
@@ -369,6 +386,9 @@ impl Command for Group41Var3 {
     fn status(&self) -> CommandStatus {
         self.status
     }
+    fn to_value(&self) -> CommandValue {
+        CommandValue::G41V3(*self)
+    }
     fn to_header_u8(&self, index: u8) -> CommandHeader {
         CommandHeader::G41V3U8(vec![(*self, index)])
     }
@@ -381,6 +401,9 @@ impl Command for Group41Var4 {
     fn status(&self) -> CommandStatus {
         self.status
     }
+    fn to_value(&self) -> CommandValue {
+        CommandValue::G41V4(*self)
+    }
     fn to_header_u8(&self, index: u8) -> CommandHeader {
         CommandHeader::G41V4U8(vec![(*self, index)])
     }
@@ -391,6 +414,7 @@ impl Command for Group41Var4 {
 }
 
 /// Collection of command headers sent from the master API
+#[derive(Clone, Debug)]
 pub struct CommandHeaders {
     headers: Vec<CommandHeader>,
 }
@@ -410,13 +434,33 @@ impl CommandHeaders {
         Ok(())
     }
 
-    pub(crate) fn compare(&self, headers: HeaderCollection) -> Result<(), CommandResponseError> {
+    pub(crate) fn compare(
+        &self,
+        headers: HeaderCollection,
+        results: &mut Vec<CommandHeaderResult>,
+    ) -> Result<(), CommandResponseError> {
         let mut iter = headers.iter();
+        let mut outcome = Ok(());
 
         for sent in &self.headers {
             match iter.next() {
                 None => return Err(CommandResponseError::HeaderCountMismatch),
-                Some(received) => sent.compare(received.details)?,
+                Some(received) => {
+                    let mut items = Vec::new();
+                    let result = sent.compare(received.details, &mut items);
+                    results.push(CommandHeaderResult { items });
+                    match result {
+                        Ok(()) => {}
+                        // keep processing the remaining headers so every status is reported
+                        Err(CommandResponseError::BadStatus(_))
+                        | Err(CommandResponseError::ObjectValueMismatch) => {
+                            if outcome.is_ok() {
+                                outcome = result;
+                            }
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
             }
         }
 
@@ -424,7 +468,67 @@ impl CommandHeaders {
             return Err(CommandResponseError::HeaderCountMismatch);
         }
 
-        Ok(())
+        outcome
+    }
+}
+
+/// Result for a single control object in a command response
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CommandItemResult {
+    /// index of the control
+    pub index: u16,
+    /// status returned by the outstation for this control
+    pub status: CommandStatus,
+    /// value echoed by the outstation
+    pub value: CommandValue,
+}
+
+/// Results for every control object in one header of a command response
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandHeaderResult {
+    /// results in the order they appear in the response
+    pub items: Vec<CommandItemResult>,
+}
+
+/// Per-control report of a command operation
+///
+/// When the master sends SELECT followed by OPERATE, the report describes the last response
+/// that was received, i.e. the SELECT response if selection failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandReport {
+    /// function code of the request whose response is described
+    pub function: FunctionCode,
+    /// results for each header in the response
+    ///
+    /// If the response doesn't match the structure of the request, this only contains the
+    /// headers and objects that were processed before the mismatch was detected.
+    pub headers: Vec<CommandHeaderResult>,
+    /// overall outcome of the operation, the same result returned by
+    /// [AssociationHandle::operate](crate::master::AssociationHandle::operate)
+    pub outcome: Result<(), CommandResponseError>,
+    /// number of the request within the association, see `Association::request_count`
+    pub(crate) request: u64,
+}
+
+impl CommandReport {
+    pub(crate) fn new(function: FunctionCode, request: u64) -> Self {
+        Self {
+            function,
+            request,
+            headers: Vec::new(),
+            outcome: Ok(()),
+        }
+    }
+
+    /// iterate over the results of every control object in the response
+    pub fn items(&self) -> impl Iterator<Item = &CommandItemResult> {
+        self.headers.iter().flat_map(|header| header.items.iter())
+    }
+
+    /// iterate over the results of control objects that did not return `CommandStatus::Success`
+    pub fn failures(&self) -> impl Iterator<Item = &CommandItemResult> {
+        self.items()
+            .filter(|item| item.status != CommandStatus::Success)
     }
 }
 
@@ -730,22 +834,31 @@ impl CommandHeader {
     fn compare_items<V, I>(
         seq: CountSequence<'_, Prefix<I, V>>,
         sent: &[(V, I)],
+        results: &mut Vec<CommandItemResult>,
     ) -> Result<(), CommandResponseError>
     where
         V: FixedSizeVariation + Command,
         I: Index,
     {
         let mut received = seq.iter();
+        let mut outcome = Ok(());
 
         for item in sent {
             match received.next() {
                 None => return Err(CommandResponseError::ObjectCountMismatch),
                 Some(x) => {
-                    if x.value.status() != CommandStatus::Success {
-                        return Err(CommandResponseError::BadStatus(x.value.status()));
-                    }
-                    if !x.equals(item) {
-                        return Err(CommandResponseError::ObjectValueMismatch);
+                    let status = x.value.status();
+                    results.push(CommandItemResult {
+                        index: x.index.widen_to_u16(),
+                        status,
+                        value: x.value.to_value(),
+                    });
+                    if outcome.is_ok() {
+                        if status != CommandStatus::Success {
+                            outcome = Err(CommandResponseError::BadStatus(status));
+                        } else if !x.equals(item) {
+                            outcome = Err(CommandResponseError::ObjectValueMismatch);
+                        }
                     }
                 }
             }
@@ -755,68 +868,72 @@ impl CommandHeader {
             return Err(CommandResponseError::ObjectCountMismatch);
         }
 
-        Ok(())
+        outcome
     }
 
-    pub(crate) fn compare(&self, response: HeaderDetails) -> Result<(), CommandResponseError> {
+    pub(crate) fn compare(
+        &self,
+        response: HeaderDetails,
+        results: &mut Vec<CommandItemResult>,
+    ) -> Result<(), CommandResponseError> {
         match self {
             CommandHeader::G12V1U8(items) => match response {
                 HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group12Var1(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G12V1U16(items) => match response {
                 HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group12Var1(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V1U8(items) => match response {
                 HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group41Var1(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V1U16(items) => match response {
                 HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group41Var1(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V2U8(items) => match response {
                 HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group41Var2(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V2U16(items) => match response {
                 HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group41Var2(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V3U8(items) => match response {
                 HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group41Var3(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V3U16(items) => match response {
                 HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group41Var3(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V4U8(items) => match response {
                 HeaderDetails::OneByteCountAndPrefix(_, PrefixedVariation::Group41Var4(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
             CommandHeader::G41V4U16(items) => match response {
                 HeaderDetails::TwoByteCountAndPrefix(_, PrefixedVariation::Group41Var4(seq)) => {
-                    Self::compare_items(seq, items)
+                    Self::compare_items(seq, items, results)
                 }
                 _ => Err(CommandResponseError::HeaderTypeMismatch),
             },
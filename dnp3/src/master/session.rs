@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::ops::Add;
 use std::time::Duration;
 
@@ -10,13 +11,16 @@ use crate::app::ControlField;
 use crate::app::Sequence;
 use crate::app::Shutdown;
 use crate::app::Timeout;
+use crate::capture::CaptureSlot;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
+use crate::link::header::AnyAddress;
 use crate::link::EndpointAddress;
 use crate::link::LinkStatusResult;
 use crate::master::association::{AssociationMap, Next};
 use crate::master::error::TaskError;
 use crate::master::messages::{MasterMsg, Message};
+use crate::master::tasks::broadcast::BroadcastTask;
 use crate::master::tasks::{AssociationTask, NonReadTask, ReadTask, RequestWriter, Task};
 use crate::master::Association;
 use crate::tokio::time::Instant;
@@ -28,10 +32,13 @@ use crate::util::phys::PhysLayer;
 pub(crate) struct MasterSession {
     enabled: bool,
     decode_level: DecodeLevel,
+    capture: CaptureSlot,
     timeout: Timeout,
     associations: AssociationMap,
     messages: Receiver<Message>,
     tx_buffer: Buffer,
+    broadcasts: VecDeque<BroadcastTask>,
+    broadcast_seq: Sequence,
 }
 
 enum ReadResponseAction {
@@ -100,10 +107,13 @@ impl MasterSession {
         Self {
             enabled,
             decode_level,
+            capture: CaptureSlot::default(),
             timeout: response_timeout,
             associations: AssociationMap::new(),
             messages,
             tx_buffer: Buffer::new(tx_buffer_size),
+            broadcasts: VecDeque::new(),
+            broadcast_seq: Sequence::default(),
         }
     }
 
@@ -146,7 +156,19 @@ impl MasterSession {
         writer: &mut TransportWriter,
         reader: &mut TransportReader,
     ) -> RunError {
+        io.set_capture(self.capture.clone());
         loop {
+            // broadcasts take priority since they complete as soon as they're sent
+            if let Some(task) = self.broadcasts.pop_front() {
+                if let Err(err) = self.run_broadcast(io, task, writer).await {
+                    self.reset(err);
+                    writer.reset();
+                    reader.reset();
+                    return err;
+                }
+                continue;
+            }
+
             let result = match self.get_next_task() {
                 Next::Now(task) => {
                     let id = task.details.get_id();
@@ -247,7 +269,7 @@ impl MasterSession {
         let message = self.messages.receive().await?;
         match message {
             Message::Master(msg) => {
-                self.process_master_message(msg);
+                self.process_master_message(msg, is_connected);
                 if is_connected && !self.enabled {
                     return Err(StateChange::Disable);
                 }
@@ -263,7 +285,7 @@ impl MasterSession {
         Ok(())
     }
 
-    fn process_master_message(&mut self, msg: MasterMsg) {
+    fn process_master_message(&mut self, msg: MasterMsg, is_connected: bool) {
         match msg {
             MasterMsg::EnableCommunication(enable) => {
                 if enable {
@@ -290,10 +312,26 @@ impl MasterSession {
             MasterMsg::GetDecodeLevel(promise) => {
                 promise.complete(Ok(self.decode_level));
             }
+            MasterMsg::SetCaptureSink(sink) => {
+                self.capture.set(sink);
+            }
+            MasterMsg::SetDecodeEventSink(sink) => {
+                self.capture.set_events(sink);
+            }
+            MasterMsg::Broadcast(task) => {
+                if is_connected {
+                    self.broadcasts.push_back(task);
+                } else {
+                    task.complete(Err(TaskError::NoConnection));
+                }
+            }
         }
     }
 
     fn reset(&mut self, err: RunError) {
+        while let Some(task) = self.broadcasts.pop_front() {
+            task.complete(Err(err.into()));
+        }
         self.associations.reset(err);
     }
 }
@@ -365,6 +403,13 @@ impl MasterSession {
                 }
             };
 
+            // some requests, e.g. DIRECT_OPERATE_NO_RESPONSE, are complete once they're sent
+            let request = self.associations.get_mut(destination)?.request_count();
+            task = match task.complete_if_no_response(request) {
+                Some(task) => task,
+                None => return Ok(()),
+            };
+
             let deadline = self.timeout.deadline_from_now();
 
             loop {
@@ -739,6 +784,47 @@ impl MasterSession {
     }
 }
 
+// Broadcast requests
+impl MasterSession {
+    async fn run_broadcast(
+        &mut self,
+        io: &mut PhysLayer,
+        task: BroadcastTask,
+        writer: &mut TransportWriter,
+    ) -> Result<(), RunError> {
+        let result = self
+            .send_broadcast(io, &task, writer)
+            .instrument(tracing::info_span!("Broadcast", "mode" = ?task.mode))
+            .await;
+        task.complete(result);
+        match result {
+            Err(TaskError::Link(err)) => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn send_broadcast(
+        &mut self,
+        io: &mut PhysLayer,
+        task: &BroadcastTask,
+        writer: &mut TransportWriter,
+    ) -> Result<(), TaskError> {
+        let seq = self.broadcast_seq.increment();
+        let mut cursor = self.tx_buffer.write_cursor();
+        let mut hw = start_request(ControlField::request(seq), task.function(), &mut cursor)?;
+        task.write(&mut hw)?;
+        writer
+            .write(
+                io,
+                self.decode_level,
+                AnyAddress::Broadcast(task.mode),
+                cursor.written(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
 // Link status stuff
 impl MasterSession {
     async fn run_link_status_task(
@@ -0,0 +1,71 @@
+use crate::app::format::write::HeaderWriter;
+use crate::app::variations::Group50Var1;
+use crate::app::FunctionCode;
+use crate::app::Timestamp;
+use crate::link::BroadcastConfirmMode;
+use crate::master::error::TaskError;
+use crate::master::handle::Promise;
+use crate::master::request::{CommandHeaders, ReadRequest};
+use crate::master::tasks::RequestWriter;
+use crate::outstation::FreezeType;
+use crate::util::cursor::WriteError;
+
+/// operations that may be broadcast to every outstation on a channel
+pub(crate) enum BroadcastAction {
+    /// DIRECT_OPERATE_NO_RESPONSE
+    DirectOperate(CommandHeaders),
+    /// WRITE g50v1
+    WriteTime(Timestamp),
+    /// IMMEDIATE_FREEZE_NO_RESPONSE or FREEZE_CLEAR_NO_RESPONSE
+    Freeze(FreezeType, ReadRequest),
+}
+
+/// broadcast request that completes as soon as it is sent
+pub(crate) struct BroadcastTask {
+    pub(crate) mode: BroadcastConfirmMode,
+    action: BroadcastAction,
+    promise: Promise<Result<(), TaskError>>,
+}
+
+impl BroadcastTask {
+    pub(crate) fn new(
+        mode: BroadcastConfirmMode,
+        action: BroadcastAction,
+        promise: Promise<Result<(), TaskError>>,
+    ) -> Self {
+        Self {
+            mode,
+            action,
+            promise,
+        }
+    }
+
+    pub(crate) fn complete(self, result: Result<(), TaskError>) {
+        self.promise.complete(result)
+    }
+}
+
+impl RequestWriter for BroadcastTask {
+    fn function(&self) -> FunctionCode {
+        match &self.action {
+            BroadcastAction::DirectOperate(_) => FunctionCode::DirectOperateNoResponse,
+            BroadcastAction::WriteTime(_) => FunctionCode::Write,
+            BroadcastAction::Freeze(FreezeType::ImmediateFreeze, _) => {
+                FunctionCode::ImmediateFreezeNoResponse
+            }
+            BroadcastAction::Freeze(FreezeType::FreezeAndClear, _) => {
+                FunctionCode::FreezeClearNoResponse
+            }
+        }
+    }
+
+    fn write(&self, writer: &mut HeaderWriter) -> Result<(), WriteError> {
+        match &self.action {
+            BroadcastAction::DirectOperate(headers) => headers.write(writer),
+            BroadcastAction::WriteTime(time) => {
+                writer.write_count_of_one(Group50Var1 { time: *time })
+            }
+            BroadcastAction::Freeze(_, headers) => headers.format(writer),
+        }
+    }
+}
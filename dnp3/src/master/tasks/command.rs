@@ -1,7 +1,8 @@
 use crate::app::format::write::HeaderWriter;
 use crate::app::parse::parser::{HeaderCollection, Response};
 use crate::app::FunctionCode;
-use crate::master::error::{CommandError, CommandResponseError, TaskError};
+use crate::master::association::Association;
+use crate::master::error::{CommandError, TaskError};
 use crate::master::handle::Promise;
 use crate::master::request::*;
 use crate::master::tasks::NonReadTask;
@@ -11,12 +12,17 @@ enum State {
     Select,
     Operate,
     DirectOperate,
+    DirectOperateNoAck,
+    /// SELECT that completes once the outstation responds, leaving the OPERATE to the user
+    SelectOnly,
+    /// OPERATE that must immediately follow the SELECT sent as the specified request of the association
+    OperateSelected(u64),
 }
 
 pub(crate) struct CommandTask {
     state: State,
     headers: CommandHeaders,
-    promise: Promise<Result<(), CommandError>>,
+    promise: Promise<Result<CommandReport, CommandError>>,
 }
 
 impl CommandMode {
@@ -24,6 +30,7 @@ impl CommandMode {
         match self {
             CommandMode::DirectOperate => State::DirectOperate,
             CommandMode::SelectBeforeOperate => State::Select,
+            CommandMode::DirectOperateNoAck => State::DirectOperateNoAck,
         }
     }
 }
@@ -32,7 +39,7 @@ impl CommandTask {
     pub(crate) fn from_mode(
         mode: CommandMode,
         headers: CommandHeaders,
-        promise: Promise<Result<(), CommandError>>,
+        promise: Promise<Result<CommandReport, CommandError>>,
     ) -> Self {
         Self {
             state: mode.to_state(),
@@ -41,10 +48,25 @@ impl CommandTask {
         }
     }
 
+    pub(crate) fn select(
+        headers: CommandHeaders,
+        promise: Promise<Result<CommandReport, CommandError>>,
+    ) -> Self {
+        Self::new(State::SelectOnly, headers, promise)
+    }
+
+    pub(crate) fn operate_selected(
+        select_request: u64,
+        headers: CommandHeaders,
+        promise: Promise<Result<CommandReport, CommandError>>,
+    ) -> Self {
+        Self::new(State::OperateSelected(select_request), headers, promise)
+    }
+
     fn new(
         state: State,
         headers: CommandHeaders,
-        promise: Promise<Result<(), CommandError>>,
+        promise: Promise<Result<CommandReport, CommandError>>,
     ) -> Self {
         Self {
             state,
@@ -66,6 +88,36 @@ impl CommandTask {
             State::DirectOperate => FunctionCode::DirectOperate,
             State::Select => FunctionCode::Select,
             State::Operate => FunctionCode::Operate,
+            State::DirectOperateNoAck => FunctionCode::DirectOperateNoResponse,
+            State::SelectOnly => FunctionCode::Select,
+            State::OperateSelected(_) => FunctionCode::Operate,
+        }
+    }
+
+    /// check that an OPERATE of a previous selection is still valid before it's sent
+    pub(crate) fn start(self, association: &mut Association) -> Option<Self> {
+        if let State::OperateSelected(select_request) = self.state {
+            // the OPERATE must be the very next request after the SELECT
+            if association.request_count() != select_request {
+                tracing::warn!("selection lost because another request was sent after the SELECT");
+                self.promise.complete(Err(CommandError::SelectionLost));
+                return None;
+            }
+        }
+
+        Some(self)
+    }
+
+    /// complete the task if the outstation doesn't respond to the request that was sent
+    pub(crate) fn complete_if_no_response(self, request: u64) -> Option<Self> {
+        match self.state {
+            State::DirectOperateNoAck => {
+                let function = self.function();
+                self.promise
+                    .complete(Ok(CommandReport::new(function, request)));
+                None
+            }
+            _ => Some(self),
         }
     }
 
@@ -73,15 +125,21 @@ impl CommandTask {
         self.headers.write(writer)
     }
 
-    fn compare(&self, headers: HeaderCollection) -> Result<(), CommandResponseError> {
-        self.headers.compare(headers)
+    fn compare(&self, request: u64, headers: HeaderCollection) -> CommandReport {
+        let mut report = CommandReport::new(self.function(), request);
+        report.outcome = self.headers.compare(headers, &mut report.headers);
+        report
     }
 
     pub(crate) fn on_task_error(self, err: TaskError) {
         self.promise.complete(Err(err.into()))
     }
 
-    pub(crate) fn handle(self, response: Response) -> Option<NonReadTask> {
+    pub(crate) fn handle(
+        self,
+        association: &Association,
+        response: Response,
+    ) -> Option<NonReadTask> {
         let headers = match response.objects {
             Ok(x) => x,
             Err(err) => {
@@ -91,8 +149,11 @@ impl CommandTask {
             }
         };
 
-        if let Err(err) = self.compare(headers) {
-            self.promise.complete(Err(err.into()));
+        // the response always belongs to the last request that was sent
+        let report = self.compare(association.request_count(), headers);
+
+        if report.outcome.is_err() {
+            self.promise.complete(Ok(report));
             return None;
         }
 
@@ -100,7 +161,7 @@ impl CommandTask {
             State::Select => Some(self.change_state(State::Operate).wrap()),
             _ => {
                 // Complete w/ success
-                self.promise.complete(Ok(()));
+                self.promise.complete(Ok(report));
                 None
             }
         }
@@ -16,6 +16,7 @@ use crate::master::tasks::time::TimeSyncTask;
 use crate::util::cursor::WriteError;
 
 pub(crate) mod auto;
+pub(crate) mod broadcast;
 pub(crate) mod command;
 pub(crate) mod read;
 pub(crate) mod restart;
@@ -197,7 +198,7 @@ impl NonReadTask {
 
     pub(crate) fn start(self, association: &mut Association) -> Option<NonReadTask> {
         match self {
-            NonReadTask::Command(_) => Some(self),
+            NonReadTask::Command(task) => task.start(association).map(|task| task.wrap()),
             NonReadTask::Auto(_) => Some(self),
             NonReadTask::TimeSync(task) => task.start(association).map(|task| task.wrap()),
             NonReadTask::Restart(_) => Some(self),
@@ -213,6 +214,16 @@ impl NonReadTask {
         }
     }
 
+    /// complete the task if the request that was just sent doesn't produce a response
+    pub(crate) fn complete_if_no_response(self, request: u64) -> Option<NonReadTask> {
+        match self {
+            NonReadTask::Command(task) => task
+                .complete_if_no_response(request)
+                .map(|task| task.wrap()),
+            _ => Some(self),
+        }
+    }
+
     pub(crate) fn on_task_error(self, association: Option<&mut Association>, err: TaskError) {
         match self {
             NonReadTask::Command(task) => task.on_task_error(err),
@@ -228,7 +239,7 @@ impl NonReadTask {
         response: Response,
     ) -> Option<NonReadTask> {
         match self {
-            NonReadTask::Command(task) => task.handle(response),
+            NonReadTask::Command(task) => task.handle(association, response),
             NonReadTask::Auto(task) => match response.objects.ok() {
                 Some(headers) => task.handle(association, response.header, headers),
                 None => None,
@@ -0,0 +1,87 @@
+use crate::app::control::OpType;
+use crate::app::format::write::start_request;
+use crate::app::variations::{Group12Var1, Group50Var1};
+use crate::app::{ControlField, FunctionCode, Sequence, Timestamp};
+use crate::link::header::AnyAddress;
+use crate::link::BroadcastConfirmMode;
+use crate::master::association::AssociationConfig;
+use crate::master::request::{CommandBuilder, CommandMode, CommandSupport};
+use crate::tokio::test::*;
+use crate::util::cursor::WriteCursor;
+
+use super::harness::create_association;
+
+fn command() -> crate::master::request::CommandHeaders {
+    CommandBuilder::single_header_u16(Group12Var1::from_op_type(OpType::LatchOn), 7)
+}
+
+#[test]
+fn direct_operate_no_ack_completes_without_response() {
+    let mut harness = create_association(AssociationConfig::quiet());
+
+    let mut association = harness.association.clone();
+    let mut operate = spawn(async move {
+        association
+            .operate(CommandMode::DirectOperateNoAck, command())
+            .await
+    });
+    assert_pending!(operate.poll());
+
+    {
+        let mut buffer = [0; 32];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let mut request = start_request(
+            ControlField::request(Sequence::default()),
+            FunctionCode::DirectOperateNoResponse,
+            &mut cursor,
+        )
+        .unwrap();
+        command().write(&mut request).unwrap();
+        harness.io.write(cursor.written());
+    }
+    harness.assert_io();
+
+    assert_ready!(operate.poll()).unwrap();
+    assert_eq!(
+        *harness.destinations.lock().unwrap(),
+        vec![AnyAddress::from(1024)]
+    );
+}
+
+#[test]
+fn broadcast_time_sync_completes_once_written_to_the_broadcast_address() {
+    for mode in [
+        BroadcastConfirmMode::Optional,
+        BroadcastConfirmMode::Mandatory,
+        BroadcastConfirmMode::NotRequired,
+    ] {
+        let mut harness = create_association(AssociationConfig::quiet());
+
+        let time = Timestamp::new(0x0102_0304_0506);
+        let mut broadcast = harness.master.broadcast(mode);
+        assert_eq!(broadcast.mode(), mode);
+        let mut sync = spawn(async move { broadcast.synchronize_time(time).await });
+        assert_pending!(sync.poll());
+
+        {
+            let mut buffer = [0; 32];
+            let mut cursor = WriteCursor::new(&mut buffer);
+            let mut request = start_request(
+                ControlField::request(Sequence::default()),
+                FunctionCode::Write,
+                &mut cursor,
+            )
+            .unwrap();
+            request.write_count_of_one(Group50Var1 { time }).unwrap();
+            harness.io.write(cursor.written());
+        }
+        harness.assert_io();
+
+        // the master never waits for a response, regardless of what the mode requests from outstations
+        assert_ready!(sync.poll()).unwrap();
+        assert_eq!(
+            *harness.destinations.lock().unwrap(),
+            vec![AnyAddress::Broadcast(mode)]
+        );
+    }
+}
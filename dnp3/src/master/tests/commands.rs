@@ -0,0 +1,211 @@
+use crate::app::control::{CommandStatus, CommandValue, OpType};
+use crate::app::format::write::{start_request, start_response};
+use crate::app::variations::Group12Var1;
+use crate::app::{ControlField, FunctionCode, Iin, ResponseFunction, Sequence};
+use crate::master::association::AssociationConfig;
+use crate::master::error::{CommandError, CommandResponseError};
+use crate::master::request::*;
+use crate::tokio::test::*;
+use crate::util::cursor::WriteCursor;
+
+use super::harness::create_association;
+use super::harness::requests::{empty_response, integrity_poll_request};
+
+fn crob(status: CommandStatus) -> Group12Var1 {
+    let mut crob = Group12Var1::from_op_type(OpType::LatchOn);
+    crob.status = status;
+    crob
+}
+
+fn headers(second: CommandStatus) -> CommandHeaders {
+    let mut builder = CommandBuilder::new();
+    builder.add_u16(crob(CommandStatus::Success), 2);
+    builder.add_u16(crob(second), 5);
+    builder.build()
+}
+
+fn command_request(
+    io: &mut io::Handle,
+    seq: Sequence,
+    function: FunctionCode,
+    headers: CommandHeaders,
+) {
+    let mut buffer = [0; 64];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let mut request = start_request(ControlField::request(seq), function, &mut cursor).unwrap();
+    headers.write(&mut request).unwrap();
+    io.write(cursor.written());
+}
+
+fn command_response(io: &mut io::Handle, seq: Sequence, headers: CommandHeaders) {
+    let mut buffer = [0; 64];
+    let mut cursor = WriteCursor::new(&mut buffer);
+    let mut response = start_response(
+        ControlField::response(seq, true, true, false),
+        ResponseFunction::Response,
+        Iin::default(),
+        &mut cursor,
+    )
+    .unwrap();
+    headers.write(&mut response).unwrap();
+    io.read(cursor.written());
+}
+
+#[test]
+fn report_contains_status_of_every_control() {
+    let mut harness = create_association(AssociationConfig::quiet());
+
+    let mut association = harness.association.clone();
+    let mut operate = spawn(async move {
+        association
+            .operate_with_report(CommandMode::DirectOperate, headers(CommandStatus::Success))
+            .await
+    });
+    assert_pending!(operate.poll());
+
+    command_request(
+        &mut harness.io,
+        Sequence::default(),
+        FunctionCode::DirectOperate,
+        headers(CommandStatus::Success),
+    );
+    harness.assert_io();
+
+    command_response(
+        &mut harness.io,
+        Sequence::default(),
+        headers(CommandStatus::NotSupported),
+    );
+    harness.assert_io();
+
+    let report = assert_ready!(operate.poll()).unwrap();
+    assert_eq!(report.function, FunctionCode::DirectOperate);
+    assert_eq!(
+        report.outcome,
+        Err(CommandResponseError::BadStatus(CommandStatus::NotSupported))
+    );
+
+    let items: Vec<CommandItemResult> = report.items().copied().collect();
+    assert_eq!(
+        items,
+        vec![
+            CommandItemResult {
+                index: 2,
+                status: CommandStatus::Success,
+                value: CommandValue::G12V1(crob(CommandStatus::Success)),
+            },
+            CommandItemResult {
+                index: 5,
+                status: CommandStatus::NotSupported,
+                value: CommandValue::G12V1(crob(CommandStatus::NotSupported)),
+            },
+        ]
+    );
+    assert_eq!(report.failures().count(), 1);
+}
+
+#[test]
+fn selection_is_operated_with_the_next_sequence_number() {
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut seq = Sequence::default();
+
+    let mut association = harness.association.clone();
+    let mut select =
+        spawn(async move { association.select(headers(CommandStatus::Success)).await });
+    assert_pending!(select.poll());
+
+    command_request(
+        &mut harness.io,
+        seq,
+        FunctionCode::Select,
+        headers(CommandStatus::Success),
+    );
+    harness.assert_io();
+    command_response(&mut harness.io, seq, headers(CommandStatus::Success));
+    harness.assert_io();
+
+    let selection = assert_ready!(select.poll()).unwrap();
+    assert_eq!(selection.report().function, FunctionCode::Select);
+
+    // the operator confirms the command
+    crate::tokio::time::advance(std::time::Duration::from_secs(2));
+    assert_eq!(selection.elapsed(), std::time::Duration::from_secs(2));
+
+    let mut operate = spawn(async move { selection.operate().await });
+    assert_pending!(operate.poll());
+
+    seq.increment();
+    command_request(
+        &mut harness.io,
+        seq,
+        FunctionCode::Operate,
+        headers(CommandStatus::Success),
+    );
+    harness.assert_io();
+    command_response(&mut harness.io, seq, headers(CommandStatus::Success));
+    harness.assert_io();
+
+    assert_ready!(operate.poll()).unwrap();
+}
+
+fn selection_is_lost_after_reads(num_reads: usize) {
+    let mut harness = create_association(AssociationConfig::quiet());
+    let mut seq = Sequence::default();
+
+    let mut association = harness.association.clone();
+    let mut select =
+        spawn(async move { association.select(headers(CommandStatus::Success)).await });
+    assert_pending!(select.poll());
+
+    command_request(
+        &mut harness.io,
+        seq,
+        FunctionCode::Select,
+        headers(CommandStatus::Success),
+    );
+    harness.assert_io();
+    command_response(
+        &mut harness.io,
+        seq.increment(),
+        headers(CommandStatus::Success),
+    );
+    harness.assert_io();
+
+    let selection = assert_ready!(select.poll()).unwrap();
+
+    // integrity polls are performed before the selection is operated
+    for _ in 0..num_reads {
+        let mut association = harness.association.clone();
+        let mut read = spawn(async move {
+            association
+                .read(ReadRequest::class_scan(Classes::all()))
+                .await
+        });
+        assert_pending!(read.poll());
+        integrity_poll_request(&mut harness.io, seq);
+        harness.assert_io();
+        empty_response(&mut harness.io, seq.increment());
+        harness.assert_io();
+        assert_ready!(read.poll()).unwrap();
+    }
+
+    // the OPERATE is never sent
+    let mut operate = spawn(async move { selection.operate().await });
+    assert_pending!(operate.poll());
+    harness.assert_io();
+    assert_eq!(
+        assert_ready!(operate.poll()),
+        Err(CommandError::SelectionLost)
+    );
+}
+
+#[test]
+fn selection_is_lost_if_another_request_is_made() {
+    selection_is_lost_after_reads(1);
+}
+
+#[test]
+fn selection_is_lost_when_the_sequence_number_wraps_around() {
+    // the next sequence number is the one that would have been used by the OPERATE
+    selection_is_lost_after_reads(16);
+}
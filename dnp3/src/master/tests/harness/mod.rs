@@ -1,10 +1,10 @@
 use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::Poll;
 
 use crate::decode::AppDecodeLevel;
-use crate::link::header::{FrameInfo, FrameType};
+use crate::link::header::{AnyAddress, FrameInfo, FrameType};
 use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::master::association::AssociationConfig;
 use crate::master::handle::{AssociationHandle, HeaderInfo, MasterChannel, ReadHandler};
@@ -21,7 +21,7 @@ pub(crate) fn create_association(
 ) -> TestHarness<impl Future<Output = RunError>> {
     let (io, io_handle) = io::mock();
 
-    let mut io = PhysLayer::Mock(io);
+    let mut io = PhysLayer::mock(io);
 
     let outstation_address = EndpointAddress::from(1024).unwrap();
 
@@ -40,8 +40,10 @@ pub(crate) fn create_association(
         LinkErrorMode::Close,
         EndpointAddress::from(1).unwrap(),
         MasterSession::MIN_RX_BUFFER_SIZE,
+        None,
     );
 
+    let destinations = writer.get_inner().destinations();
     reader
         .get_inner()
         .set_rx_frame_info(FrameInfo::new(outstation_address, None, FrameType::Data));
@@ -69,6 +71,7 @@ pub(crate) fn create_association(
         association,
         num_requests,
         io: io_handle,
+        destinations,
     }
 }
 
@@ -153,6 +156,8 @@ pub(crate) struct TestHarness<F: Future<Output = RunError>> {
     pub(crate) association: AssociationHandle,
     pub(crate) num_requests: Arc<AtomicU64>,
     pub(crate) io: io::Handle,
+    /// destination of every fragment written by the master
+    pub(crate) destinations: Arc<Mutex<Vec<AnyAddress>>>,
 }
 
 impl<F: Future<Output = RunError>> TestHarness<F> {
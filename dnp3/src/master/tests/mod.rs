@@ -1,4 +1,6 @@
 mod harness;
 
 mod auto_tasks;
+mod broadcast;
+mod commands;
 mod startup;
@@ -1,9 +1,15 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
 use tracing::Instrument;
 
 use crate::app::{Listener, Shutdown};
 use crate::outstation::session::RunError;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::ConnectionState;
+use crate::tcp::{
+    AcceptReason, CloseReason, ConnectionEvent, ConnectionPolicy, RejectReason, SharedListener,
+};
 use crate::util::channel::{request_channel, Receiver, Sender};
 use crate::util::phys::PhysLayer;
 
@@ -12,12 +18,80 @@ use crate::util::phys::PhysLayer;
 #[derive(Debug)]
 pub(crate) struct NewSession {
     pub(crate) id: u64,
+    pub(crate) addr: SocketAddr,
     pub(crate) phys: PhysLayer,
 }
 
 impl NewSession {
-    pub(crate) fn new(id: u64, phys: PhysLayer) -> Self {
-        Self { id, phys }
+    pub(crate) fn new(id: u64, addr: SocketAddr, phys: PhysLayer) -> Self {
+        Self { id, addr, phys }
+    }
+}
+
+/// tracks the sessions that are active across all of the tasks serving a single outstation
+pub(crate) struct SessionTracker {
+    policy: ConnectionPolicy,
+    // connection id assigned to each task
+    sessions: Vec<Option<u64>>,
+    active: usize,
+    listener: Box<dyn Listener<ConnectionState>>,
+}
+
+impl SessionTracker {
+    pub(crate) fn new(
+        policy: ConnectionPolicy,
+        listener: Box<dyn Listener<ConnectionState>>,
+    ) -> Self {
+        Self {
+            policy,
+            sessions: vec![None; policy.max_sessions()],
+            active: 0,
+            listener,
+        }
+    }
+
+    /// assign a connection to one of the tasks according to the policy
+    pub(crate) fn assign(&mut self, id: u64) -> Result<(usize, AcceptReason), RejectReason> {
+        if let Some(index) = self.sessions.iter().position(|x| x.is_none()) {
+            self.sessions[index] = Some(id);
+            return Ok((index, AcceptReason::CapacityAvailable));
+        }
+
+        match self.policy {
+            ConnectionPolicy::ReplaceOldest => {
+                // connection ids are assigned in ascending order
+                let oldest = self
+                    .sessions
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, x)| **x)
+                    .map(|(index, _)| index)
+                    .unwrap_or(0);
+                self.sessions[oldest] = Some(id);
+                Ok((oldest, AcceptReason::ReplacedOldest))
+            }
+            ConnectionPolicy::RejectNew | ConnectionPolicy::AcceptConcurrent(_) => {
+                Err(RejectReason::SessionLimit)
+            }
+        }
+    }
+
+    fn started(&mut self) {
+        self.active += 1;
+        if self.active == 1 {
+            self.listener.update(ConnectionState::Connected);
+        }
+    }
+
+    fn ended(&mut self, index: usize, id: u64) {
+        // the task may have already been assigned a replacement connection
+        if self.sessions[index] == Some(id) {
+            self.sessions[index] = None;
+        }
+        self.active = self.active.saturating_sub(1);
+        if self.active == 0 {
+            self.listener.update(ConnectionState::Disconnected);
+        }
     }
 }
 
@@ -26,20 +100,26 @@ impl NewSession {
 pub(crate) struct OutstationTaskAdapter {
     receiver: Receiver<NewSession>,
     task: OutstationTask,
-    listener: Box<dyn Listener<ConnectionState>>,
+    index: usize,
+    tracker: Arc<Mutex<SessionTracker>>,
+    events: SharedListener<ConnectionEvent>,
 }
 
 impl OutstationTaskAdapter {
     pub(crate) fn create(
         task: OutstationTask,
-        listener: Box<dyn Listener<ConnectionState>>,
+        index: usize,
+        tracker: Arc<Mutex<SessionTracker>>,
+        events: SharedListener<ConnectionEvent>,
     ) -> (Self, Sender<NewSession>) {
         let (tx, rx) = request_channel();
         (
             Self {
                 receiver: rx,
                 task,
-                listener,
+                index,
+                tracker,
+                events,
             },
             tx,
         )
@@ -79,13 +159,15 @@ impl OutstationTaskAdapter {
                 }
                 Some(mut s) => {
                     let id = s.id;
+                    let addr = s.addr;
 
-                    self.listener.update(ConnectionState::Connected);
+                    self.task.set_connection(id, addr);
+                    self.tracker.lock().unwrap().started();
                     let result = self
                         .run_one_session(&mut s.phys)
                         .instrument(tracing::info_span!("Session", "id" = id))
                         .await;
-                    self.listener.update(ConnectionState::Disconnected);
+                    self.tracker.lock().unwrap().ended(self.index, id);
 
                     // reset outstation state in between sessions
                     self.task.reset();
@@ -97,17 +179,66 @@ impl OutstationTaskAdapter {
                                 id,
                                 new_session.id
                             );
+                            self.closed(id, addr, CloseReason::Replaced);
                             // go to next iteration with a new session
                             session.replace(new_session);
                         }
                         Err(RunError::Link(err)) => {
                             // go to next iteration to get a new session
                             tracing::warn!("Session error: {}", err);
+                            self.closed(id, addr, CloseReason::LinkError);
+                        }
+                        Err(RunError::Shutdown) => {
+                            self.closed(id, addr, CloseReason::Shutdown);
+                            return Err(Shutdown);
                         }
-                        Err(RunError::Shutdown) => return Err(Shutdown),
                     }
                 }
             }
         }
     }
+
+    fn closed(&self, id: u64, addr: SocketAddr, reason: CloseReason) {
+        self.events
+            .update(ConnectionEvent::Closed { id, addr, reason });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::NullListener;
+
+    fn tracker(policy: ConnectionPolicy) -> SessionTracker {
+        SessionTracker::new(policy, NullListener::create())
+    }
+
+    #[test]
+    fn replaces_oldest_session() {
+        let mut tracker = tracker(ConnectionPolicy::ReplaceOldest);
+        assert_eq!(tracker.assign(0), Ok((0, AcceptReason::CapacityAvailable)));
+        assert_eq!(tracker.assign(1), Ok((0, AcceptReason::ReplacedOldest)));
+        // the replaced session ending doesn't free the task
+        tracker.ended(0, 0);
+        assert_eq!(tracker.assign(2), Ok((0, AcceptReason::ReplacedOldest)));
+    }
+
+    #[test]
+    fn rejects_new_session_while_one_is_active() {
+        let mut tracker = tracker(ConnectionPolicy::RejectNew);
+        assert_eq!(tracker.assign(0), Ok((0, AcceptReason::CapacityAvailable)));
+        assert_eq!(tracker.assign(1), Err(RejectReason::SessionLimit));
+        tracker.ended(0, 0);
+        assert_eq!(tracker.assign(2), Ok((0, AcceptReason::CapacityAvailable)));
+    }
+
+    #[test]
+    fn accepts_concurrent_sessions_up_to_limit() {
+        let mut tracker = tracker(ConnectionPolicy::AcceptConcurrent(2));
+        assert_eq!(tracker.assign(0), Ok((0, AcceptReason::CapacityAvailable)));
+        assert_eq!(tracker.assign(1), Ok((1, AcceptReason::CapacityAvailable)));
+        assert_eq!(tracker.assign(2), Err(RejectReason::SessionLimit));
+        tracker.ended(0, 0);
+        assert_eq!(tracker.assign(3), Ok((0, AcceptReason::CapacityAvailable)));
+    }
 }
@@ -0,0 +1,273 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::app::control::{CommandStatus, CommandValue};
+use crate::app::Timestamp;
+use crate::outstation::traits::{OperateType, RequestContext};
+
+/// Control operation recorded in a [CommandAuditRecord]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AuditOperation {
+    /// SELECT of a control point
+    Select,
+    /// operation of a control point
+    Operate(OperateType),
+}
+
+/// Record of a single control point in a SELECT, OPERATE, DIRECT_OPERATE or DIRECT_OPERATE_NO_RESPONSE request
+///
+/// Records are produced for every control point in the request, including ones that were
+/// rejected by the outstation without invoking the [ControlHandler](crate::outstation::ControlHandler),
+/// e.g. because the select timed out, the sequence numbers didn't match, or the request contained
+/// too many controls.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CommandAuditRecord {
+    /// time at which the control was processed
+    pub timestamp: SystemTime,
+    /// information about the request and who sent it
+    pub context: RequestContext,
+    /// operation performed on the control point
+    pub operation: AuditOperation,
+    /// index of the control point
+    pub index: u16,
+    /// value of the control as received in the request
+    pub value: CommandValue,
+    /// resulting status of the control
+    pub status: CommandStatus,
+}
+
+/// Receives an audit record for every control processed by the outstation
+///
+/// Records for a request are delivered once the request has been fully processed,
+/// i.e. after any controls that complete asynchronously have finished. The sink is
+/// invoked from the outstation task and should not block for long periods.
+pub trait CommandAuditSink: Send + 'static {
+    /// record a processed control
+    fn record(&mut self, record: &CommandAuditRecord);
+}
+
+pub(crate) type SharedAuditSink = Arc<Mutex<Box<dyn CommandAuditSink>>>;
+
+/// [CommandAuditSink] that appends one line per record to a local file, rotating it when it reaches a maximum size
+///
+/// When the file at `path` would exceed `max_size` bytes, it is renamed to `path.1`,
+/// `path.1` is renamed to `path.2`, and so on. At most `max_files` rotated files are kept.
+///
+/// Each line contains the following comma-separated fields: timestamp, session id, master address,
+/// remote address, sequence, function, operation, index, status, value
+pub struct RotatingFileSink {
+    path: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFileSink {
+    /// Open (or create) the audit file at `path`, appending to it if it already exists
+    pub fn new<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = Self::open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_size,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    fn open(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            // shift the existing files, discarding the oldest
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = Self::open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64;
+        if self.size > 0 && self.size + len > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn format(record: &CommandAuditRecord) -> String {
+        let timestamp = match Timestamp::try_from_system_time(record.timestamp) {
+            Some(x) => x.to_string(),
+            None => "invalid".to_string(),
+        };
+        let session = match record.context.session_id {
+            Some(x) => x.to_string(),
+            None => "-".to_string(),
+        };
+        let remote = match record.context.remote_addr {
+            Some(x) => x.to_string(),
+            None => "-".to_string(),
+        };
+        format!(
+            "{},{},{},{},{},{:?},{:?},{},{:?},{}\n",
+            timestamp,
+            session,
+            record.context.master_address,
+            remote,
+            record.context.seq.value(),
+            record.context.function,
+            record.operation,
+            record.index,
+            record.status,
+            record.value
+        )
+    }
+}
+
+impl CommandAuditSink for RotatingFileSink {
+    fn record(&mut self, record: &CommandAuditRecord) {
+        let line = Self::format(record);
+        if let Err(err) = self.write_line(&line) {
+            tracing::error!(
+                "unable to write command audit record to {}: {}",
+                self.path.display(),
+                err
+            );
+        }
+    }
+}
+
+/// records produced while processing a request, delivered to the sink when processing completes
+pub(crate) struct CommandAudit {
+    sink: Option<SharedAuditSink>,
+    records: Vec<CommandAuditRecord>,
+}
+
+impl CommandAudit {
+    pub(crate) fn new() -> Self {
+        Self {
+            sink: None,
+            records: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_sink(&mut self, sink: SharedAuditSink) {
+        self.sink = Some(sink);
+    }
+
+    /// add a record, returning its position if a sink is installed
+    pub(crate) fn record(
+        &mut self,
+        context: &RequestContext,
+        operation: AuditOperation,
+        index: u16,
+        value: CommandValue,
+        status: CommandStatus,
+    ) -> Option<usize> {
+        self.sink.as_ref()?;
+        self.records.push(CommandAuditRecord {
+            timestamp: SystemTime::now(),
+            context: *context,
+            operation,
+            index,
+            value,
+            status,
+        });
+        Some(self.records.len() - 1)
+    }
+
+    /// update the status of a control that completed asynchronously
+    pub(crate) fn complete(&mut self, record: usize, status: CommandStatus) {
+        if let Some(x) = self.records.get_mut(record) {
+            x.status = status;
+        }
+    }
+
+    /// deliver the accumulated records to the sink
+    pub(crate) fn flush(&mut self) {
+        if self.records.is_empty() {
+            return;
+        }
+        if let Some(sink) = &self.sink {
+            let mut sink = sink.lock().unwrap();
+            for record in self.records.iter() {
+                sink.record(record);
+            }
+        }
+        self.records.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::variations::Group41Var2;
+    use crate::app::{FunctionCode, Sequence};
+    use crate::link::EndpointAddress;
+
+    fn record(index: u16) -> CommandAuditRecord {
+        CommandAuditRecord {
+            timestamp: SystemTime::UNIX_EPOCH,
+            context: RequestContext {
+                master_address: EndpointAddress::from(1).unwrap(),
+                broadcast: None,
+                session_id: Some(3),
+                remote_addr: None,
+                seq: Sequence::new(4),
+                function: FunctionCode::DirectOperate,
+            },
+            operation: AuditOperation::Operate(OperateType::DirectOperate),
+            index,
+            value: CommandValue::G41V2(Group41Var2::new(513)),
+            status: CommandStatus::Success,
+        }
+    }
+
+    #[test]
+    fn rotating_file_sink_rotates_when_full() {
+        let dir = std::env::temp_dir().join(format!("dnp3-audit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.log");
+        let line_len = RotatingFileSink::format(&record(0)).len() as u64;
+
+        let mut sink = RotatingFileSink::new(&path, 2 * line_len, 2).unwrap();
+        for index in 0..7 {
+            sink.record(&record(index));
+        }
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap().lines().count();
+        assert_eq!(read(path.clone()), 1);
+        assert_eq!(read(sink.rotated_path(1)), 2);
+        assert_eq!(read(sink.rotated_path(2)), 2);
+        assert!(!sink.rotated_path(3).exists());
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with(
+            "1970-01-01T00:00:00.000Z,3,1,-,4,DirectOperate,Operate(DirectOperate),6,Success,"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
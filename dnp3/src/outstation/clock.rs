@@ -0,0 +1,184 @@
+use std::time::{Duration, SystemTime};
+
+use crate::app::measurement::Time;
+use crate::app::Timestamp;
+
+/// Configuration of the clock maintained by the outstation
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockConfig {
+    /// amount of time after a successful synchronization before NEED_TIME is asserted again
+    ///
+    /// A value of `None` means that NEED_TIME is only asserted until the first synchronization
+    pub need_time_interval: Option<Duration>,
+}
+
+impl ClockConfig {
+    /// Default interval after which NEED_TIME is asserted again
+    pub const DEFAULT_NEED_TIME_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+    /// construct a `ClockConfig` from its fields
+    pub fn new(need_time_interval: Option<Duration>) -> Self {
+        Self { need_time_interval }
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self::new(Some(Self::DEFAULT_NEED_TIME_INTERVAL))
+    }
+}
+
+/// Difference between the outstation clock and the master measured during a synchronization
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockDrift {
+    /// amount of time in milliseconds that the clock was adjusted by
+    ///
+    /// A positive value means the outstation clock was behind the master
+    pub correction_ms: i64,
+    /// amount of time that elapsed since the previous synchronization
+    pub interval: Duration,
+}
+
+impl ClockDrift {
+    /// drift rate of the outstation clock in parts-per-million
+    pub fn ppm(&self) -> f64 {
+        let interval_ms = self.interval.as_secs_f64() * 1000.0;
+        if interval_ms == 0.0 {
+            return 0.0;
+        }
+        (self.correction_ms as f64 / interval_ms) * 1_000_000.0
+    }
+}
+
+/// Current state of the outstation clock
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockStatus {
+    /// current time of the clock
+    pub time: Time,
+    /// true if the outstation is requesting time synchronization (IIN1.4)
+    pub need_time: bool,
+    /// drift measured during the most recent synchronization, if any
+    pub drift: Option<ClockDrift>,
+}
+
+#[derive(Copy, Clone)]
+struct Reference {
+    time: Timestamp,
+    instant: crate::tokio::time::Instant,
+}
+
+impl Reference {
+    fn now(&self) -> Timestamp {
+        let elapsed = crate::tokio::time::Instant::now().saturating_duration_since(self.instant);
+        self.time
+            .checked_add(elapsed)
+            .unwrap_or_else(Timestamp::max)
+    }
+}
+
+/// clock that is synchronized by the master using the LAN or non-LAN procedures
+pub(crate) struct Clock {
+    config: ClockConfig,
+    reference: Option<Reference>,
+    drift: Option<ClockDrift>,
+}
+
+impl Clock {
+    pub(crate) fn new(config: ClockConfig) -> Self {
+        Self {
+            config,
+            reference: None,
+            drift: None,
+        }
+    }
+
+    pub(crate) fn config(&self) -> ClockConfig {
+        self.config
+    }
+
+    pub(crate) fn need_time(&self) -> bool {
+        match (self.reference, self.config.need_time_interval) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(reference), Some(interval)) => {
+                crate::tokio::time::Instant::now().saturating_duration_since(reference.instant)
+                    >= interval
+            }
+        }
+    }
+
+    /// current time, reported as unsynchronized if the clock needs to be synchronized
+    pub(crate) fn now(&self) -> Time {
+        let timestamp = match self.reference {
+            Some(reference) => reference.now(),
+            None => {
+                Timestamp::try_from_system_time(SystemTime::now()).unwrap_or_else(Timestamp::min)
+            }
+        };
+
+        if self.need_time() {
+            Time::NotSynchronized(timestamp)
+        } else {
+            Time::Synchronized(timestamp)
+        }
+    }
+
+    /// apply a time written by the master
+    pub(crate) fn write(&mut self, time: Timestamp) {
+        let now = crate::tokio::time::Instant::now();
+
+        if let Some(reference) = self.reference {
+            let correction_ms = time.raw_value() as i64 - reference.now().raw_value() as i64;
+            self.drift = Some(ClockDrift {
+                correction_ms,
+                interval: now.saturating_duration_since(reference.instant),
+            });
+            tracing::info!("clock corrected by {} ms", correction_ms);
+        }
+
+        self.reference = Some(Reference { time, instant: now });
+    }
+
+    pub(crate) fn status(&self) -> ClockStatus {
+        ClockStatus {
+            time: self.now(),
+            need_time: self.need_time(),
+            drift: self.drift,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_drift_between_synchronizations() {
+        let mut clock = Clock::new(ClockConfig::new(Some(Duration::from_secs(60))));
+        assert!(clock.need_time());
+
+        clock.write(Timestamp::new(1_000_000));
+        assert!(!clock.need_time());
+
+        crate::tokio::time::advance(Duration::from_secs(50));
+        assert_eq!(clock.now(), Time::Synchronized(Timestamp::new(1_050_000)));
+
+        // master says 5 ms more time has elapsed
+        clock.write(Timestamp::new(1_050_005));
+        assert_eq!(
+            clock.status().drift,
+            Some(ClockDrift {
+                correction_ms: 5,
+                interval: Duration::from_secs(50),
+            })
+        );
+        assert_eq!(clock.status().drift.unwrap().ppm(), 100.0);
+
+        crate::tokio::time::advance(Duration::from_secs(60));
+        assert!(clock.need_time());
+        assert_eq!(
+            clock.now(),
+            Time::NotSynchronized(Timestamp::new(1_110_005))
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::decode::DecodeLevel;
-use crate::link::EndpointAddress;
-use crate::outstation::database::ClassZeroConfig;
+use crate::link::{EndpointAddress, LinkConfirmConfig};
+use crate::outstation::clock::ClockConfig;
+use crate::outstation::database::{ClassZeroConfig, EventClass};
 use crate::util::buffer::Buffer;
 
 /// Validated buffer size for use in the outstation
@@ -83,6 +84,62 @@ impl Default for Features {
     }
 }
 
+/// Controls how long events of a particular class are held before an unsolicited response is sent
+///
+/// An unsolicited response is sent when either the hold time has elapsed since the first
+/// unreported event of the class was detected, or the number of unreported events of the class
+/// reaches the hold count, whichever comes first.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UnsolicitedHold {
+    /// maximum amount of time that unreported events of the class are held
+    pub hold_time: std::time::Duration,
+    /// number of unreported events of the class that triggers an unsolicited response
+    /// before the hold time elapses. A value of zero is treated as one.
+    pub hold_count: usize,
+}
+
+impl UnsolicitedHold {
+    /// report events of the class as soon as they are detected
+    pub fn immediate() -> Self {
+        Self::new(std::time::Duration::from_secs(0), 1)
+    }
+
+    /// construct an `UnsolicitedHold` from a hold time and hold count
+    pub fn new(hold_time: std::time::Duration, hold_count: usize) -> Self {
+        Self {
+            hold_time,
+            hold_count,
+        }
+    }
+}
+
+impl Default for UnsolicitedHold {
+    fn default() -> Self {
+        Self::immediate()
+    }
+}
+
+/// Per-class hold settings used to coalesce events into fewer unsolicited responses
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct UnsolicitedHoldConfig {
+    /// hold settings for class 1 events (default == immediate)
+    pub class1: UnsolicitedHold,
+    /// hold settings for class 2 events (default == immediate)
+    pub class2: UnsolicitedHold,
+    /// hold settings for class 3 events (default == immediate)
+    pub class3: UnsolicitedHold,
+}
+
+impl UnsolicitedHoldConfig {
+    pub(crate) fn get(&self, class: EventClass) -> UnsolicitedHold {
+        match class {
+            EventClass::Class1 => self.class1,
+            EventClass::Class2 => self.class2,
+            EventClass::Class3 => self.class3,
+        }
+    }
+}
+
 /// Outstation configuration parameters
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct OutstationConfig {
@@ -102,12 +159,19 @@ pub struct OutstationConfig {
     pub confirm_timeout: std::time::Duration,
     /// timeout after which a matching OPERATE will fail with SELECT_TIMEOUT
     pub select_timeout: std::time::Duration,
+    /// maximum amount of time to wait for controls that complete asynchronously before responding
+    ///
+    /// Controls that haven't completed when this timeout elapses are reported with `CommandStatus::Timeout`.
+    /// The master's response timeout should be longer than this value.
+    pub operate_timeout: std::time::Duration,
     /// optional features that can be enabled
     pub features: Features,
     /// number of non-regenerated unsolicited retries to perform
     pub max_unsolicited_retries: Option<usize>,
     /// amount of time to wait after a failed unsolicited response series before starting another series
     pub unsolicited_retry_delay: std::time::Duration,
+    /// per-class hold time and hold count used to coalesce events into unsolicited responses
+    pub unsolicited_hold: UnsolicitedHoldConfig,
     /// time without any link activity before the outstation will send REQUEST_LINK_STATES
     ///
     /// A value of `None` will disable this feature
@@ -123,6 +187,18 @@ pub struct OutstationConfig {
     pub max_controls_per_request: Option<u16>,
     /// controls responses to class 0 READ requests
     pub class_zero: ClassZeroConfig,
+    /// optional clock maintained by the outstation from time synchronization requests
+    ///
+    /// When enabled, the outstation applies time writes to the clock, manages the NEED_TIME IIN bit,
+    /// and stamps database updates that do not specify a time. A value of `None` leaves time
+    /// synchronization to `OutstationApplication::write_absolute_time`.
+    ///
+    /// The clock belongs to the static database, so outstations that share a database also share
+    /// its clock. The first outstation that enables it determines the `ClockConfig`, and a different
+    /// configuration on another outstation is ignored with a warning.
+    pub clock: Option<ClockConfig>,
+    /// optional confirmed data link service, typically only used on serial links
+    pub link_confirm: Option<LinkConfirmConfig>,
 }
 
 impl Feature {
@@ -142,6 +218,8 @@ impl OutstationConfig {
     pub const DEFAULT_CONFIRM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
     /// Default select timeout
     pub const DEFAULT_SELECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+    /// Default timeout for controls that complete asynchronously
+    pub const DEFAULT_OPERATE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
     /// Default unsolicited retry delay between series
     pub const DEFAULT_UNSOLICITED_RETRY_DELAY: std::time::Duration =
         std::time::Duration::from_secs(5);
@@ -158,13 +236,17 @@ impl OutstationConfig {
             decode_level: DecodeLevel::nothing(),
             confirm_timeout: Self::DEFAULT_CONFIRM_TIMEOUT,
             select_timeout: Self::DEFAULT_SELECT_TIMEOUT,
+            operate_timeout: Self::DEFAULT_OPERATE_TIMEOUT,
             features: Features::default(),
             max_unsolicited_retries: None,
             unsolicited_retry_delay: Self::DEFAULT_UNSOLICITED_RETRY_DELAY,
+            unsolicited_hold: UnsolicitedHoldConfig::default(),
             keep_alive_timeout: Some(std::time::Duration::from_secs(60)),
             max_read_request_headers: None,
             max_controls_per_request: None,
             class_zero: ClassZeroConfig::default(),
+            clock: None,
+            link_confirm: None,
         }
     }
 }
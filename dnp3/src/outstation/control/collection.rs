@@ -5,29 +5,55 @@ use crate::app::parse::parser::{HeaderCollection, HeaderDetails, HeaderIterator,
 use crate::app::parse::prefix::Prefix;
 use crate::app::parse::traits::{FixedSizeVariation, Index};
 use crate::app::{QualifierCode, Variation};
+use crate::outstation::audit::{AuditOperation, CommandAudit};
 use crate::outstation::control::control_type::ControlType;
+use crate::outstation::control::pending::PendingControls;
 use crate::outstation::control::prefix::PrefixWriter;
 use crate::outstation::database::Database;
-use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType};
+use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType, RequestContext};
+use crate::outstation::OperateResult;
 use crate::util::cursor::{WriteCursor, WriteError};
 
 pub(crate) struct ControlTransaction<'a> {
     stared: bool,
     handler: &'a mut dyn ControlHandler,
+    context: RequestContext,
+    audit: &'a mut CommandAudit,
 }
 
 impl<'a> ControlTransaction<'a> {
-    pub(crate) fn new(handler: &'a mut dyn ControlHandler) -> Self {
+    pub(crate) fn new(
+        handler: &'a mut dyn ControlHandler,
+        context: RequestContext,
+        audit: &'a mut CommandAudit,
+    ) -> Self {
         ControlTransaction {
             stared: false,
             handler,
+            context,
+            audit,
         }
     }
 
+    /// record the outcome of a control in the command audit
+    fn audit<V>(
+        &mut self,
+        operation: AuditOperation,
+        index: u16,
+        value: V,
+        status: CommandStatus,
+    ) -> Option<usize>
+    where
+        V: Into<CommandValue>,
+    {
+        self.audit
+            .record(&self.context, operation, index, value.into(), status)
+    }
+
     fn start(&mut self) {
         if !self.stared {
             self.stared = true;
-            self.handler.begin_fragment();
+            self.handler.begin_fragment(&self.context);
         }
     }
 }
@@ -35,123 +61,38 @@ impl<'a> ControlTransaction<'a> {
 impl<'a> Drop for ControlTransaction<'a> {
     fn drop(&mut self) {
         if self.stared {
-            self.handler.end_fragment();
+            self.handler.end_fragment(&self.context);
         }
     }
 }
 
-impl<'a> ControlSupport<Group12Var1> for ControlTransaction<'a> {
-    fn select(
-        &mut self,
-        control: Group12Var1,
-        index: u16,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.select(control, index, database)
-    }
-
-    fn operate(
-        &mut self,
-        control: Group12Var1,
-        index: u16,
-        op_type: OperateType,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.operate(control, index, op_type, database)
-    }
-}
-
-impl<'a> ControlSupport<Group41Var1> for ControlTransaction<'a> {
-    fn select(
-        &mut self,
-        control: Group41Var1,
-        index: u16,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.select(control, index, database)
-    }
-
-    fn operate(
-        &mut self,
-        control: Group41Var1,
-        index: u16,
-        op_type: OperateType,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.operate(control, index, op_type, database)
-    }
-}
-
-impl<'a> ControlSupport<Group41Var2> for ControlTransaction<'a> {
-    fn select(
-        &mut self,
-        control: Group41Var2,
-        index: u16,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.select(control, index, database)
-    }
-
-    fn operate(
-        &mut self,
-        control: Group41Var2,
-        index: u16,
-        op_type: OperateType,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.operate(control, index, op_type, database)
-    }
-}
-
-impl<'a> ControlSupport<Group41Var3> for ControlTransaction<'a> {
-    fn select(
-        &mut self,
-        control: Group41Var3,
-        index: u16,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.select(control, index, database)
-    }
-
-    fn operate(
-        &mut self,
-        control: Group41Var3,
-        index: u16,
-        op_type: OperateType,
-        database: &mut Database,
-    ) -> CommandStatus {
-        self.start();
-        self.handler.operate(control, index, op_type, database)
-    }
-}
-
-impl<'a> ControlSupport<Group41Var4> for ControlTransaction<'a> {
-    fn select(
+impl<'a> ControlTransaction<'a> {
+    pub(crate) fn select<T>(
         &mut self,
-        control: Group41Var4,
+        control: T,
         index: u16,
         database: &mut Database,
-    ) -> CommandStatus {
+    ) -> CommandStatus
+    where
+        dyn ControlHandler: ControlSupport<T>,
+    {
         self.start();
-        self.handler.select(control, index, database)
+        self.handler.select(control, index, &self.context, database)
     }
 
-    fn operate(
+    pub(crate) fn begin_operate<T>(
         &mut self,
-        control: Group41Var4,
+        control: T,
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
+    ) -> OperateResult
+    where
+        dyn ControlHandler: ControlSupport<T>,
+    {
         self.start();
-        self.handler.operate(control, index, op_type, database)
+        self.handler
+            .begin_operate(control, index, op_type, &self.context, database)
     }
 }
 
@@ -258,10 +199,11 @@ impl<'a> ControlCollection<'a> {
     pub(crate) fn respond_with_status(
         &self,
         cursor: &mut WriteCursor,
+        transaction: &mut ControlTransaction,
         status: CommandStatus,
     ) -> Result<(), WriteError> {
         for header in self.iter() {
-            header.respond_with_status(cursor, status)?;
+            header.respond_with_status(cursor, transaction, status)?;
         }
         Ok(())
     }
@@ -295,6 +237,7 @@ impl<'a> ControlCollection<'a> {
         transaction: &mut ControlTransaction,
         database: &mut Database,
         max_controls_per_request: Option<u16>,
+        pending: &mut PendingControls,
     ) -> Result<CommandStatus, WriteError> {
         let mut num_controls = 0;
         let mut status = CommandStatus::Success;
@@ -306,6 +249,7 @@ impl<'a> ControlCollection<'a> {
                 database,
                 max_controls_per_request,
                 &mut num_controls,
+                pending,
             )?;
             status = status.first_error(result);
         }
@@ -317,6 +261,7 @@ impl<'a> ControlCollection<'a> {
         transaction: &mut ControlTransaction,
         database: &mut Database,
         max_controls_per_request: Option<u16>,
+        pending: &mut PendingControls,
     ) {
         let mut num_controls = 0;
         for header in self.iter() {
@@ -325,6 +270,7 @@ impl<'a> ControlCollection<'a> {
                 database,
                 max_controls_per_request,
                 &mut num_controls,
+                pending,
             );
         }
     }
@@ -351,19 +297,20 @@ impl<'a> ControlHeader<'a> {
     fn respond_with_status(
         &self,
         cursor: &mut WriteCursor,
+        transaction: &mut ControlTransaction,
         status: CommandStatus,
     ) -> Result<(), WriteError> {
         match self {
-            Self::OneByteGroup12Var1(seq) => respond_with_status(cursor, seq, status),
-            Self::OneByteGroup41Var1(seq) => respond_with_status(cursor, seq, status),
-            Self::OneByteGroup41Var2(seq) => respond_with_status(cursor, seq, status),
-            Self::OneByteGroup41Var3(seq) => respond_with_status(cursor, seq, status),
-            Self::OneByteGroup41Var4(seq) => respond_with_status(cursor, seq, status),
-            Self::TwoByteGroup12Var1(seq) => respond_with_status(cursor, seq, status),
-            Self::TwoByteGroup41Var1(seq) => respond_with_status(cursor, seq, status),
-            Self::TwoByteGroup41Var2(seq) => respond_with_status(cursor, seq, status),
-            Self::TwoByteGroup41Var3(seq) => respond_with_status(cursor, seq, status),
-            Self::TwoByteGroup41Var4(seq) => respond_with_status(cursor, seq, status),
+            Self::OneByteGroup12Var1(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::OneByteGroup41Var1(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::OneByteGroup41Var2(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::OneByteGroup41Var3(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::OneByteGroup41Var4(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::TwoByteGroup12Var1(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::TwoByteGroup41Var1(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::TwoByteGroup41Var2(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::TwoByteGroup41Var3(seq) => respond_with_status(cursor, transaction, seq, status),
+            Self::TwoByteGroup41Var4(seq) => respond_with_status(cursor, transaction, seq, status),
         }
     }
 
@@ -459,6 +406,7 @@ impl<'a> ControlHeader<'a> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn operate_with_response(
         &self,
         operate_type: OperateType,
@@ -467,6 +415,7 @@ impl<'a> ControlHeader<'a> {
         database: &mut Database,
         max_controls_per_request: Option<u16>,
         num_controls: &mut u16,
+        pending: &mut PendingControls,
     ) -> Result<CommandStatus, WriteError> {
         match self {
             Self::OneByteGroup12Var1(seq) => operate_header_with_response(
@@ -477,6 +426,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var1(seq) => operate_header_with_response(
                 cursor,
@@ -486,6 +436,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var2(seq) => operate_header_with_response(
                 cursor,
@@ -495,6 +446,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var3(seq) => operate_header_with_response(
                 cursor,
@@ -504,6 +456,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var4(seq) => operate_header_with_response(
                 cursor,
@@ -513,6 +466,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup12Var1(seq) => operate_header_with_response(
                 cursor,
@@ -522,6 +476,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var1(seq) => operate_header_with_response(
                 cursor,
@@ -531,6 +486,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var2(seq) => operate_header_with_response(
                 cursor,
@@ -540,6 +496,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var3(seq) => operate_header_with_response(
                 cursor,
@@ -549,6 +506,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var4(seq) => operate_header_with_response(
                 cursor,
@@ -558,6 +516,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
         }
     }
@@ -568,6 +527,7 @@ impl<'a> ControlHeader<'a> {
         database: &mut Database,
         max_controls_per_request: Option<u16>,
         num_controls: &mut u16,
+        pending: &mut PendingControls,
     ) {
         match self {
             Self::OneByteGroup12Var1(seq) => operate_header_no_ack(
@@ -576,6 +536,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var1(seq) => operate_header_no_ack(
                 seq,
@@ -583,6 +544,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var2(seq) => operate_header_no_ack(
                 seq,
@@ -590,6 +552,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var3(seq) => operate_header_no_ack(
                 seq,
@@ -597,6 +560,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::OneByteGroup41Var4(seq) => operate_header_no_ack(
                 seq,
@@ -604,6 +568,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup12Var1(seq) => operate_header_no_ack(
                 seq,
@@ -611,6 +576,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var1(seq) => operate_header_no_ack(
                 seq,
@@ -618,6 +584,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var2(seq) => operate_header_no_ack(
                 seq,
@@ -625,6 +592,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var3(seq) => operate_header_no_ack(
                 seq,
@@ -632,6 +600,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
             Self::TwoByteGroup41Var4(seq) => operate_header_no_ack(
                 seq,
@@ -639,6 +608,7 @@ impl<'a> ControlHeader<'a> {
                 transaction,
                 max_controls_per_request,
                 num_controls,
+                pending,
             ),
         }
     }
@@ -646,6 +616,7 @@ impl<'a> ControlHeader<'a> {
 
 fn respond_with_status<I, V>(
     cursor: &mut WriteCursor,
+    transaction: &mut ControlTransaction,
     seq: &CountSequence<Prefix<I, V>>,
     status: CommandStatus,
 ) -> Result<(), WriteError>
@@ -656,6 +627,13 @@ where
     let mut writer = PrefixWriter::new();
     for item in seq.iter() {
         writer.write(cursor, item.value.with_status(status), item.index)?;
+        // only OPERATE requests are rejected without invoking the handler
+        transaction.audit(
+            AuditOperation::Operate(OperateType::SelectBeforeOperate),
+            item.index.widen_to_u16(),
+            item.value,
+            status,
+        );
     }
     Ok(())
 }
@@ -682,12 +660,19 @@ where
             CommandStatus::TooManyOps
         };
         writer.write(cursor, item.value.with_status(status), item.index)?;
+        transaction.audit(
+            AuditOperation::Select,
+            item.index.widen_to_u16(),
+            item.value,
+            status,
+        );
         ret = ret.first_error(status);
         *num_controls += 1;
     }
     Ok(ret)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn operate_header_with_response<I, V>(
     cursor: &mut WriteCursor,
     seq: &CountSequence<Prefix<I, V>>,
@@ -696,6 +681,7 @@ fn operate_header_with_response<I, V>(
     transaction: &mut ControlTransaction,
     max_controls_per_request: Option<u16>,
     num_controls: &mut u16,
+    pending: &mut PendingControls,
 ) -> Result<CommandStatus, WriteError>
 where
     I: Index,
@@ -704,7 +690,7 @@ where
     let mut writer = PrefixWriter::new();
     let mut ret = CommandStatus::Success;
     for item in seq.iter() {
-        let status = if max_controls_per_request.map_or(true, |max| *num_controls < max) {
+        let result = if max_controls_per_request.map_or(true, |max| *num_controls < max) {
             item.value.operate(
                 transaction,
                 item.index.widen_to_u16(),
@@ -712,10 +698,35 @@ where
                 database,
             )
         } else {
-            CommandStatus::TooManyOps
+            OperateResult::Complete(CommandStatus::TooManyOps)
         };
-        writer.write(cursor, item.value.with_status(status), item.index)?;
-        ret = ret.first_error(status);
+        match result {
+            OperateResult::Complete(status) => {
+                writer.write(cursor, item.value.with_status(status), item.index)?;
+                transaction.audit(
+                    AuditOperation::Operate(operate_type),
+                    item.index.widen_to_u16(),
+                    item.value,
+                    status,
+                );
+                ret = ret.first_error(status);
+            }
+            OperateResult::Pending(operation) => {
+                // the status is the last byte of every control object and is filled in once the operation completes
+                writer.write(
+                    cursor,
+                    item.value.with_status(CommandStatus::Success),
+                    item.index,
+                )?;
+                let record = transaction.audit(
+                    AuditOperation::Operate(operate_type),
+                    item.index.widen_to_u16(),
+                    item.value,
+                    CommandStatus::Success,
+                );
+                pending.push(cursor.position() - 1, operation, record);
+            }
+        }
         *num_controls += 1;
     }
     Ok(ret)
@@ -727,18 +738,34 @@ fn operate_header_no_ack<I, V>(
     transaction: &mut ControlTransaction,
     max_controls_per_request: Option<u16>,
     num_controls: &mut u16,
+    pending: &mut PendingControls,
 ) where
     I: Index,
     V: FixedSizeVariation + ControlType,
 {
+    let operation = AuditOperation::Operate(OperateType::DirectOperateNoAck);
     for item in seq.iter() {
+        let index = item.index.widen_to_u16();
         if max_controls_per_request.map_or(true, |max| *num_controls < max) {
-            item.value.operate(
+            let result = item.value.operate(
                 transaction,
-                item.index.widen_to_u16(),
+                index,
                 OperateType::DirectOperateNoAck,
                 database,
             );
+            match result {
+                OperateResult::Complete(status) => {
+                    transaction.audit(operation, index, item.value, status);
+                }
+                OperateResult::Pending(op) => {
+                    // no response is sent, but the operation is still driven to completion
+                    let record =
+                        transaction.audit(operation, index, item.value, CommandStatus::Success);
+                    pending.push(0, op, record);
+                }
+            }
+        } else {
+            transaction.audit(operation, index, item.value, CommandStatus::TooManyOps);
         }
         *num_controls += 1;
     }
@@ -3,10 +3,10 @@ use std::fmt::Debug;
 use crate::app::control::*;
 use crate::outstation::control::collection::ControlTransaction;
 use crate::outstation::database::Database;
-use crate::outstation::traits::ControlSupport;
 use crate::outstation::traits::OperateType;
+use crate::outstation::OperateResult;
 
-pub(crate) trait ControlType: Debug {
+pub(crate) trait ControlType: Debug + Copy + Into<CommandValue> {
     /// make a copy of this control type with a new status code
     fn with_status(&self, status: CommandStatus) -> Self;
 
@@ -27,7 +27,7 @@ pub(crate) trait ControlType: Debug {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus;
+    ) -> OperateResult;
 }
 
 impl ControlType for Group12Var1 {
@@ -54,8 +54,8 @@ impl ControlType for Group12Var1 {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
-        transaction.operate(self, index, op_type, database)
+    ) -> OperateResult {
+        transaction.begin_operate(self, index, op_type, database)
     }
 }
 
@@ -83,8 +83,8 @@ impl ControlType for Group41Var1 {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
-        transaction.operate(self, index, op_type, database)
+    ) -> OperateResult {
+        transaction.begin_operate(self, index, op_type, database)
     }
 }
 
@@ -112,8 +112,8 @@ impl ControlType for Group41Var2 {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
-        transaction.operate(self, index, op_type, database)
+    ) -> OperateResult {
+        transaction.begin_operate(self, index, op_type, database)
     }
 }
 
@@ -141,8 +141,8 @@ impl ControlType for Group41Var3 {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
-        transaction.operate(self, index, op_type, database)
+    ) -> OperateResult {
+        transaction.begin_operate(self, index, op_type, database)
     }
 }
 
@@ -170,7 +170,7 @@ impl ControlType for Group41Var4 {
         index: u16,
         op_type: OperateType,
         database: &mut Database,
-    ) -> CommandStatus {
-        transaction.operate(self, index, op_type, database)
+    ) -> OperateResult {
+        transaction.begin_operate(self, index, op_type, database)
     }
 }
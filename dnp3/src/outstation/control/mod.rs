@@ -1,4 +1,5 @@
 pub(crate) mod collection;
 pub(crate) mod control_type;
+pub(crate) mod pending;
 pub(crate) mod prefix;
 pub(crate) mod select;
@@ -0,0 +1,104 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::app::control::CommandStatus;
+use crate::outstation::audit::CommandAudit;
+use crate::outstation::PendingOperate;
+use crate::util::cursor::{WriteCursor, WriteError};
+
+struct PendingControl {
+    /// position of the status byte in the response
+    status_pos: usize,
+    operation: PendingOperate,
+    /// position of the record in the command audit, if any
+    record: Option<usize>,
+    status: Option<CommandStatus>,
+}
+
+/// controls in a request that are waiting to complete asynchronously
+pub(crate) struct PendingControls {
+    controls: Vec<PendingControl>,
+}
+
+impl PendingControls {
+    pub(crate) fn new() -> Self {
+        Self {
+            controls: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.controls.is_empty()
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        status_pos: usize,
+        operation: PendingOperate,
+        record: Option<usize>,
+    ) {
+        self.controls.push(PendingControl {
+            status_pos,
+            operation,
+            record,
+            status: None,
+        })
+    }
+
+    /// update the command audit with the final statuses
+    pub(crate) fn update_audit(&self, audit: &mut CommandAudit) {
+        for control in self.controls.iter() {
+            if let Some(record) = control.record {
+                audit.complete(record, control.status.unwrap_or(CommandStatus::Timeout));
+            }
+        }
+    }
+
+    /// wait for every pending control to complete
+    pub(crate) fn wait(&mut self) -> WaitAll<'_> {
+        WaitAll { inner: self }
+    }
+
+    /// write the final statuses into the response, returning the first error
+    ///
+    /// Controls that haven't completed are reported as `CommandStatus::Timeout`
+    pub(crate) fn write_statuses(
+        self,
+        cursor: &mut WriteCursor,
+    ) -> Result<CommandStatus, WriteError> {
+        let mut ret = CommandStatus::Success;
+        for control in self.controls {
+            let status = control.status.unwrap_or(CommandStatus::Timeout);
+            cursor.at_pos(control.status_pos, |cur| cur.write_u8(status.as_u8()))?;
+            ret = ret.first_error(status);
+        }
+        Ok(ret)
+    }
+}
+
+pub(crate) struct WaitAll<'a> {
+    inner: &'a mut PendingControls,
+}
+
+impl<'a> Future for WaitAll<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut complete = true;
+        for control in self.get_mut().inner.controls.iter_mut() {
+            if control.status.is_none() {
+                match control.operation.poll_status(cx) {
+                    Poll::Ready(status) => control.status = Some(status),
+                    Poll::Pending => complete = false,
+                }
+            }
+        }
+
+        if complete {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
@@ -1,5 +1,11 @@
+use std::time::Duration;
+
 /// Enum representing all possible `Binary` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventBinaryVariation {
     /// Binary Input Event - Without Time
     Group2Var1,
@@ -11,6 +17,10 @@ pub enum EventBinaryVariation {
 
 /// Enum representing all possible `BinaryOutputStatus` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventBinaryOutputStatusVariation {
     /// Binary Output Event - Output Status Without Time
     Group11Var1,
@@ -20,6 +30,10 @@ pub enum EventBinaryOutputStatusVariation {
 
 /// Enum representing all possible `DoubleBitBinary` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventDoubleBitBinaryVariation {
     /// Double-bit Binary Input Event - Without Time
     Group4Var1,
@@ -31,6 +45,10 @@ pub enum EventDoubleBitBinaryVariation {
 
 /// Enum representing all possible `Counter` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventCounterVariation {
     /// Counter Event - 32-bit With Flag
     Group22Var1,
@@ -44,6 +62,10 @@ pub enum EventCounterVariation {
 
 /// Enum representing all possible `FrozenCounter` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventFrozenCounterVariation {
     /// Frozen Counter Event - 32-bit With Flag
     Group23Var1,
@@ -57,6 +79,10 @@ pub enum EventFrozenCounterVariation {
 
 /// Enum representing all possible `Analog` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventAnalogVariation {
     /// Analog Input Event - 32-bit With Flag
     Group32Var1,
@@ -78,6 +104,10 @@ pub enum EventAnalogVariation {
 
 /// Enum representing all possible `AnalogOutputStatus` event variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventAnalogOutputStatusVariation {
     /// Analog Output Event - 32-bit With Flag
     Group42Var1,
@@ -103,6 +133,10 @@ pub(crate) struct EventOctetStringVariation;
 
 /// Enum representing all possible `Binary` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticBinaryVariation {
     /// Binary Input - Packed Format
     Group1Var1,
@@ -112,6 +146,10 @@ pub enum StaticBinaryVariation {
 
 /// Enum representing all possible `BinaryOutputStatus` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticBinaryOutputStatusVariation {
     /// Binary Output - Packed Format
     Group10Var1,
@@ -121,6 +159,10 @@ pub enum StaticBinaryOutputStatusVariation {
 
 /// Enum representing all possible `DoubleBitBinary` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticDoubleBitBinaryVariation {
     /// Double-bit Binary Input - Packed Format
     Group3Var1,
@@ -130,6 +172,10 @@ pub enum StaticDoubleBitBinaryVariation {
 
 /// Enum representing all possible `Counter` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticCounterVariation {
     /// Counter - 32-bit With Flag
     Group20Var1,
@@ -143,6 +189,10 @@ pub enum StaticCounterVariation {
 
 /// Enum representing all possible `FrozenCounter` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticFrozenCounterVariation {
     /// Frozen Counter - 32-bit With Flag
     Group21Var1,
@@ -160,6 +210,10 @@ pub enum StaticFrozenCounterVariation {
 
 /// Enum representing all possible `Analog` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticAnalogVariation {
     /// Analog Input - 32-bit With Flag
     Group30Var1,
@@ -177,6 +231,10 @@ pub enum StaticAnalogVariation {
 
 /// Enum representing all possible `AnalogOutputStatus` static variations
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum StaticAnalogOutputStatusVariation {
     /// Analog Output Status - 32-bit With Flag
     Group40Var1,
@@ -194,24 +252,62 @@ pub(crate) struct StaticOctetStringVariation;
 
 /// configuration for a `Binary` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct BinaryConfig {
     /// default static variation
     pub s_var: StaticBinaryVariation,
     /// default event variation
     pub e_var: EventBinaryVariation,
+    /// optional chatter filter
+    pub chatter_filter: Option<ChatterFilter>,
 }
 
 /// configuration for a `DoubleBitBinary` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct DoubleBitBinaryConfig {
     /// default static variation
     pub s_var: StaticDoubleBitBinaryVariation,
     /// default event variation
     pub e_var: EventDoubleBitBinaryVariation,
+    /// optional chatter filter
+    pub chatter_filter: Option<ChatterFilter>,
+}
+
+/// Chatter filter for a `Binary` or `DoubleBitBinary` point
+///
+/// When `max_transitions` state changes occur within `window`, the point enters the chattering
+/// state: the CHATTER_FILTER flag is set and further state changes don't produce events. The
+/// flag is cleared once no state change has occurred for `quiet_period`, even if no further update
+/// is received.
+///
+/// Entering and leaving the chattering state each produce a single event because the flags change.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct ChatterFilter {
+    /// number of state changes within `window` that causes the point to be filtered
+    pub max_transitions: u16,
+    /// period over which state changes are counted
+    pub window: Duration,
+    /// period without state changes required to clear the filter
+    pub quiet_period: Duration,
 }
 
 /// configuration for a `BinaryOutputStatus` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct BinaryOutputStatusConfig {
     /// default static variation
     pub s_var: StaticBinaryOutputStatusVariation,
@@ -221,6 +317,10 @@ pub struct BinaryOutputStatusConfig {
 
 /// configuration for a `Counter` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct CounterConfig {
     /// default static variation
     pub s_var: StaticCounterVariation,
@@ -232,6 +332,10 @@ pub struct CounterConfig {
 
 /// configuration for a `FrozenCounter` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct FrozenCounterConfig {
     /// default static variation
     pub s_var: StaticFrozenCounterVariation,
@@ -243,6 +347,10 @@ pub struct FrozenCounterConfig {
 
 /// configuration for an `Analog` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct AnalogConfig {
     /// default static variation
     pub s_var: StaticAnalogVariation,
@@ -250,10 +358,20 @@ pub struct AnalogConfig {
     pub e_var: EventAnalogVariation,
     /// deadband - value of 0 means that any change will trigger an event
     pub deadband: f64,
+    /// how the deadband is interpreted
+    pub deadband_mode: DeadbandMode,
+    /// if set, an event is produced for a point that hasn't produced an event within this interval
+    ///
+    /// The event reports the current value with the timestamp of its last update.
+    pub max_report_interval: Option<Duration>,
 }
 
 /// configuration for an `AnalogOutputStatus` point
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct AnalogOutputStatusConfig {
     /// default static variation
     pub s_var: StaticAnalogOutputStatusVariation,
@@ -261,16 +379,53 @@ pub struct AnalogOutputStatusConfig {
     pub e_var: EventAnalogOutputStatusVariation,
     /// deadband - value of 0 means that any change will trigger an event
     pub deadband: f64,
+    /// how the deadband is interpreted
+    pub deadband_mode: DeadbandMode,
+    /// if set, an event is produced for a point that hasn't produced an event within this interval
+    ///
+    /// The event reports the current value with the timestamp of its last update.
+    pub max_report_interval: Option<Duration>,
+}
+
+/// Determines how the deadband of an `Analog` or `AnalogOutputStatus` point is interpreted
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum DeadbandMode {
+    /// an event is produced when the value differs from the last reported value by more than the deadband
+    Absolute,
+    /// the deadband is a percentage of the range of the point, i.e. `deadband * (max - min) / 100`
+    Percent {
+        /// minimum value of the point
+        min: f64,
+        /// maximum value of the point
+        max: f64,
+    },
+    /// an event is produced when the deviation from the last reported value integrated over time
+    /// exceeds the deadband, in units of value-seconds
+    ///
+    /// The deviation is accumulated each time the point is updated, so the point should be updated periodically
+    Integrating,
 }
 
 ///  Placeholder object required by a couple of traits
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub struct OctetStringConfig;
 
 impl BinaryConfig {
     /// construct a `BinaryConfig` from its fields
     pub fn new(s_var: StaticBinaryVariation, e_var: EventBinaryVariation) -> Self {
-        Self { s_var, e_var }
+        Self {
+            s_var,
+            e_var,
+            chatter_filter: None,
+        }
     }
 }
 
@@ -280,7 +435,22 @@ impl DoubleBitBinaryConfig {
         s_var: StaticDoubleBitBinaryVariation,
         e_var: EventDoubleBitBinaryVariation,
     ) -> Self {
-        Self { s_var, e_var }
+        Self {
+            s_var,
+            e_var,
+            chatter_filter: None,
+        }
+    }
+}
+
+impl ChatterFilter {
+    /// construct a `ChatterFilter` from its fields
+    pub fn new(max_transitions: u16, window: Duration, quiet_period: Duration) -> Self {
+        Self {
+            max_transitions,
+            window,
+            quiet_period,
+        }
     }
 }
 
@@ -327,6 +497,8 @@ impl AnalogConfig {
             s_var,
             e_var,
             deadband,
+            deadband_mode: DeadbandMode::Absolute,
+            max_report_interval: None,
         }
     }
 }
@@ -342,6 +514,8 @@ impl AnalogOutputStatusConfig {
             s_var,
             e_var,
             deadband,
+            deadband_mode: DeadbandMode::Absolute,
+            max_report_interval: None,
         }
     }
 }
@@ -1,61 +1,172 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use crate::app::measurement::{Analog, AnalogOutputStatus, Binary, DoubleBitBinary, Flags, Time};
 use crate::app::Iin2;
 use crate::master::EventClasses;
+use crate::outstation::clock::{Clock, ClockConfig};
 use crate::outstation::database::details::event::buffer::EventBuffer;
 use crate::outstation::database::details::range::static_db::{
-    PointConfig, StaticDatabase, Updatable,
+    HasFlags, PointConfig, Snapshot, StaticDatabase, StaticSelection, Updatable,
 };
 use crate::outstation::database::read::ReadHeader;
 use crate::outstation::database::{
-    ClassZeroConfig, EventBufferConfig, ResponseInfo, UpdateOptions,
+    ClassZeroConfig, EventBufferConfig, EventClass, EventCounts, PointInfo, PointSnapshot,
+    ResponseInfo, UpdateOptions,
 };
+use crate::tokio::time::Instant;
 use crate::util::cursor::WriteCursor;
 
+/// identifies a session attached to a database
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SessionId(u64);
+
+/// the session is not attached to the database, i.e. it was detached when its task stopped
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UnknownSession(SessionId);
+
+impl std::fmt::Display for UnknownSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "session {} is not attached to the database", (self.0).0)
+    }
+}
+
+/// state that is private to each session attached to the database
+struct Session {
+    id: SessionId,
+    selection: StaticSelection,
+    event_buffer: EventBuffer,
+    notify: Arc<crate::tokio::sync::Notify>,
+}
+
 pub(crate) struct Database {
     static_db: StaticDatabase,
-    event_buffer: EventBuffer,
+    sessions: Vec<Session>,
+    next_id: u64,
+    clock: Option<Clock>,
 }
 
 impl Database {
-    pub(crate) fn new(
+    pub(crate) fn new() -> Self {
+        Self {
+            static_db: StaticDatabase::new(),
+            sessions: Vec::new(),
+            next_id: 0,
+            clock: None,
+        }
+    }
+
+    /// attach a session with its own event buffer and read selection
+    pub(crate) fn attach(
+        &mut self,
         max_read_selection: Option<u16>,
         class_zero_config: ClassZeroConfig,
         config: EventBufferConfig,
-    ) -> Self {
-        Self {
-            static_db: StaticDatabase::new(max_read_selection, class_zero_config),
+        notify: Arc<crate::tokio::sync::Notify>,
+    ) -> SessionId {
+        let id = SessionId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.sessions.push(Session {
+            id,
+            selection: StaticSelection::new(max_read_selection, class_zero_config),
             event_buffer: EventBuffer::new(config),
+            notify,
+        });
+        id
+    }
+
+    /// enable the outstation clock if it isn't already enabled
+    ///
+    /// The clock belongs to the database and is shared by every session, so it keeps the
+    /// configuration of the first session that enabled it.
+    pub(crate) fn enable_clock(&mut self, config: ClockConfig) {
+        match &self.clock {
+            None => self.clock = Some(Clock::new(config)),
+            Some(clock) if clock.config() != config => tracing::warn!(
+                "ignoring clock configuration {:?}, the shared database already uses {:?}",
+                config,
+                clock.config()
+            ),
+            Some(_) => {}
         }
     }
 
-    pub(crate) fn reset(&mut self) {
-        self.static_db.reset();
-        self.event_buffer.reset();
+    pub(crate) fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
     }
 
-    pub(crate) fn clear_written_events(&mut self) {
-        self.event_buffer.clear_written();
+    pub(crate) fn clock_mut(&mut self) -> Option<&mut Clock> {
+        self.clock.as_mut()
     }
 
-    pub(crate) fn unwritten_classes(&self) -> EventClasses {
-        self.event_buffer.unwritten_classes()
+    /// detach a session discarding any events buffered for it
+    pub(crate) fn detach(&mut self, id: SessionId) {
+        self.sessions.retain(|s| s.id != id);
     }
 
-    pub(crate) fn is_overflown(&self) -> bool {
-        self.event_buffer.is_overflown()
+    /// notify every attached session that the database has changed
+    pub(crate) fn notify_sessions(&self) {
+        for session in self.sessions.iter() {
+            session.notify.notify_one();
+        }
     }
 
-    pub(crate) fn select_by_header(&mut self, header: ReadHeader) -> Iin2 {
-        match header {
-            ReadHeader::Static(header) => self.static_db.select(header),
+    pub(crate) fn reset(&mut self, id: SessionId) -> Result<(), UnknownSession> {
+        let session = self.get_session_mut(id)?;
+        session.selection.reset();
+        session.event_buffer.reset();
+        Ok(())
+    }
+
+    pub(crate) fn clear_written_events(&mut self, id: SessionId) -> Result<(), UnknownSession> {
+        self.get_session_mut(id)?.event_buffer.clear_written();
+        Ok(())
+    }
+
+    pub(crate) fn unwritten_classes(&self, id: SessionId) -> Result<EventClasses, UnknownSession> {
+        Ok(self.get_session(id)?.event_buffer.unwritten_classes())
+    }
+
+    pub(crate) fn unwritten_count(
+        &self,
+        id: SessionId,
+        class: EventClass,
+    ) -> Result<usize, UnknownSession> {
+        Ok(self.get_session(id)?.event_buffer.unwritten_count(class))
+    }
+
+    pub(crate) fn is_overflown(&self, id: SessionId) -> Result<bool, UnknownSession> {
+        Ok(self.get_session(id)?.event_buffer.is_overflown())
+    }
+
+    pub(crate) fn select_by_header(
+        &mut self,
+        id: SessionId,
+        header: ReadHeader,
+    ) -> Result<Iin2, UnknownSession> {
+        let static_db = &self.static_db;
+        let session = Self::find_session(&mut self.sessions, id)?;
+
+        let iin2 = match header {
+            ReadHeader::Static(header) => session.selection.select(static_db, header),
             ReadHeader::Event(header) => {
-                self.event_buffer.select_by_header(header);
+                session.event_buffer.select_by_header(header);
                 Iin2::default()
             }
-        }
+        };
+
+        Ok(iin2)
     }
 
-    pub(crate) fn select_event_classes(&mut self, classes: EventClasses) -> usize {
-        self.event_buffer.select_by_class(classes, None)
+    pub(crate) fn select_event_classes(
+        &mut self,
+        id: SessionId,
+        classes: EventClasses,
+    ) -> Result<usize, UnknownSession> {
+        Ok(self
+            .get_session_mut(id)?
+            .event_buffer
+            .select_by_class(classes, None))
     }
 
     pub(crate) fn add<T>(&mut self, index: u16, config: PointConfig<T>) -> bool
@@ -79,24 +190,144 @@ impl Database {
         self.static_db.get::<T>(index)
     }
 
+    pub(crate) fn clear<T>(&mut self)
+    where
+        T: Updatable,
+    {
+        self.static_db.clear::<T>()
+    }
+
+    pub(crate) fn snapshot<T>(&self) -> Vec<PointSnapshot<T, T::Config>>
+    where
+        T: Snapshot,
+    {
+        self.static_db.snapshot::<T>()
+    }
+
+    pub(crate) fn points<'a, T>(&'a self) -> impl Iterator<Item = PointInfo<T::Config>> + 'a
+    where
+        T: Snapshot + 'a,
+    {
+        self.static_db.points::<T>()
+    }
+
+    pub(crate) fn point_info<T>(&self, index: u16) -> Option<PointInfo<T::Config>>
+    where
+        T: Snapshot,
+    {
+        self.static_db.point_info::<T>(index)
+    }
+
+    pub(crate) fn event_counts(&self, id: SessionId) -> Result<EventCounts, UnknownSession> {
+        Ok(self.get_session(id)?.event_buffer.counts())
+    }
+
+    pub(crate) fn restore<T>(&mut self, index: u16, value: &T) -> bool
+    where
+        T: Updatable,
+    {
+        self.static_db.restore(index, value)
+    }
+
+    /// set and clear flags on the points of type `T` with an index in `range`, returning the number of points modified
+    ///
+    /// Points whose flags are unchanged are skipped, the others are updated with the specified time
+    pub(crate) fn update_flags<T>(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize
+    where
+        T: HasFlags,
+    {
+        let mut count = 0;
+        for (index, mut value) in self.static_db.get_range::<T>(range) {
+            let flags = value.flags_mut();
+            let modified = Flags::new((flags.value | set.value) & !clear.value);
+            if *flags == modified {
+                continue;
+            }
+            *flags = modified;
+            *value.time_mut() = Some(time);
+            self.update(&value, index, options);
+            count += 1;
+        }
+        count
+    }
+
     pub(crate) fn update<T>(&mut self, value: &T, index: u16, options: UpdateOptions) -> bool
     where
         T: Updatable,
     {
+        // values without a time are stamped using the outstation clock
+        let stamped = self
+            .clock
+            .as_ref()
+            .and_then(|clock| value.stamped(clock.now()));
+        let value = stamped.as_ref().unwrap_or(value);
+
         let (exists, event_data) = self.static_db.update(value, index, options);
 
-        // if an event should be produced, insert it into the buffer
-        if let Some((variation, class)) = event_data {
-            // Overflow is handled in the event buffer
-            let _ = self.event_buffer.insert(index, class, value, variation);
+        // if an event should be produced, insert it into the buffer of every session
+        if let Some((variation, class, value)) = event_data {
+            for session in self.sessions.iter_mut() {
+                // Overflow is handled in the event buffer
+                let _ = session.event_buffer.insert(index, class, &value, variation);
+            }
         }
 
         exists
     }
 
-    pub(crate) fn write_response_headers(&mut self, cursor: &mut WriteCursor) -> ResponseInfo {
+    /// produce events for points whose maximum reporting interval has elapsed and clear the
+    /// chatter filter of points that have been quiet, returning the next deadline
+    pub(crate) fn check_report_deadlines(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+        let mut produced = false;
+        let deadlines = [
+            self.check_report_deadlines_for::<Binary>(now, &mut produced),
+            self.check_report_deadlines_for::<DoubleBitBinary>(now, &mut produced),
+            self.check_report_deadlines_for::<Analog>(now, &mut produced),
+            self.check_report_deadlines_for::<AnalogOutputStatus>(now, &mut produced),
+        ];
+        if produced {
+            self.notify_sessions();
+        }
+        deadlines.iter().flatten().min().copied()
+    }
+
+    fn check_report_deadlines_for<T>(
+        &mut self,
+        now: Instant,
+        produced: &mut bool,
+    ) -> Option<Instant>
+    where
+        T: Updatable,
+    {
+        let sessions = &mut self.sessions;
+        self.static_db
+            .check_report_deadlines::<T, _>(now, |index, value, variation, class| {
+                *produced = true;
+                for session in sessions.iter_mut() {
+                    // Overflow is handled in the event buffer
+                    let _ = session.event_buffer.insert(index, class, value, variation);
+                }
+            })
+    }
+
+    pub(crate) fn write_response_headers(
+        &mut self,
+        id: SessionId,
+        cursor: &mut WriteCursor,
+    ) -> Result<ResponseInfo, UnknownSession> {
+        let static_db = &self.static_db;
+        let session = Self::find_session(&mut self.sessions, id)?;
+
         // first we write events
-        let result = self.event_buffer.write_events(cursor);
+        let result = session.event_buffer.write_events(cursor);
         let has_events = match result {
             Ok(count) => count > 0,
             Err(count) => count > 0,
@@ -107,20 +338,127 @@ impl Database {
             false
         } else {
             // write all events to we can try to write all static data
-            self.static_db.write(cursor).is_ok()
+            session.selection.write(static_db, cursor).is_ok()
         };
 
-        ResponseInfo {
+        Ok(ResponseInfo {
             has_events,
             complete,
-        }
+        })
     }
 
-    pub(crate) fn write_events_only(&mut self, cursor: &mut WriteCursor) -> usize {
+    pub(crate) fn write_events_only(
+        &mut self,
+        id: SessionId,
+        cursor: &mut WriteCursor,
+    ) -> Result<usize, UnknownSession> {
         // doesn't matter if we wrote all of them or not
-        match self.event_buffer.write_events(cursor) {
+        let count = match self.get_session_mut(id)?.event_buffer.write_events(cursor) {
             Ok(x) => x,
             Err(x) => x,
+        };
+        Ok(count)
+    }
+
+    fn get_session(&self, id: SessionId) -> Result<&Session, UnknownSession> {
+        self.sessions
+            .iter()
+            .find(|s| s.id == id)
+            .ok_or(UnknownSession(id))
+    }
+
+    fn get_session_mut(&mut self, id: SessionId) -> Result<&mut Session, UnknownSession> {
+        Self::find_session(&mut self.sessions, id)
+    }
+
+    // borrows only the sessions so that the static database remains available to the caller
+    fn find_session(
+        sessions: &mut [Session],
+        id: SessionId,
+    ) -> Result<&mut Session, UnknownSession> {
+        sessions
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or(UnknownSession(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Timestamp;
+    use crate::outstation::database::details::range::static_db::ChatterDetector;
+    use crate::outstation::database::BinaryConfig;
+
+    #[test]
+    fn shared_clock_keeps_the_first_configuration() {
+        let mut database = Database::new();
+        let first = ClockConfig::new(None);
+        database.enable_clock(first);
+        database.enable_clock(ClockConfig::default());
+        assert_eq!(database.clock().map(|x| x.config()), Some(first));
+    }
+
+    #[test]
+    fn flag_updates_preserve_values_and_produce_events() {
+        let mut database = Database::new();
+        let session = database.attach(
+            None,
+            ClassZeroConfig::default(),
+            EventBufferConfig::all_types(10),
+            Default::default(),
+        );
+        let config = BinaryConfig::default();
+        for index in 0..3 {
+            database.add(
+                index,
+                PointConfig::<Binary>::new(
+                    Some(EventClass::Class1),
+                    ChatterDetector::new(None),
+                    config.s_var,
+                    config.e_var,
+                ),
+            );
+            database.update(
+                &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+                index,
+                UpdateOptions::initialize(),
+            );
         }
+
+        let time = Time::Synchronized(Timestamp::new(42));
+        let count = database.update_flags::<Binary>(
+            0..=1,
+            Flags::COMM_LOST,
+            Flags::ONLINE,
+            time,
+            UpdateOptions::default(),
+        );
+        assert_eq!(count, 2);
+        assert_eq!(database.event_counts(session).unwrap().binary, 2);
+        assert_eq!(
+            database.get::<Binary>(0),
+            Some(Binary::new(true, Flags::COMM_LOST, time))
+        );
+        assert_eq!(database.get::<Binary>(2).unwrap().flags, Flags::ONLINE);
+
+        // only the two points that were COMM_LOST are modified
+        let count = database.update_flags::<Binary>(
+            0..=u16::MAX,
+            Flags::ONLINE,
+            Flags::COMM_LOST,
+            time,
+            UpdateOptions::default(),
+        );
+        assert_eq!(count, 2);
+        assert_eq!(database.event_counts(session).unwrap().binary, 4);
+        assert_eq!(
+            database.get::<Binary>(0),
+            Some(Binary::new(true, Flags::ONLINE, time))
+        );
+        assert_eq!(
+            database.get::<Binary>(2),
+            Some(Binary::new(true, Flags::ONLINE, Time::synchronized(0)))
+        );
     }
 }
@@ -6,7 +6,7 @@ use crate::app::measurement::{self};
 use crate::master::EventClasses;
 use crate::outstation::database::config::*;
 use crate::outstation::database::read::EventReadHeader;
-use crate::outstation::database::{EventBufferConfig, EventClass};
+use crate::outstation::database::{EventBufferConfig, EventClass, EventCounts};
 use crate::util::cursor::{WriteCursor, WriteError};
 
 use super::list::VecList;
@@ -33,7 +33,7 @@ impl BitOr<EventClass> for EventClass {
 }
 
 impl EventClasses {
-    fn matches(&self, class: EventClass) -> bool {
+    pub(crate) fn matches(&self, class: EventClass) -> bool {
         match class {
             EventClass::Class1 => self.class1,
             EventClass::Class2 => self.class2,
@@ -414,6 +414,33 @@ impl EventBuffer {
         )
     }
 
+    pub(crate) fn counts(&self) -> EventCounts {
+        let classes = &self.total.classes;
+        let types = &self.total.types;
+        EventCounts {
+            class_1: classes.num_class_1.get(),
+            class_2: classes.num_class_2.get(),
+            class_3: classes.num_class_3.get(),
+            binary: types.num_binary.get(),
+            double_bit_binary: types.num_double_binary.get(),
+            binary_output_status: types.num_binary_output_status.get(),
+            counter: types.num_counter.get(),
+            frozen_counter: types.num_frozen_counter.get(),
+            analog: types.num_analog.get(),
+            analog_output_status: types.num_analog_output_status.get(),
+            octet_string: types.num_octet_string.get(),
+        }
+    }
+
+    pub(crate) fn unwritten_count(&self, class: EventClass) -> usize {
+        let unwritten = self.total.classes.subtract(&self.written.classes);
+        match class {
+            EventClass::Class1 => unwritten.num_class_1.value,
+            EventClass::Class2 => unwritten.num_class_2.value,
+            EventClass::Class3 => unwritten.num_class_3.value,
+        }
+    }
+
     pub(crate) fn insert<T>(
         &mut self,
         index: u16,
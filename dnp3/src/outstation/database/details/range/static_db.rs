@@ -1,5 +1,6 @@
 use std::collections::{BTreeMap, Bound, VecDeque};
-use std::ops::RangeBounds;
+use std::ops::{RangeBounds, RangeInclusive};
+use std::time::Duration;
 
 use crate::app::measurement::*;
 use crate::app::Iin2;
@@ -9,14 +10,41 @@ use crate::outstation::database::details::event::buffer::Insertable;
 use crate::outstation::database::details::range::traits::StaticVariation;
 use crate::outstation::database::details::range::writer::RangeWriter;
 use crate::outstation::database::read::StaticReadHeader;
-use crate::outstation::database::{ClassZeroConfig, EventClass, EventMode, UpdateOptions};
+use crate::outstation::database::{
+    ClassZeroConfig, EventClass, EventMode, PointInfo, PointSnapshot, UpdateOptions,
+};
+use crate::tokio::time::Instant;
+use crate::util::bit::bits;
 use crate::util::cursor::{WriteCursor, WriteError};
 
 pub(crate) trait EventDetector<T>
 where
     T: Updatable,
 {
-    fn is_event(&self, new: &T, old: &T) -> bool;
+    fn is_event(&mut self, new: &T, old: &T) -> bool;
+    /// optionally modify a new value before it is stored and events are detected
+    fn filter(&mut self, _new: &T) -> Filtered<T> {
+        Filtered::Pass
+    }
+    /// notification that an event was produced for the point
+    fn on_event(&mut self) {}
+    /// time at which an event must be produced for the point, if any
+    fn report_deadline(&self) -> Option<Instant> {
+        None
+    }
+    /// value that is stored and reported once the deadline has passed
+    fn on_deadline(&mut self, current: &T) -> T {
+        current.clone()
+    }
+}
+
+pub(crate) enum Filtered<T> {
+    /// the value is used as is
+    Pass,
+    /// the value is replaced, but events are still detected
+    Replace(T),
+    /// the value is replaced and event detection is suppressed
+    Suppress(T),
 }
 
 pub(crate) trait Updatable: Insertable + Clone + Default {
@@ -24,8 +52,20 @@ pub(crate) trait Updatable: Insertable + Clone + Default {
     type Detector: EventDetector<Self>;
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self>;
     fn get_mut_map(maps: &mut StaticDatabase) -> &mut PointMap<Self>;
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self>;
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self>;
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange;
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool;
+    /// copy of the value with the time set, if the type has a time and it is absent
+    fn stamped(&self, _time: Time) -> Option<Self> {
+        None
+    }
+}
+
+/// types whose user-facing point configuration can be recovered from a `PointConfig`
+pub(crate) trait Snapshot: Updatable {
+    type Config;
+    fn get_config(config: &PointConfig<Self>) -> Self::Config;
 }
 
 #[derive(Copy, Clone)]
@@ -159,8 +199,6 @@ where
 {
     // current value
     current: T,
-    // value that is frozen during READ requests to be reported
-    selected: T,
     // last value that produced an event
     last_event: T,
     // configuration
@@ -174,7 +212,6 @@ where
     pub(crate) fn new(config: PointConfig<T>) -> Self {
         Self {
             current: T::default(),
-            selected: T::default(),
             last_event: T::default(),
             config,
         }
@@ -186,6 +223,9 @@ where
     T: Updatable,
 {
     inner: BTreeMap<u16, Point<T>>,
+    // no report deadline of the points is earlier than this, so the points
+    // only need to be checked once it has passed
+    earliest_deadline: Option<Instant>,
 }
 
 impl<T> PointMap<T>
@@ -195,25 +235,25 @@ where
     fn empty() -> Self {
         Self {
             inner: BTreeMap::new(),
+            earliest_deadline: None,
         }
     }
 
-    fn get_mut(&mut self, index: u16) -> Option<&mut Point<T>> {
-        self.inner.get_mut(&index)
+    /// record the report deadline of a point that was added or whose detector changed
+    fn schedule(&mut self, deadline: Option<Instant>) {
+        if let Some(deadline) = deadline {
+            self.earliest_deadline = Some(match self.earliest_deadline {
+                Some(x) => x.min(deadline),
+                None => deadline,
+            });
+        }
     }
 
-    fn select_all(&mut self) -> Option<VariationRange> {
-        self.select_all_with_variation(None)
+    fn get_mut(&mut self, index: u16) -> Option<&mut Point<T>> {
+        self.inner.get_mut(&index)
     }
 
-    fn select_all_with_variation(
-        &mut self,
-        variation: Option<T::StaticVariation>,
-    ) -> Option<VariationRange> {
-        self.inner
-            .values_mut()
-            .for_each(|x| x.selected = x.current.clone());
-
+    fn full_range(&self) -> Option<IndexRange> {
         /*
           TODO - when this feature is stabilized we can use it here
           TODO - #![feature(map_first_last)]
@@ -221,13 +261,12 @@ where
         */
         let start = self.inner.iter().next().map(|(key, _)| *key)?;
         let stop = self.inner.iter().next_back().map(|(key, _)| *key)?;
-        Some(T::wrap(IndexRange::new(start, stop), variation))
+        Some(IndexRange::new(start, stop))
     }
 }
 
+/// Static points shared between all of the sessions attached to a database
 pub(crate) struct StaticDatabase {
-    class_zero: ClassZeroConfig,
-    selected: SelectionQueue,
     // maps for the various types
     binary: PointMap<Binary>,
     double_bit_binary: PointMap<DoubleBitBinary>,
@@ -241,20 +280,13 @@ pub(crate) struct StaticDatabase {
 
 impl Default for StaticDatabase {
     fn default() -> Self {
-        Self::new(None, ClassZeroConfig::default())
+        Self::new()
     }
 }
 
 impl StaticDatabase {
-    pub(crate) fn new(max_read_selection: Option<u16>, class_zero: ClassZeroConfig) -> Self {
-        // don't allow values smaller than the default
-        let max_read_selection = max_read_selection
-            .map(|x| x.max(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS))
-            .unwrap_or(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS);
-
+    pub(crate) fn new() -> Self {
         Self {
-            class_zero,
-            selected: SelectionQueue::new(max_read_selection),
             binary: PointMap::empty(),
             double_bit_binary: PointMap::empty(),
             binary_output_status: PointMap::empty(),
@@ -265,24 +297,6 @@ impl StaticDatabase {
             octet_strings: PointMap::empty(),
         }
     }
-    /*
-       pub(crate) fn exceeded_capacity(&self) -> Option<usize> {
-           if self.selected.capacity_exceeded > 0 {
-               Some(self.selected.capacity_exceeded)
-           } else {
-               None
-           }
-       }
-    */
-
-    #[cfg(test)]
-    pub(crate) fn selection_capacity(&self) -> usize {
-        self.selected.queue.capacity()
-    }
-
-    pub(crate) fn reset(&mut self) {
-        self.selected.reset();
-    }
 
     pub(crate) fn add<T>(&mut self, index: u16, config: PointConfig<T>) -> bool
     where
@@ -294,7 +308,9 @@ impl StaticDatabase {
             return false;
         }
 
-        map.inner.insert(index, Point::new(config));
+        let point = Point::new(config);
+        map.schedule(point.config.detector.report_deadline());
+        map.inner.insert(index, point);
 
         true
     }
@@ -316,18 +332,101 @@ impl StaticDatabase {
             .map(|point| point.current.clone())
     }
 
+    /// current values of the points of type `T` with an index in `range`
+    pub(crate) fn get_range<T>(&self, range: RangeInclusive<u16>) -> Vec<(u16, T)>
+    where
+        T: Updatable,
+    {
+        self.get_map::<T>()
+            .inner
+            .range(range)
+            .map(|(index, point)| (*index, point.current.clone()))
+            .collect()
+    }
+
+    pub(crate) fn clear<T>(&mut self)
+    where
+        T: Updatable,
+    {
+        let map = self.get_mut_map::<T>();
+        map.inner.clear();
+        map.earliest_deadline = None;
+    }
+
+    pub(crate) fn snapshot<T>(&self) -> Vec<PointSnapshot<T, T::Config>>
+    where
+        T: Snapshot,
+    {
+        self.get_map::<T>()
+            .inner
+            .iter()
+            .map(|(index, point)| {
+                PointSnapshot::new(
+                    *index,
+                    point.config.class,
+                    T::get_config(&point.config),
+                    point.current.clone(),
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn points<'a, T>(&'a self) -> impl Iterator<Item = PointInfo<T::Config>> + 'a
+    where
+        T: Snapshot + 'a,
+    {
+        self.get_map::<T>().inner.iter().map(|(index, point)| {
+            PointInfo::new(*index, point.config.class, T::get_config(&point.config))
+        })
+    }
+
+    pub(crate) fn point_info<T>(&self, index: u16) -> Option<PointInfo<T::Config>>
+    where
+        T: Snapshot,
+    {
+        self.get_map::<T>()
+            .inner
+            .get(&index)
+            .map(|point| PointInfo::new(index, point.config.class, T::get_config(&point.config)))
+    }
+
+    /// set the current value without performing any event detection. The value is
+    /// also recorded as the last reported event so that it is the basis for future detection
+    pub(crate) fn restore<T>(&mut self, index: u16, value: &T) -> bool
+    where
+        T: Updatable,
+    {
+        match self.get_mut_map::<T>().get_mut(index) {
+            None => false,
+            Some(x) => {
+                x.current = value.clone();
+                x.last_event = value.clone();
+                true
+            }
+        }
+    }
+
+    /// update a point, returning whether it exists and, if an event should be produced,
+    /// the variation, class, and value of the event
     pub(crate) fn update<T>(
         &mut self,
         value: &T,
         index: u16,
         options: UpdateOptions,
-    ) -> (bool, Option<(T::EventVariation, EventClass)>)
+    ) -> (bool, Option<(T::EventVariation, EventClass, T)>)
     where
         T: Updatable,
     {
-        match self.get_mut_map::<T>().get_mut(index) {
+        let map = self.get_mut_map::<T>();
+        match map.inner.get_mut(&index) {
             None => (false, None),
             Some(x) => {
+                let (value, suppressed) = match x.config.detector.filter(value) {
+                    Filtered::Pass => (value.clone(), false),
+                    Filtered::Replace(value) => (value, false),
+                    Filtered::Suppress(value) => (value, true),
+                };
+
                 if options.update_static {
                     x.current = value.clone();
                 }
@@ -337,26 +436,169 @@ impl StaticDatabase {
                     EventMode::Suppress => None,
                     EventMode::Force => {
                         x.last_event = value.clone();
-                        x.config.class.map(|ec| (x.config.e_var, ec))
+                        x.config.detector.on_event();
+                        x.config.class.map(|ec| (x.config.e_var, ec, value))
                     }
                     EventMode::Detect => {
-                        if x.config.detector.is_event(&x.last_event, &value) {
+                        if !suppressed && x.config.detector.is_event(&value, &x.last_event) {
                             x.last_event = value.clone();
-                            x.config.class.map(|ec| (x.config.e_var, ec))
+                            x.config.detector.on_event();
+                            x.config.class.map(|ec| (x.config.e_var, ec, value))
                         } else {
                             None
                         }
                     }
                 };
 
+                let deadline = x.config.detector.report_deadline();
+                map.schedule(deadline);
+
                 (true, event)
             }
         }
     }
 
-    pub(crate) fn write(&mut self, cursor: &mut WriteCursor) -> Result<(), WriteError> {
+    /// produce an event for every point of type `T` whose report deadline has passed, returning the next deadline
+    pub(crate) fn check_report_deadlines<T, F>(
+        &mut self,
+        now: Instant,
+        mut on_event: F,
+    ) -> Option<Instant>
+    where
+        T: Updatable,
+        F: FnMut(u16, &T, T::EventVariation, EventClass),
+    {
+        let map = self.get_mut_map::<T>();
+        match map.earliest_deadline {
+            Some(x) if now >= x => {}
+            // none of the deadlines have passed
+            x => return x,
+        }
+
+        let mut next: Option<Instant> = None;
+        for (index, point) in map.inner.iter_mut() {
+            let mut deadline = match point.config.detector.report_deadline() {
+                Some(x) => x,
+                None => continue,
+            };
+            if now >= deadline {
+                // the event carries the timestamp of the last update, i.e. the time at which
+                // the value was measured, rather than the time of the report
+                point.current = point.config.detector.on_deadline(&point.current);
+                point.last_event = point.current.clone();
+                point.config.detector.on_event();
+                if let Some(class) = point.config.class {
+                    on_event(*index, &point.current, point.config.e_var, class);
+                }
+                match point.config.detector.report_deadline() {
+                    Some(x) => deadline = x,
+                    None => continue,
+                }
+            }
+            next = Some(match next {
+                Some(x) => x.min(deadline),
+                None => deadline,
+            });
+        }
+        map.earliest_deadline = next;
+        next
+    }
+
+    fn get_map<T>(&self) -> &PointMap<T>
+    where
+        T: Updatable,
+    {
+        T::get_map(self)
+    }
+
+    fn get_mut_map<T>(&mut self) -> &mut PointMap<T>
+    where
+        T: Updatable,
+    {
+        T::get_mut_map(self)
+    }
+}
+
+/// values that are frozen when selected by a READ request so that
+/// they are reported consistently across multiple fragments
+#[derive(Default)]
+pub(crate) struct SelectedValues {
+    binary: BTreeMap<u16, Binary>,
+    double_bit_binary: BTreeMap<u16, DoubleBitBinary>,
+    binary_output_status: BTreeMap<u16, BinaryOutputStatus>,
+    counter: BTreeMap<u16, Counter>,
+    frozen_counter: BTreeMap<u16, FrozenCounter>,
+    analog: BTreeMap<u16, Analog>,
+    analog_output_status: BTreeMap<u16, AnalogOutputStatus>,
+    octet_strings: BTreeMap<u16, OctetString>,
+}
+
+impl SelectedValues {
+    fn clear(&mut self) {
+        self.binary.clear();
+        self.double_bit_binary.clear();
+        self.binary_output_status.clear();
+        self.counter.clear();
+        self.frozen_counter.clear();
+        self.analog.clear();
+        self.analog_output_status.clear();
+        self.octet_strings.clear();
+    }
+}
+
+/// Static data selected by a single session for reporting
+pub(crate) struct StaticSelection {
+    class_zero: ClassZeroConfig,
+    selected: SelectionQueue,
+    values: SelectedValues,
+}
+
+impl Default for StaticSelection {
+    fn default() -> Self {
+        Self::new(None, ClassZeroConfig::default())
+    }
+}
+
+impl StaticSelection {
+    pub(crate) fn new(max_read_selection: Option<u16>, class_zero: ClassZeroConfig) -> Self {
+        // don't allow values smaller than the default
+        let max_read_selection = max_read_selection
+            .map(|x| x.max(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS))
+            .unwrap_or(OutstationConfig::DEFAULT_MAX_READ_REQUEST_HEADERS);
+
+        Self {
+            class_zero,
+            selected: SelectionQueue::new(max_read_selection),
+            values: SelectedValues::default(),
+        }
+    }
+    /*
+       pub(crate) fn exceeded_capacity(&self) -> Option<usize> {
+           if self.selected.capacity_exceeded > 0 {
+               Some(self.selected.capacity_exceeded)
+           } else {
+               None
+           }
+       }
+    */
+
+    #[cfg(test)]
+    pub(crate) fn selection_capacity(&self) -> usize {
+        self.selected.queue.capacity()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.selected.reset();
+        self.values.clear();
+    }
+
+    pub(crate) fn write(
+        &mut self,
+        db: &StaticDatabase,
+        cursor: &mut WriteCursor,
+    ) -> Result<(), WriteError> {
         while let Some(range) = self.selected.peek() {
-            match self.write_range(cursor, range) {
+            match self.write_range(db, cursor, range) {
                 // done with this header
                 Ok(()) => {
                     self.selected.pop();
@@ -374,40 +616,42 @@ impl StaticDatabase {
     }
 
     fn write_range(
-        &mut self,
+        &self,
+        db: &StaticDatabase,
         cursor: &mut WriteCursor,
         range: VariationRange,
     ) -> Result<(), VariationRange> {
         match range.variation {
             SpecificVariation::Binary(var) => {
-                self.write_typed_range::<Binary>(cursor, range.range, var)
+                self.write_typed_range::<Binary>(db, cursor, range.range, var)
             }
             SpecificVariation::DoubleBitBinary(var) => {
-                self.write_typed_range::<DoubleBitBinary>(cursor, range.range, var)
+                self.write_typed_range::<DoubleBitBinary>(db, cursor, range.range, var)
             }
             SpecificVariation::BinaryOutputStatus(var) => {
-                self.write_typed_range::<BinaryOutputStatus>(cursor, range.range, var)
+                self.write_typed_range::<BinaryOutputStatus>(db, cursor, range.range, var)
             }
             SpecificVariation::Counter(var) => {
-                self.write_typed_range::<Counter>(cursor, range.range, var)
+                self.write_typed_range::<Counter>(db, cursor, range.range, var)
             }
             SpecificVariation::FrozenCounter(var) => {
-                self.write_typed_range::<FrozenCounter>(cursor, range.range, var)
+                self.write_typed_range::<FrozenCounter>(db, cursor, range.range, var)
             }
             SpecificVariation::Analog(var) => {
-                self.write_typed_range::<Analog>(cursor, range.range, var)
+                self.write_typed_range::<Analog>(db, cursor, range.range, var)
             }
             SpecificVariation::AnalogOutputStatus(var) => {
-                self.write_typed_range::<AnalogOutputStatus>(cursor, range.range, var)
+                self.write_typed_range::<AnalogOutputStatus>(db, cursor, range.range, var)
             }
             SpecificVariation::OctetString => {
-                self.write_typed_range::<OctetString>(cursor, range.range, None)
+                self.write_typed_range::<OctetString>(db, cursor, range.range, None)
             }
         }
     }
 
     fn write_typed_range<T>(
-        &mut self,
+        &self,
+        db: &StaticDatabase,
         cursor: &mut WriteCursor,
         range: IndexRange,
         variation: Option<T::StaticVariation>,
@@ -415,15 +659,22 @@ impl StaticDatabase {
     where
         T: Updatable,
     {
+        let points = db.get_map::<T>();
         let mut writer = RangeWriter::new();
-        for (index, item) in self.get_map::<T>().inner.range(range) {
+        for (index, value) in T::get_selected(&self.values).range(range) {
+            // the point may have been removed since it was selected
+            let item = match points.inner.get(index) {
+                Some(x) => x,
+                None => continue,
+            };
+
             // first determine what variation should be written
             let info = variation
                 .unwrap_or(item.config.s_var)
-                .promote(&item.selected)
-                .get_write_info(&item.selected);
+                .promote(value)
+                .get_write_info(value);
 
-            if writer.write(cursor, *index, &item.selected, info).is_err() {
+            if writer.write(cursor, *index, value, info).is_err() {
                 // ran out of space, tell calling code to resume at this index
                 return Err(T::wrap(IndexRange::new(*index, range.stop), variation));
             }
@@ -432,110 +683,103 @@ impl StaticDatabase {
         Ok(())
     }
 
-    pub(crate) fn select(&mut self, variation: StaticReadHeader) -> Iin2 {
+    pub(crate) fn select(&mut self, db: &StaticDatabase, variation: StaticReadHeader) -> Iin2 {
         match variation {
-            StaticReadHeader::Class0 => self.select_class_zero(),
+            StaticReadHeader::Class0 => self.select_class_zero(db),
             StaticReadHeader::Binary(variation, range) => {
-                self.select_by_type::<Binary>(variation, range)
+                self.select_by_type::<Binary>(db, variation, range)
             }
             StaticReadHeader::DoubleBitBinary(variation, range) => {
-                self.select_by_type::<DoubleBitBinary>(variation, range)
+                self.select_by_type::<DoubleBitBinary>(db, variation, range)
             }
             StaticReadHeader::BinaryOutputStatus(variation, range) => {
-                self.select_by_type::<BinaryOutputStatus>(variation, range)
+                self.select_by_type::<BinaryOutputStatus>(db, variation, range)
             }
             StaticReadHeader::Counter(variation, range) => {
-                self.select_by_type::<Counter>(variation, range)
+                self.select_by_type::<Counter>(db, variation, range)
             }
             StaticReadHeader::FrozenCounter(variation, range) => {
-                self.select_by_type::<FrozenCounter>(variation, range)
+                self.select_by_type::<FrozenCounter>(db, variation, range)
             }
             StaticReadHeader::Analog(variation, range) => {
-                self.select_by_type::<Analog>(variation, range)
+                self.select_by_type::<Analog>(db, variation, range)
             }
             StaticReadHeader::AnalogOutputStatus(variation, range) => {
-                self.select_by_type::<AnalogOutputStatus>(variation, range)
+                self.select_by_type::<AnalogOutputStatus>(db, variation, range)
+            }
+            StaticReadHeader::OctetString(range) => {
+                self.select_by_type::<OctetString>(db, None, range)
             }
-            StaticReadHeader::OctetString(range) => self.select_by_type::<OctetString>(None, range),
         }
     }
 
     fn select_by_type<T>(
         &mut self,
+        db: &StaticDatabase,
         variation: Option<T::StaticVariation>,
         range: Option<IndexRange>,
     ) -> Iin2
     where
         T: Updatable,
     {
-        match range {
-            Some(range) => self.push_selection(T::wrap(range, variation)),
-            None => {
-                if let Some(x) = T::get_mut_map(self).select_all_with_variation(variation) {
-                    self.push_selection(x)
-                } else {
-                    Iin2::default()
-                }
-            }
+        let points = db.get_map::<T>();
+
+        let range = match range.or_else(|| points.full_range()) {
+            Some(x) => x,
+            None => return Iin2::default(),
+        };
+
+        // freeze the current values so that they are reported consistently
+        let selected = T::get_selected_mut(&mut self.values);
+        for (index, point) in points.inner.range(range) {
+            selected.insert(*index, point.current.clone());
         }
-    }
 
-    fn push_selection(&mut self, range: VariationRange) -> Iin2 {
-        if self.selected.push_back(range) {
+        if self.selected.push_back(T::wrap(range, variation)) {
             Iin2::default()
         } else {
             Iin2::PARAMETER_ERROR
         }
     }
 
-    fn get_map<T>(&self) -> &PointMap<T>
-    where
-        T: Updatable,
-    {
-        T::get_map(self)
-    }
-
-    fn get_mut_map<T>(&mut self) -> &mut PointMap<T>
-    where
-        T: Updatable,
-    {
-        T::get_mut_map(self)
-    }
-
-    fn select_class_zero_type<T>(&mut self) -> Iin2
+    fn select_class_zero_type<T>(&mut self, db: &StaticDatabase) -> Iin2
     where
         T: Updatable,
     {
         if T::enabled_class_zero(&self.class_zero) {
-            let full_range = match T::get_mut_map(self).select_all() {
-                None => return Iin2::default(),
-                Some(x) => x,
-            };
-
-            if self.selected.push_back(full_range) {
-                Iin2::default()
-            } else {
-                // out of space for read headers
-                Iin2::PARAMETER_ERROR
-            }
+            self.select_by_type::<T>(db, None, None)
         } else {
             Iin2::default()
         }
     }
 
-    fn select_class_zero(&mut self) -> Iin2 {
-        self.select_class_zero_type::<Binary>()
-            | self.select_class_zero_type::<DoubleBitBinary>()
-            | self.select_class_zero_type::<BinaryOutputStatus>()
-            | self.select_class_zero_type::<Counter>()
-            | self.select_class_zero_type::<FrozenCounter>()
-            | self.select_class_zero_type::<Analog>()
-            | self.select_class_zero_type::<AnalogOutputStatus>()
-            | self.select_class_zero_type::<OctetString>()
+    fn select_class_zero(&mut self, db: &StaticDatabase) -> Iin2 {
+        self.select_class_zero_type::<Binary>(db)
+            | self.select_class_zero_type::<DoubleBitBinary>(db)
+            | self.select_class_zero_type::<BinaryOutputStatus>(db)
+            | self.select_class_zero_type::<Counter>(db)
+            | self.select_class_zero_type::<FrozenCounter>(db)
+            | self.select_class_zero_type::<Analog>(db)
+            | self.select_class_zero_type::<AnalogOutputStatus>(db)
+            | self.select_class_zero_type::<OctetString>(db)
     }
 }
 
 pub(crate) struct FlagsDetector;
+
+/// detects events from flags and applies an optional chatter filter to the state of the point
+pub(crate) struct ChatterDetector<S> {
+    filter: Option<ChatterState<S>>,
+}
+
+struct ChatterState<S> {
+    config: ChatterFilter,
+    // last state received, used to detect transitions
+    last_state: Option<S>,
+    // times of the state transitions within the window
+    transitions: VecDeque<Instant>,
+    chattering: bool,
+}
 pub(crate) struct Deadband<N>
 where
     N: std::ops::Sub<N, Output = N> + PartialOrd<N>,
@@ -545,6 +789,18 @@ where
 
 pub(crate) struct OctetStringDetector;
 
+/// detects events for analog values using one of the deadband modes
+pub(crate) struct AnalogDetector {
+    deadband: f64,
+    mode: DeadbandMode,
+    max_report_interval: Option<Duration>,
+    // deviation from the last reported value integrated over time
+    integral: f64,
+    // previous value and the time at which it was received
+    previous: Option<(f64, Instant)>,
+    last_report: Instant,
+}
+
 impl<N> Deadband<N>
 where
     N: std::ops::Sub<N, Output = N> + PartialOrd<N>,
@@ -560,20 +816,172 @@ where
     }
 }
 
-impl EventDetector<Binary> for FlagsDetector {
-    fn is_event(&self, new: &Binary, old: &Binary) -> bool {
+impl<S> ChatterDetector<S>
+where
+    S: PartialEq,
+{
+    pub(crate) fn new(config: Option<ChatterFilter>) -> Self {
+        Self {
+            filter: config.map(ChatterState::new),
+        }
+    }
+
+    fn config(&self) -> Option<ChatterFilter> {
+        self.filter.as_ref().map(|x| x.config)
+    }
+
+    /// time at which the flag is cleared if no state change occurs before it
+    fn quiet_deadline(&self) -> Option<Instant> {
+        let filter = self.filter.as_ref()?;
+        if !filter.chattering {
+            return None;
+        }
+        filter
+            .transitions
+            .back()
+            .map(|last| *last + filter.config.quiet_period)
+    }
+
+    /// leave the chattering state once the quiet period has elapsed, returning the flags without CHATTER_FILTER
+    fn clear_flags(&mut self, flags: Flags) -> Flags {
+        if let Some(filter) = self.filter.as_mut() {
+            filter.chattering = false;
+            filter.transitions.clear();
+        }
+        flags.with_bits_set_to(bits::BIT_5, false)
+    }
+
+    fn filter_flags(&mut self, state: S, flags: Flags) -> Option<(Flags, bool)> {
+        let filter = self.filter.as_mut()?;
+        let was_chattering = filter.chattering;
+        let chattering = filter.on_state(state, Instant::now());
+        let flags = flags.with_bits_set_to(bits::BIT_5, chattering);
+        // only the transitions into and out of the chattering state are reported
+        Some((flags, was_chattering && chattering))
+    }
+}
+
+impl<S> ChatterState<S>
+where
+    S: PartialEq,
+{
+    fn new(config: ChatterFilter) -> Self {
+        Self {
+            config,
+            last_state: None,
+            transitions: VecDeque::new(),
+            chattering: false,
+        }
+    }
+
+    /// record the state of the point, returning true if the point is chattering
+    fn on_state(&mut self, state: S, now: Instant) -> bool {
+        let changed = matches!(&self.last_state, Some(x) if *x != state);
+        self.last_state = Some(state);
+
+        if changed {
+            self.transitions.push_back(now);
+        }
+
+        if self.chattering {
+            let quiet = match self.transitions.back() {
+                Some(last) => now.duration_since(*last) >= self.config.quiet_period,
+                None => true,
+            };
+            if quiet {
+                self.chattering = false;
+                self.transitions.clear();
+            }
+        } else {
+            // discard the transitions that have left the window
+            while let Some(first) = self.transitions.front() {
+                if now.duration_since(*first) > self.config.window {
+                    self.transitions.pop_front();
+                } else {
+                    break;
+                }
+            }
+            if self.transitions.len() >= self.config.max_transitions.max(1) as usize {
+                self.chattering = true;
+            }
+        }
+
+        if self.chattering {
+            // only the most recent transition is needed to detect the quiet period
+            while self.transitions.len() > 1 {
+                self.transitions.pop_front();
+            }
+        }
+
+        self.chattering
+    }
+}
+
+fn filtered<T>(
+    value: T,
+    result: Option<(Flags, bool)>,
+    set_flags: fn(&mut T, Flags),
+) -> Filtered<T> {
+    match result {
+        None => Filtered::Pass,
+        Some((flags, suppress)) => {
+            let mut value = value;
+            set_flags(&mut value, flags);
+            if suppress {
+                Filtered::Suppress(value)
+            } else {
+                Filtered::Replace(value)
+            }
+        }
+    }
+}
+
+impl EventDetector<Binary> for ChatterDetector<bool> {
+    fn is_event(&mut self, new: &Binary, old: &Binary) -> bool {
         new.get_wire_flags() != old.get_wire_flags()
     }
+
+    fn filter(&mut self, new: &Binary) -> Filtered<Binary> {
+        let result = self.filter_flags(new.value, new.flags);
+        filtered(*new, result, |x, flags| x.flags = flags)
+    }
+
+    fn report_deadline(&self) -> Option<Instant> {
+        self.quiet_deadline()
+    }
+
+    fn on_deadline(&mut self, current: &Binary) -> Binary {
+        Binary {
+            flags: self.clear_flags(current.flags),
+            ..*current
+        }
+    }
 }
 
-impl EventDetector<BinaryOutputStatus> for FlagsDetector {
-    fn is_event(&self, new: &BinaryOutputStatus, old: &BinaryOutputStatus) -> bool {
+impl EventDetector<DoubleBitBinary> for ChatterDetector<DoubleBit> {
+    fn is_event(&mut self, new: &DoubleBitBinary, old: &DoubleBitBinary) -> bool {
         new.get_wire_flags() != old.get_wire_flags()
     }
+
+    fn filter(&mut self, new: &DoubleBitBinary) -> Filtered<DoubleBitBinary> {
+        let result = self.filter_flags(new.value, new.flags);
+        filtered(*new, result, |x, flags| x.flags = flags)
+    }
+
+    fn report_deadline(&self) -> Option<Instant> {
+        self.quiet_deadline()
+    }
+
+    fn on_deadline(&mut self, current: &DoubleBitBinary) -> DoubleBitBinary {
+        DoubleBitBinary {
+            flags: self.clear_flags(current.flags),
+            ..*current
+        }
+    }
 }
 
-impl EventDetector<DoubleBitBinary> for FlagsDetector {
-    fn is_event(&self, new: &DoubleBitBinary, old: &DoubleBitBinary) -> bool {
+impl EventDetector<BinaryOutputStatus> for FlagsDetector {
+    fn is_event(&mut self, new: &BinaryOutputStatus, old: &BinaryOutputStatus) -> bool {
         new.get_wire_flags() != old.get_wire_flags()
     }
 }
@@ -582,6 +990,93 @@ pub(crate) trait HasValue<T> {
     fn value(&self) -> T;
 }
 
+/// types whose flags can be modified independently of their value
+pub(crate) trait HasFlags: Updatable {
+    fn flags_mut(&mut self) -> &mut Flags;
+    fn time_mut(&mut self) -> &mut Option<Time>;
+}
+
+/// copy of the value with the time set, if it is absent
+fn stamp<T: HasFlags>(value: &T, time: Time) -> Option<T> {
+    let mut copy = value.clone();
+    let current = copy.time_mut();
+    if current.is_some() {
+        return None;
+    }
+    *current = Some(time);
+    Some(copy)
+}
+
+impl HasFlags for Binary {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for DoubleBitBinary {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for BinaryOutputStatus {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for Counter {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for FrozenCounter {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for Analog {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
+impl HasFlags for AnalogOutputStatus {
+    fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    fn time_mut(&mut self) -> &mut Option<Time> {
+        &mut self.time
+    }
+}
+
 impl HasValue<u32> for Counter {
     fn value(&self) -> u32 {
         self.value
@@ -611,7 +1106,7 @@ where
     T: Updatable + HasValue<N> + WireFlags,
     N: std::ops::Sub<N, Output = N> + PartialOrd<N>,
 {
-    fn is_event(&self, new: &T, old: &T) -> bool {
+    fn is_event(&mut self, new: &T, old: &T) -> bool {
         if new.get_wire_flags() != old.get_wire_flags() {
             return true;
         }
@@ -620,15 +1115,71 @@ where
     }
 }
 
+impl AnalogDetector {
+    pub(crate) fn new(
+        deadband: f64,
+        mode: DeadbandMode,
+        max_report_interval: Option<Duration>,
+    ) -> Self {
+        Self {
+            deadband,
+            mode,
+            max_report_interval,
+            integral: 0.0,
+            previous: None,
+            last_report: Instant::now(),
+        }
+    }
+
+    fn exceeded(&mut self, new: f64, old: f64) -> bool {
+        match self.mode {
+            DeadbandMode::Absolute => (new - old).abs() > self.deadband,
+            DeadbandMode::Percent { min, max } => {
+                (new - old).abs() > self.deadband * (max - min).abs() / 100.0
+            }
+            DeadbandMode::Integrating => {
+                let now = Instant::now();
+                // the previous value was held from the time it was received until now
+                if let Some((previous, time)) = self.previous {
+                    self.integral +=
+                        (previous - old).abs() * now.duration_since(time).as_secs_f64();
+                }
+                self.previous = Some((new, now));
+                self.integral > self.deadband
+            }
+        }
+    }
+}
+
+impl<T> EventDetector<T> for AnalogDetector
+where
+    T: Updatable + HasValue<f64> + WireFlags,
+{
+    fn is_event(&mut self, new: &T, old: &T) -> bool {
+        // always evaluate the deadband so that the integral is accumulated
+        let exceeded = self.exceeded(new.value(), old.value());
+        exceeded || new.get_wire_flags() != old.get_wire_flags()
+    }
+
+    fn on_event(&mut self) {
+        self.integral = 0.0;
+        self.last_report = Instant::now();
+    }
+
+    fn report_deadline(&self) -> Option<Instant> {
+        self.max_report_interval.map(|x| self.last_report + x)
+    }
+}
+
 impl EventDetector<OctetString> for OctetStringDetector {
-    fn is_event(&self, new: &OctetString, old: &OctetString) -> bool {
+    fn is_event(&mut self, new: &OctetString, old: &OctetString) -> bool {
         new.value() != old.value()
     }
 }
 
 impl Updatable for Binary {
     type StaticVariation = StaticBinaryVariation;
-    type Detector = FlagsDetector;
+    type Detector = ChatterDetector<bool>;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.binary
@@ -638,6 +1189,14 @@ impl Updatable for Binary {
         &mut maps.binary
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.binary
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.binary
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::Binary(variation).with(range)
     }
@@ -645,11 +1204,15 @@ impl Updatable for Binary {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.binary
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for DoubleBitBinary {
     type StaticVariation = StaticDoubleBitBinaryVariation;
-    type Detector = FlagsDetector;
+    type Detector = ChatterDetector<DoubleBit>;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.double_bit_binary
@@ -659,6 +1222,14 @@ impl Updatable for DoubleBitBinary {
         &mut maps.double_bit_binary
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.double_bit_binary
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.double_bit_binary
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::DoubleBitBinary(variation).with(range)
     }
@@ -666,6 +1237,10 @@ impl Updatable for DoubleBitBinary {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.double_bit_binary
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for BinaryOutputStatus {
@@ -680,6 +1255,14 @@ impl Updatable for BinaryOutputStatus {
         &mut maps.binary_output_status
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.binary_output_status
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.binary_output_status
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::BinaryOutputStatus(variation).with(range)
     }
@@ -687,6 +1270,10 @@ impl Updatable for BinaryOutputStatus {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.binary_output_status
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for Counter {
@@ -701,6 +1288,14 @@ impl Updatable for Counter {
         &mut maps.counter
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.counter
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.counter
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::Counter(variation).with(range)
     }
@@ -708,6 +1303,10 @@ impl Updatable for Counter {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.counter
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for FrozenCounter {
@@ -722,6 +1321,14 @@ impl Updatable for FrozenCounter {
         &mut maps.frozen_counter
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.frozen_counter
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.frozen_counter
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::FrozenCounter(variation).with(range)
     }
@@ -729,11 +1336,15 @@ impl Updatable for FrozenCounter {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.frozen_counter
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for Analog {
     type StaticVariation = StaticAnalogVariation;
-    type Detector = Deadband<f64>;
+    type Detector = AnalogDetector;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.analog
@@ -743,6 +1354,14 @@ impl Updatable for Analog {
         &mut maps.analog
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.analog
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.analog
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::Analog(variation).with(range)
     }
@@ -750,11 +1369,15 @@ impl Updatable for Analog {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.analog
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for AnalogOutputStatus {
     type StaticVariation = StaticAnalogOutputStatusVariation;
-    type Detector = Deadband<f64>;
+    type Detector = AnalogDetector;
 
     fn get_map(maps: &StaticDatabase) -> &PointMap<Self> {
         &maps.analog_output_status
@@ -764,6 +1387,14 @@ impl Updatable for AnalogOutputStatus {
         &mut maps.analog_output_status
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.analog_output_status
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.analog_output_status
+    }
+
     fn wrap(range: IndexRange, variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::AnalogOutputStatus(variation).with(range)
     }
@@ -771,6 +1402,10 @@ impl Updatable for AnalogOutputStatus {
     fn enabled_class_zero(config: &ClassZeroConfig) -> bool {
         config.analog_output_status
     }
+
+    fn stamped(&self, time: Time) -> Option<Self> {
+        stamp(self, time)
+    }
 }
 
 impl Updatable for OctetString {
@@ -785,6 +1420,14 @@ impl Updatable for OctetString {
         &mut maps.octet_strings
     }
 
+    fn get_selected(values: &SelectedValues) -> &BTreeMap<u16, Self> {
+        &values.octet_strings
+    }
+
+    fn get_selected_mut(values: &mut SelectedValues) -> &mut BTreeMap<u16, Self> {
+        &mut values.octet_strings
+    }
+
     fn wrap(range: IndexRange, _variation: Option<Self::StaticVariation>) -> VariationRange {
         SpecificVariation::OctetString.with(range)
     }
@@ -794,6 +1437,84 @@ impl Updatable for OctetString {
     }
 }
 
+impl Snapshot for Binary {
+    type Config = BinaryConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        BinaryConfig {
+            chatter_filter: config.detector.config(),
+            ..BinaryConfig::new(config.s_var, config.e_var)
+        }
+    }
+}
+
+impl Snapshot for DoubleBitBinary {
+    type Config = DoubleBitBinaryConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        DoubleBitBinaryConfig {
+            chatter_filter: config.detector.config(),
+            ..DoubleBitBinaryConfig::new(config.s_var, config.e_var)
+        }
+    }
+}
+
+impl Snapshot for BinaryOutputStatus {
+    type Config = BinaryOutputStatusConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        BinaryOutputStatusConfig::new(config.s_var, config.e_var)
+    }
+}
+
+impl Snapshot for Counter {
+    type Config = CounterConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        CounterConfig::new(config.s_var, config.e_var, config.detector.deadband)
+    }
+}
+
+impl Snapshot for FrozenCounter {
+    type Config = FrozenCounterConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        FrozenCounterConfig::new(config.s_var, config.e_var, config.detector.deadband)
+    }
+}
+
+impl Snapshot for Analog {
+    type Config = AnalogConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        AnalogConfig {
+            deadband_mode: config.detector.mode,
+            max_report_interval: config.detector.max_report_interval,
+            ..AnalogConfig::new(config.s_var, config.e_var, config.detector.deadband)
+        }
+    }
+}
+
+impl Snapshot for AnalogOutputStatus {
+    type Config = AnalogOutputStatusConfig;
+
+    fn get_config(config: &PointConfig<Self>) -> Self::Config {
+        AnalogOutputStatusConfig {
+            deadband_mode: config.detector.mode,
+            max_report_interval: config.detector.max_report_interval,
+            ..AnalogOutputStatusConfig::new(config.s_var, config.e_var, config.detector.deadband)
+        }
+    }
+}
+
+impl Snapshot for OctetString {
+    type Config = OctetStringConfig;
+
+    fn get_config(_config: &PointConfig<Self>) -> Self::Config {
+        OctetStringConfig
+    }
+}
+
 impl Default for Binary {
     fn default() -> Self {
         Self::new(false, Flags::RESTART, Time::not_synchronized(0))
@@ -855,7 +1576,7 @@ mod tests {
             class: Some(EventClass::Class1),
             s_var: var,
             e_var: EventBinaryVariation::Group2Var1,
-            detector: FlagsDetector {},
+            detector: ChatterDetector::new(None),
         }
     }
 
@@ -873,7 +1594,7 @@ mod tests {
             class: Some(EventClass::Class1),
             s_var: var,
             e_var: EventAnalogVariation::Group32Var1,
-            detector: Deadband::new(0.0),
+            detector: AnalogDetector::new(0.0, DeadbandMode::Absolute, None),
         }
     }
 
@@ -885,12 +1606,13 @@ mod tests {
         assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
         assert!(db.add(2, analog_config(StaticAnalogVariation::Group30Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        selection.select_class_zero(&db);
 
         let mut buffer = [0u8; 64];
         let mut cursor = WriteCursor::new(buffer.as_mut());
 
-        db.write(&mut cursor).unwrap();
+        selection.write(&db, &mut cursor).unwrap();
 
         assert_eq!(
             cursor.written(),
@@ -913,13 +1635,14 @@ mod tests {
         assert!(db.add(1, counter_config(StaticCounterVariation::Group20Var1)));
         assert!(db.add(2, analog_config(StaticAnalogVariation::Group30Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        selection.select_class_zero(&db);
 
         let mut buffer = [0u8; 12]; // can only fit one header at a time
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap_err(); // incomplete !
+            selection.write(&db, &mut cursor).unwrap_err(); // incomplete !
 
             assert_eq!(
                 cursor.written(),
@@ -932,7 +1655,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap_err(); // incomplete !
+            selection.write(&db, &mut cursor).unwrap_err(); // incomplete !
 
             assert_eq!(
                 cursor.written(),
@@ -945,7 +1668,7 @@ mod tests {
 
         {
             let mut cursor = WriteCursor::new(buffer.as_mut());
-            db.write(&mut cursor).unwrap(); // complete !
+            selection.write(&db, &mut cursor).unwrap(); // complete !
 
             assert_eq!(
                 cursor.written(),
@@ -957,18 +1680,223 @@ mod tests {
         }
     }
 
+    fn update_binary(db: &mut StaticDatabase, value: bool) -> Option<Binary> {
+        let value = Binary::new(value, Flags::ONLINE, Time::not_synchronized(0));
+        let (exists, event) = db.update(&value, 0, UpdateOptions::default());
+        assert!(exists);
+        event.map(|(_, _, value)| value)
+    }
+
+    #[test]
+    fn chatter_filter_suppresses_events_and_sets_flag() {
+        let mut db = StaticDatabase::default();
+        let filter = ChatterFilter::new(3, Duration::from_secs(10), Duration::from_secs(5));
+        assert!(db.add(
+            0,
+            PointConfig {
+                detector: ChatterDetector::new(Some(filter)),
+                ..binary_config(StaticBinaryVariation::Group1Var2)
+            }
+        ));
+
+        // the first two transitions produce events normally
+        assert!(update_binary(&mut db, false).is_some());
+        assert!(update_binary(&mut db, true).is_some());
+        assert!(update_binary(&mut db, false).is_some());
+
+        // the third transition within the window sets the flag
+        let event = update_binary(&mut db, true).unwrap();
+        assert!(event.value);
+        assert!(event.flags.is_set(Flags::CHATTER_FILTER));
+
+        // further transitions are suppressed, but the static value is updated
+        assert!(update_binary(&mut db, false).is_none());
+        let current = db.get::<Binary>(0).unwrap();
+        assert!(!current.value);
+        assert!(current.flags.is_set(Flags::CHATTER_FILTER));
+
+        // an update before the quiet period has elapsed doesn't clear the flag
+        crate::tokio::time::advance(Duration::from_secs(4));
+        assert!(update_binary(&mut db, false).is_none());
+
+        // the first update after the quiet period clears the flag
+        crate::tokio::time::advance(Duration::from_secs(1));
+        let event = update_binary(&mut db, false).unwrap();
+        assert!(!event.value);
+        assert_eq!(event.flags, Flags::ONLINE);
+    }
+
+    #[test]
+    fn chatter_filter_clears_flag_after_quiet_period_without_update() {
+        let mut db = StaticDatabase::default();
+        let filter = ChatterFilter::new(2, Duration::from_secs(10), Duration::from_secs(5));
+        assert!(db.add(
+            0,
+            PointConfig {
+                detector: ChatterDetector::new(Some(filter)),
+                ..binary_config(StaticBinaryVariation::Group1Var2)
+            }
+        ));
+
+        assert!(update_binary(&mut db, false).is_some());
+        assert!(update_binary(&mut db, true).is_some());
+        let event = update_binary(&mut db, false).unwrap();
+        assert!(event.flags.is_set(Flags::CHATTER_FILTER));
+
+        // the flag is cleared once the quiet period elapses after the last transition
+        let mut events: Vec<Binary> = Vec::new();
+        let deadline = db
+            .check_report_deadlines::<Binary, _>(Instant::now(), |_, value, _, _| {
+                events.push(*value)
+            })
+            .unwrap();
+        assert_eq!(deadline, Instant::now() + Duration::from_secs(5));
+        assert!(events.is_empty());
+
+        crate::tokio::time::advance(Duration::from_secs(5));
+        let next = db.check_report_deadlines::<Binary, _>(Instant::now(), |_, value, _, _| {
+            events.push(*value)
+        });
+        assert_eq!(next, None);
+        assert_eq!(
+            events,
+            vec![Binary::new(false, Flags::ONLINE, Time::not_synchronized(0))]
+        );
+        assert_eq!(db.get::<Binary>(0).unwrap().flags, Flags::ONLINE);
+
+        // the next state change is detected normally
+        assert!(update_binary(&mut db, true).is_some());
+    }
+
+    #[test]
+    fn chatter_filter_ignores_transitions_outside_window() {
+        let mut db = StaticDatabase::default();
+        let filter = ChatterFilter::new(2, Duration::from_secs(1), Duration::from_secs(1));
+        assert!(db.add(
+            0,
+            PointConfig {
+                detector: ChatterDetector::new(Some(filter)),
+                ..binary_config(StaticBinaryVariation::Group1Var2)
+            }
+        ));
+
+        assert!(update_binary(&mut db, false).is_some());
+        for value in [true, false, true].iter() {
+            crate::tokio::time::advance(Duration::from_secs(2));
+            let event = update_binary(&mut db, *value).unwrap();
+            assert_eq!(event.flags, Flags::ONLINE);
+        }
+    }
+
+    fn add_analog(db: &mut StaticDatabase, detector: AnalogDetector) {
+        assert!(db.add(
+            0,
+            PointConfig {
+                detector,
+                ..analog_config(StaticAnalogVariation::Group30Var1)
+            }
+        ));
+    }
+
+    fn update_analog(db: &mut StaticDatabase, value: f64) -> bool {
+        let value = Analog::new(value, Flags::ONLINE, Time::not_synchronized(0));
+        let (exists, event) = db.update(&value, 0, UpdateOptions::default());
+        assert!(exists);
+        event.is_some()
+    }
+
+    #[test]
+    fn percent_deadband_is_relative_to_range() {
+        let mut db = StaticDatabase::default();
+        let mode = DeadbandMode::Percent {
+            min: -100.0,
+            max: 100.0,
+        };
+        add_analog(&mut db, AnalogDetector::new(5.0, mode, None));
+
+        assert!(update_analog(&mut db, 0.0));
+        // 5% of the 200 unit range is 10
+        assert!(!update_analog(&mut db, 10.0));
+        assert!(update_analog(&mut db, 10.5));
+        assert!(!update_analog(&mut db, 1.0));
+    }
+
+    #[test]
+    fn integrating_deadband_accumulates_deviation_over_time() {
+        let mut db = StaticDatabase::default();
+        add_analog(
+            &mut db,
+            AnalogDetector::new(10.0, DeadbandMode::Integrating, None),
+        );
+
+        assert!(update_analog(&mut db, 0.0));
+
+        // a deviation of 2 is held for 4 seconds => 8 value-seconds
+        assert!(!update_analog(&mut db, 2.0));
+        crate::tokio::time::advance(Duration::from_secs(4));
+        assert!(!update_analog(&mut db, 2.0));
+
+        // another 2 seconds => 12 value-seconds
+        crate::tokio::time::advance(Duration::from_secs(2));
+        assert!(update_analog(&mut db, 2.0));
+
+        // the integral restarts from the reported value
+        crate::tokio::time::advance(Duration::from_secs(100));
+        assert!(!update_analog(&mut db, 2.0));
+    }
+
+    #[test]
+    fn forces_events_when_max_report_interval_elapses() {
+        let mut db = StaticDatabase::default();
+        let interval = Duration::from_secs(60);
+        add_analog(
+            &mut db,
+            AnalogDetector::new(100.0, DeadbandMode::Absolute, Some(interval)),
+        );
+
+        let mut events: Vec<u16> = Vec::new();
+        let start = Instant::now();
+        let next =
+            db.check_report_deadlines::<Analog, _>(start, |index, _, _, _| events.push(index));
+        assert_eq!(next, Some(start + interval));
+        assert!(events.is_empty());
+
+        // a normal event moves the deadline, but the points aren't checked again until the earliest
+        // deadline that was previously reported
+        crate::tokio::time::advance(Duration::from_secs(30));
+        assert!(update_analog(&mut db, 0.0));
+        let next = db.check_report_deadlines::<Analog, _>(Instant::now(), |index, _, _, _| {
+            events.push(index)
+        });
+        assert_eq!(next, Some(start + interval));
+
+        crate::tokio::time::advance(Duration::from_secs(30));
+        let next = db.check_report_deadlines::<Analog, _>(Instant::now(), |index, _, _, _| {
+            events.push(index)
+        });
+        assert_eq!(next, Some(start + Duration::from_secs(90)));
+        assert!(events.is_empty());
+
+        crate::tokio::time::advance(Duration::from_secs(30));
+        let now = Instant::now();
+        let next = db.check_report_deadlines::<Analog, _>(now, |index, _, _, _| events.push(index));
+        assert_eq!(events, vec![0]);
+        assert_eq!(next, Some(now + interval));
+    }
+
     #[test]
     fn promotes_g1v1_to_g1v2_if_flags_other_than_just_online() {
         let mut db = StaticDatabase::default();
 
         assert!(db.add(0, binary_config(StaticBinaryVariation::Group1Var1)));
 
-        db.select_class_zero();
+        let mut selection = StaticSelection::default();
+        selection.select_class_zero(&db);
 
         let mut buffer = [0u8; 64];
         let mut cursor = WriteCursor::new(buffer.as_mut());
 
-        db.write(&mut cursor).unwrap();
+        selection.write(&db, &mut cursor).unwrap();
 
         assert_eq!(
             cursor.written(),
@@ -0,0 +1,251 @@
+use crate::outstation::database::*;
+
+/// Configuration and class assignment of a point in the database
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointInfo<C> {
+    /// index of the point
+    pub index: u16,
+    /// event class assignment (None == static only)
+    pub class: Option<EventClass>,
+    /// configuration of the point
+    pub config: C,
+}
+
+impl<C> PointInfo<C> {
+    pub(crate) fn new(index: u16, class: Option<EventClass>, config: C) -> Self {
+        Self {
+            index,
+            class,
+            config,
+        }
+    }
+}
+
+/// Iterator over the points of a single type in ascending index order
+pub struct PointIter<'a, C> {
+    inner: Box<dyn Iterator<Item = PointInfo<C>> + 'a>,
+}
+
+impl<'a, C> PointIter<'a, C> {
+    fn new<I>(inner: I) -> Self
+    where
+        I: Iterator<Item = PointInfo<C>> + 'a,
+    {
+        Self {
+            inner: Box::new(inner),
+        }
+    }
+}
+
+impl<'a, C> Iterator for PointIter<'a, C> {
+    type Item = PointInfo<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+/// trait for enumerating the points of a type by their configuration type
+///
+/// ```
+/// use dnp3::outstation::database::*;
+///
+/// fn binary_indices(database: &Database) -> Vec<u16> {
+///     let points: PointIter<BinaryConfig> = database.points();
+///     points.map(|x| x.index).collect()
+/// }
+/// ```
+pub trait Points<C> {
+    /// iterate over every configured point of the type in ascending index order
+    fn points(&self) -> PointIter<'_, C>;
+
+    /// retrieve the configuration and class of the point at an index, if it exists
+    fn point_info(&self, index: u16) -> Option<PointInfo<C>>;
+}
+
+/// Number of events currently buffered for an outstation, by class and by type
+///
+/// Events that have been reported but not yet confirmed by the master are included.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct EventCounts {
+    /// number of class 1 events
+    pub class_1: usize,
+    /// number of class 2 events
+    pub class_2: usize,
+    /// number of class 3 events
+    pub class_3: usize,
+    /// number of binary input events
+    pub binary: usize,
+    /// number of double-bit binary input events
+    pub double_bit_binary: usize,
+    /// number of binary output status events
+    pub binary_output_status: usize,
+    /// number of counter events
+    pub counter: usize,
+    /// number of frozen counter events
+    pub frozen_counter: usize,
+    /// number of analog input events
+    pub analog: usize,
+    /// number of analog output status events
+    pub analog_output_status: usize,
+    /// number of octet string events
+    pub octet_string: usize,
+}
+
+impl Points<BinaryConfig> for Database {
+    fn points(&self) -> PointIter<'_, BinaryConfig> {
+        PointIter::new(self.inner.points::<Binary>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<BinaryConfig>> {
+        self.inner.point_info::<Binary>(index)
+    }
+}
+
+impl Points<DoubleBitBinaryConfig> for Database {
+    fn points(&self) -> PointIter<'_, DoubleBitBinaryConfig> {
+        PointIter::new(self.inner.points::<DoubleBitBinary>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<DoubleBitBinaryConfig>> {
+        self.inner.point_info::<DoubleBitBinary>(index)
+    }
+}
+
+impl Points<BinaryOutputStatusConfig> for Database {
+    fn points(&self) -> PointIter<'_, BinaryOutputStatusConfig> {
+        PointIter::new(self.inner.points::<BinaryOutputStatus>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<BinaryOutputStatusConfig>> {
+        self.inner.point_info::<BinaryOutputStatus>(index)
+    }
+}
+
+impl Points<CounterConfig> for Database {
+    fn points(&self) -> PointIter<'_, CounterConfig> {
+        PointIter::new(self.inner.points::<Counter>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<CounterConfig>> {
+        self.inner.point_info::<Counter>(index)
+    }
+}
+
+impl Points<FrozenCounterConfig> for Database {
+    fn points(&self) -> PointIter<'_, FrozenCounterConfig> {
+        PointIter::new(self.inner.points::<FrozenCounter>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<FrozenCounterConfig>> {
+        self.inner.point_info::<FrozenCounter>(index)
+    }
+}
+
+impl Points<AnalogConfig> for Database {
+    fn points(&self) -> PointIter<'_, AnalogConfig> {
+        PointIter::new(self.inner.points::<Analog>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<AnalogConfig>> {
+        self.inner.point_info::<Analog>(index)
+    }
+}
+
+impl Points<AnalogOutputStatusConfig> for Database {
+    fn points(&self) -> PointIter<'_, AnalogOutputStatusConfig> {
+        PointIter::new(self.inner.points::<AnalogOutputStatus>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<AnalogOutputStatusConfig>> {
+        self.inner.point_info::<AnalogOutputStatus>(index)
+    }
+}
+
+impl Points<OctetStringConfig> for Database {
+    fn points(&self) -> PointIter<'_, OctetStringConfig> {
+        PointIter::new(self.inner.points::<OctetString>())
+    }
+
+    fn point_info(&self, index: u16) -> Option<PointInfo<OctetStringConfig>> {
+        self.inner.point_info::<OctetString>(index)
+    }
+}
+
+impl EventCounts {
+    /// total number of buffered events
+    pub fn total(&self) -> usize {
+        self.class_1 + self.class_2 + self.class_3
+    }
+
+    pub(crate) fn add(&mut self, other: &EventCounts) {
+        self.class_1 += other.class_1;
+        self.class_2 += other.class_2;
+        self.class_3 += other.class_3;
+        self.binary += other.binary;
+        self.double_bit_binary += other.double_bit_binary;
+        self.binary_output_status += other.binary_output_status;
+        self.counter += other.counter;
+        self.frozen_counter += other.frozen_counter;
+        self.analog += other.analog;
+        self.analog_output_status += other.analog_output_status;
+        self.octet_string += other.octet_string;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enumerates_points_and_counts_events() {
+        let mut db = Database::new();
+        let session = db.inner.attach(
+            None,
+            ClassZeroConfig::default(),
+            EventBufferConfig::all_types(5),
+            Default::default(),
+        );
+        let analog_config = AnalogConfig::new(
+            StaticAnalogVariation::Group30Var5,
+            EventAnalogVariation::Group32Var7,
+            2.5,
+        );
+        db.add(7, Some(EventClass::Class1), BinaryConfig::default());
+        db.add(3, None, BinaryConfig::default());
+        db.add(5, Some(EventClass::Class2), analog_config);
+
+        let points: PointIter<BinaryConfig> = db.points();
+        assert_eq!(
+            points.collect::<Vec<_>>(),
+            vec![
+                PointInfo::new(3, None, BinaryConfig::default()),
+                PointInfo::new(7, Some(EventClass::Class1), BinaryConfig::default()),
+            ]
+        );
+        assert_eq!(
+            db.point_info(5),
+            Some(PointInfo::new(5, Some(EventClass::Class2), analog_config))
+        );
+        assert_eq!(Points::<AnalogConfig>::point_info(&db, 4), None);
+
+        db.update(
+            7,
+            &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+            UpdateOptions::default(),
+        );
+        db.update(
+            5,
+            &Analog::new(10.0, Flags::ONLINE, Time::synchronized(0)),
+            UpdateOptions::default(),
+        );
+
+        let counts = db.inner.event_counts(session).unwrap();
+        assert_eq!(counts.class_1, 1);
+        assert_eq!(counts.class_2, 1);
+        assert_eq!(counts.class_3, 0);
+        assert_eq!(counts.binary, 1);
+        assert_eq!(counts.analog, 1);
+        assert_eq!(counts.total(), 2);
+    }
+}
@@ -1,20 +1,29 @@
+use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
 
 pub use config::*;
-use details::range::static_db::{Deadband, FlagsDetector, OctetStringDetector, PointConfig};
+use details::database::{SessionId, UnknownSession};
+use details::range::static_db::{
+    AnalogDetector, ChatterDetector, Deadband, FlagsDetector, OctetStringDetector, PointConfig,
+};
+pub use info::*;
+pub use snapshot::*;
 
 use crate::app::measurement::*;
 use crate::app::parse::parser::HeaderCollection;
-use crate::app::Iin2;
+use crate::app::{Iin2, Timestamp};
 use crate::master::EventClasses;
+use crate::outstation::clock::{ClockConfig, ClockStatus};
 use crate::outstation::database::read::ReadHeader;
 use crate::util::cursor::WriteCursor;
 
 mod config;
 /// private internal control only needed by the parent module
 mod details;
+mod info;
 /// read headers
 pub(crate) mod read;
+mod snapshot;
 
 /// Controls how events are processed when updating values in the database
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -29,6 +38,10 @@ pub enum EventMode {
 
 /// Event class (1/2/3) assignment
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum EventClass {
     /// Class 1 data per the protocol specification
     Class1,
@@ -255,50 +268,91 @@ pub trait Update<T> {
     fn update(&mut self, index: u16, value: &T, options: UpdateOptions) -> bool;
 }
 
+/// trait for modifying the flags of existing values in the database without changing their values
+///
+/// This is useful for marking points COMM_LOST when an upstream device disconnects, and then
+/// clearing the flag when it reconnects. The modified values are stamped with the time at which
+/// the flags changed.
+pub trait UpdateFlags<T> {
+    /// Set and then clear flags on every point of the type with an index in `range`.
+    /// The options control how static/event data is modified
+    ///
+    /// Points whose flags already have the requested state are left untouched. Returns the number
+    /// of points whose flags were modified
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize;
+
+    /// Set and then clear flags on every point of the type
+    ///
+    /// Returns the number of points whose flags were modified
+    fn update_all_flags(
+        &mut self,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.update_flags(0..=u16::MAX, set, clear, time, options)
+    }
+}
+
 /// trait for getting the current value in the database
 pub trait Get<T> {
     /// retrieve the current value off the database.
     fn get(&self, index: u16) -> Option<T>;
 }
 
-/// Core database implementation shared between an outstation task and the user facing API.
+/// Core database implementation shared between one or more outstation tasks and the user facing API.
 /// This type is always guarded by a `DatabaseHandle` which provides a transactional API.
 pub struct Database {
     pub(crate) inner: crate::outstation::database::details::database::Database,
 }
 
 impl Database {
-    /// Create a database by specified how it will buffer events
-    pub(crate) fn new(
-        max_read_selection: Option<u16>,
-        class_zero_config: ClassZeroConfig,
-        config: EventBufferConfig,
-    ) -> Self {
+    /// Create a database without any points or attached sessions
+    pub(crate) fn new() -> Self {
         Self {
-            inner: crate::outstation::database::details::database::Database::new(
-                max_read_selection,
-                class_zero_config,
-                config,
-            ),
+            inner: crate::outstation::database::details::database::Database::new(),
         }
     }
+
+    /// Retrieve the state of the outstation clock, if enabled in the `OutstationConfig`
+    pub fn clock_status(&self) -> Option<ClockStatus> {
+        self.inner.clock().map(|clock| clock.status())
+    }
 }
 
 /// Handle type that can be used to perform transactions on an underlying database
+///
+/// Each handle is associated with a session that has its own event buffer and read selection
+/// while the static points are shared with every other session attached to the same database.
+/// The session of a handle is attached until the task that owns it is dropped, so the
+/// methods used by the task itself never observe an unknown session.
 #[derive(Clone)]
 pub(crate) struct DatabaseHandle {
     inner: Arc<Mutex<Database>>,
+    session: SessionId,
     notify: Arc<crate::tokio::sync::Notify>,
 }
 
+// the task only uses its handle while the session is attached
+const ATTACHED: &str = "session is attached while its task exists";
+
 impl DatabaseHandle {
     /// Perform a transaction on the underlying database using a closure
     pub(crate) fn transaction<F, R>(&self, mut func: F) -> R
     where
         F: FnMut(&mut Database) -> R,
     {
-        let ret = func(&mut self.inner.lock().unwrap());
-        self.notify.notify_one();
+        let mut guard = self.inner.lock().unwrap();
+        let ret = func(&mut guard);
+        guard.inner.notify_sessions();
         ret
     }
 
@@ -311,29 +365,112 @@ impl DatabaseHandle {
         class_zero_config: ClassZeroConfig,
         event_config: EventBufferConfig,
     ) -> Self {
+        Self::attach_to(
+            Arc::new(Mutex::new(Database::new())),
+            max_read_selection,
+            class_zero_config,
+            event_config,
+        )
+    }
+
+    /// attach a new session to the same underlying database
+    pub(crate) fn attach(
+        &self,
+        max_read_selection: Option<u16>,
+        class_zero_config: ClassZeroConfig,
+        event_config: EventBufferConfig,
+    ) -> Self {
+        Self::attach_to(
+            self.inner.clone(),
+            max_read_selection,
+            class_zero_config,
+            event_config,
+        )
+    }
+
+    fn attach_to(
+        inner: Arc<Mutex<Database>>,
+        max_read_selection: Option<u16>,
+        class_zero_config: ClassZeroConfig,
+        event_config: EventBufferConfig,
+    ) -> Self {
+        let notify = Arc::new(crate::tokio::sync::Notify::new());
+        let session = inner.lock().unwrap().inner.attach(
+            max_read_selection,
+            class_zero_config,
+            event_config,
+            notify.clone(),
+        );
         Self {
-            inner: Arc::new(Mutex::new(Database::new(
-                max_read_selection,
-                class_zero_config,
-                event_config,
-            ))),
-            notify: Arc::new(crate::tokio::sync::Notify::new()),
+            inner,
+            session,
+            notify,
         }
     }
 
+    /// enable the outstation clock if it isn't already enabled
+    pub(crate) fn enable_clock(&self, config: ClockConfig) {
+        self.inner.lock().unwrap().inner.enable_clock(config);
+    }
+
+    /// detach the session from the database, discarding any buffered events
+    pub(crate) fn detach(&self) {
+        self.inner.lock().unwrap().inner.detach(self.session);
+    }
+
     pub(crate) fn clear_written_events(&mut self) {
-        self.inner.lock().unwrap().inner.clear_written_events();
+        self.inner
+            .lock()
+            .unwrap()
+            .inner
+            .clear_written_events(self.session)
+            .expect(ATTACHED);
     }
 
     pub(crate) fn get_events_info(&self) -> EventsInfo {
         let guard = self.inner.lock().unwrap();
 
         EventsInfo {
-            unwritten_classes: guard.inner.unwritten_classes(),
-            is_overflown: guard.inner.is_overflown(),
+            unwritten_classes: guard.inner.unwritten_classes(self.session).expect(ATTACHED),
+            is_overflown: guard.inner.is_overflown(self.session).expect(ATTACHED),
         }
     }
 
+    /// apply a time to the outstation clock, returning false if the clock isn't enabled
+    pub(crate) fn write_time(&self, time: Timestamp) -> bool {
+        match self.inner.lock().unwrap().inner.clock_mut() {
+            Some(clock) => {
+                clock.write(time);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn need_time(&self) -> bool {
+        self.inner
+            .lock()
+            .unwrap()
+            .inner
+            .clock()
+            .map(|clock| clock.need_time())
+            .unwrap_or(false)
+    }
+
+    /// fails if the task that owns the session has been dropped
+    pub(crate) fn event_counts(&self) -> Result<EventCounts, UnknownSession> {
+        self.inner.lock().unwrap().inner.event_counts(self.session)
+    }
+
+    pub(crate) fn unwritten_count(&self, class: EventClass) -> usize {
+        self.inner
+            .lock()
+            .unwrap()
+            .inner
+            .unwritten_count(self.session, class)
+            .expect(ATTACHED)
+    }
+
     pub(crate) fn select(&mut self, headers: &HeaderCollection) -> Iin2 {
         let mut iin2 = Iin2::default();
         let mut guard = self.inner.lock().unwrap();
@@ -342,18 +479,38 @@ impl DatabaseHandle {
                 None => {
                     iin2 |= Iin2::NO_FUNC_CODE_SUPPORT;
                 }
-                Some(x) => iin2 |= guard.inner.select_by_header(x),
+                Some(x) => {
+                    iin2 |= guard
+                        .inner
+                        .select_by_header(self.session, x)
+                        .expect(ATTACHED)
+                }
             }
         }
         iin2
     }
 
+    /// reset the selection and then select previously parsed READ headers
+    pub(crate) fn select_headers(&mut self, headers: &[ReadHeader]) -> Iin2 {
+        let mut guard = self.inner.lock().unwrap();
+        guard.inner.reset(self.session).expect(ATTACHED);
+        let mut iin2 = Iin2::default();
+        for header in headers.iter() {
+            iin2 |= guard
+                .inner
+                .select_by_header(self.session, *header)
+                .expect(ATTACHED);
+        }
+        iin2
+    }
+
     pub(crate) fn write_response_headers(&mut self, cursor: &mut WriteCursor) -> ResponseInfo {
         self.inner
             .lock()
             .unwrap()
             .inner
-            .write_response_headers(cursor)
+            .write_response_headers(self.session, cursor)
+            .expect(ATTACHED)
     }
 
     pub(crate) fn write_unsolicited(
@@ -362,16 +519,33 @@ impl DatabaseHandle {
         cursor: &mut WriteCursor,
     ) -> usize {
         let mut guard = self.inner.lock().unwrap();
-        guard.inner.reset();
-        let count = guard.inner.select_event_classes(classes);
+        guard.inner.reset(self.session).expect(ATTACHED);
+        let count = guard
+            .inner
+            .select_event_classes(self.session, classes)
+            .expect(ATTACHED);
         if count == 0 {
             return 0;
         }
-        guard.inner.write_events_only(cursor)
+        guard
+            .inner
+            .write_events_only(self.session, cursor)
+            .expect(ATTACHED)
     }
 
     pub(crate) fn reset(&mut self) {
-        self.inner.lock().unwrap().inner.reset()
+        self.inner
+            .lock()
+            .unwrap()
+            .inner
+            .reset(self.session)
+            .expect(ATTACHED)
+    }
+
+    /// produce events for points whose maximum reporting interval has elapsed or whose chatter filter
+    /// has cleared, returning the next deadline
+    pub(crate) fn check_report_deadlines(&self) -> Option<crate::tokio::time::Instant> {
+        self.inner.lock().unwrap().inner.check_report_deadlines()
     }
 }
 
@@ -423,10 +597,112 @@ impl Update<OctetString> for Database {
     }
 }
 
+impl UpdateFlags<Binary> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<Binary>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<DoubleBitBinary> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<DoubleBitBinary>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<BinaryOutputStatus> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<BinaryOutputStatus>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<Counter> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<Counter>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<FrozenCounter> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<FrozenCounter>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<Analog> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<Analog>(range, set, clear, time, options)
+    }
+}
+
+impl UpdateFlags<AnalogOutputStatus> for Database {
+    fn update_flags(
+        &mut self,
+        range: RangeInclusive<u16>,
+        set: Flags,
+        clear: Flags,
+        time: Time,
+        options: UpdateOptions,
+    ) -> usize {
+        self.inner
+            .update_flags::<AnalogOutputStatus>(range, set, clear, time, options)
+    }
+}
+
 impl Add<BinaryConfig> for Database {
     fn add(&mut self, index: u16, class: Option<EventClass>, config: BinaryConfig) -> bool {
-        let config =
-            PointConfig::<Binary>::new(class, FlagsDetector {}, config.s_var, config.e_var);
+        let config = PointConfig::<Binary>::new(
+            class,
+            ChatterDetector::new(config.chatter_filter),
+            config.s_var,
+            config.e_var,
+        );
         self.inner.add(index, config)
     }
 }
@@ -440,7 +716,7 @@ impl Add<DoubleBitBinaryConfig> for Database {
     ) -> bool {
         let config = PointConfig::<DoubleBitBinary>::new(
             class,
-            FlagsDetector {},
+            ChatterDetector::new(config.chatter_filter),
             config.s_var,
             config.e_var,
         );
@@ -493,7 +769,11 @@ impl Add<AnalogConfig> for Database {
     fn add(&mut self, index: u16, class: Option<EventClass>, config: AnalogConfig) -> bool {
         let config = PointConfig::<Analog>::new(
             class,
-            Deadband::new(config.deadband),
+            AnalogDetector::new(
+                config.deadband,
+                config.deadband_mode,
+                config.max_report_interval,
+            ),
             config.s_var,
             config.e_var,
         );
@@ -510,7 +790,11 @@ impl Add<AnalogOutputStatusConfig> for Database {
     ) -> bool {
         let config = PointConfig::<AnalogOutputStatus>::new(
             class,
-            Deadband::new(config.deadband),
+            AnalogDetector::new(
+                config.deadband,
+                config.deadband_mode,
+                config.max_report_interval,
+            ),
             config.s_var,
             config.e_var,
         );
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+
+use crate::outstation::database::details::range::static_db::Updatable;
+use crate::outstation::database::*;
+
+/// Static value, configuration and class assignment of a single point
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct PointSnapshot<T, C> {
+    /// index of the point
+    pub index: u16,
+    /// event class assignment (None == static only)
+    pub class: Option<EventClass>,
+    /// configuration of the point
+    pub config: C,
+    /// current value, flags and timestamp
+    pub value: T,
+}
+
+impl<T, C> PointSnapshot<T, C> {
+    /// construct a `PointSnapshot` from its fields
+    pub fn new(index: u16, class: Option<EventClass>, config: C, value: T) -> Self {
+        Self {
+            index,
+            class,
+            config,
+            value,
+        }
+    }
+}
+
+/// Full contents of the static portion of a `Database`
+///
+/// Buffered events are not part of the snapshot. When the `serialization` feature is enabled,
+/// this type and everything it contains implements `serde::Serialize` and `serde::Deserialize`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialization",
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct DatabaseSnapshot {
+    /// version of the snapshot format, see [DatabaseSnapshot::VERSION]
+    pub version: u16,
+    /// binary input points
+    pub binary: Vec<PointSnapshot<Binary, BinaryConfig>>,
+    /// double-bit binary input points
+    pub double_bit_binary: Vec<PointSnapshot<DoubleBitBinary, DoubleBitBinaryConfig>>,
+    /// binary output status points
+    pub binary_output_status: Vec<PointSnapshot<BinaryOutputStatus, BinaryOutputStatusConfig>>,
+    /// counter points
+    pub counter: Vec<PointSnapshot<Counter, CounterConfig>>,
+    /// frozen counter points
+    pub frozen_counter: Vec<PointSnapshot<FrozenCounter, FrozenCounterConfig>>,
+    /// analog input points
+    pub analog: Vec<PointSnapshot<Analog, AnalogConfig>>,
+    /// analog output status points
+    pub analog_output_status: Vec<PointSnapshot<AnalogOutputStatus, AnalogOutputStatusConfig>>,
+    /// octet string points
+    pub octet_string: Vec<PointSnapshot<OctetString, OctetStringConfig>>,
+}
+
+impl DatabaseSnapshot {
+    /// version of the snapshot format produced by this version of the library
+    pub const VERSION: u16 = 1;
+}
+
+/// Errors that can occur when restoring a `DatabaseSnapshot`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    /// the snapshot was produced with an unsupported format version
+    UnsupportedVersion(u16),
+    /// the snapshot contains the same index more than once for a type
+    DuplicateIndex(u16),
+    /// the value of the point with the index could not be restored
+    PointNotRestored(u16),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "snapshot version {} is not supported (expected {})",
+                version,
+                DatabaseSnapshot::VERSION
+            ),
+            Self::DuplicateIndex(index) => {
+                write!(f, "snapshot contains duplicate index: {}", index)
+            }
+            Self::PointNotRestored(index) => {
+                write!(f, "unable to restore the value of point: {}", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl Database {
+    /// Capture the value, flags, timestamp, configuration and class assignment of every point
+    pub fn snapshot(&self) -> DatabaseSnapshot {
+        DatabaseSnapshot {
+            version: DatabaseSnapshot::VERSION,
+            binary: self.inner.snapshot::<Binary>(),
+            double_bit_binary: self.inner.snapshot::<DoubleBitBinary>(),
+            binary_output_status: self.inner.snapshot::<BinaryOutputStatus>(),
+            counter: self.inner.snapshot::<Counter>(),
+            frozen_counter: self.inner.snapshot::<FrozenCounter>(),
+            analog: self.inner.snapshot::<Analog>(),
+            analog_output_status: self.inner.snapshot::<AnalogOutputStatus>(),
+            octet_string: self.inner.snapshot::<OctetString>(),
+        }
+    }
+
+    /// Replace every point in the database with the contents of a snapshot
+    ///
+    /// The snapshot is validated before the database is modified. Restoring values
+    /// never produces events, and the restored values become the basis for future
+    /// event detection.
+    ///
+    /// If a point fails to be added or restored despite the validation, the error is returned
+    /// and the database only contains the points restored up to that point.
+    pub fn restore(&mut self, snapshot: &DatabaseSnapshot) -> Result<(), SnapshotError> {
+        if snapshot.version != DatabaseSnapshot::VERSION {
+            return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+        }
+
+        check_indices(&snapshot.binary)?;
+        check_indices(&snapshot.double_bit_binary)?;
+        check_indices(&snapshot.binary_output_status)?;
+        check_indices(&snapshot.counter)?;
+        check_indices(&snapshot.frozen_counter)?;
+        check_indices(&snapshot.analog)?;
+        check_indices(&snapshot.analog_output_status)?;
+        check_indices(&snapshot.octet_string)?;
+
+        self.restore_points(&snapshot.binary)?;
+        self.restore_points(&snapshot.double_bit_binary)?;
+        self.restore_points(&snapshot.binary_output_status)?;
+        self.restore_points(&snapshot.counter)?;
+        self.restore_points(&snapshot.frozen_counter)?;
+        self.restore_points(&snapshot.analog)?;
+        self.restore_points(&snapshot.analog_output_status)?;
+        self.restore_points(&snapshot.octet_string)?;
+
+        Ok(())
+    }
+
+    fn restore_points<T, C>(&mut self, points: &[PointSnapshot<T, C>]) -> Result<(), SnapshotError>
+    where
+        T: Updatable,
+        C: Copy,
+        Database: Add<C>,
+    {
+        self.inner.clear::<T>();
+        for point in points {
+            if !self.add(point.index, point.class, point.config) {
+                return Err(SnapshotError::DuplicateIndex(point.index));
+            }
+            if !self.inner.restore(point.index, &point.value) {
+                return Err(SnapshotError::PointNotRestored(point.index));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_indices<T, C>(points: &[PointSnapshot<T, C>]) -> Result<(), SnapshotError> {
+    let mut indices = HashSet::new();
+    for point in points {
+        if !indices.insert(point.index) {
+            return Err(SnapshotError::DuplicateIndex(point.index));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_database() -> Database {
+        Database::new()
+    }
+
+    #[test]
+    fn restores_values_and_configuration_from_snapshot() {
+        let mut db = create_database();
+        db.add(3, Some(EventClass::Class1), BinaryConfig::default());
+        db.add(
+            7,
+            None,
+            AnalogConfig::new(
+                StaticAnalogVariation::Group30Var5,
+                EventAnalogVariation::Group32Var7,
+                2.5,
+            ),
+        );
+        db.update(
+            7,
+            &Analog::new(42.0, Flags::ONLINE, Time::synchronized(1000)),
+            UpdateOptions::initialize(),
+        );
+
+        let snapshot = db.snapshot();
+
+        let mut other = create_database();
+        let session = other.inner.attach(
+            None,
+            ClassZeroConfig::default(),
+            EventBufferConfig::all_types(5),
+            Default::default(),
+        );
+        other.add(1, Some(EventClass::Class2), BinaryConfig::default());
+        other.restore(&snapshot).unwrap();
+
+        assert_eq!(other.snapshot(), snapshot);
+        assert_eq!(Get::<Binary>::get(&other, 1), None);
+        assert_eq!(
+            Get::<Analog>::get(&other, 7),
+            Some(Analog::new(42.0, Flags::ONLINE, Time::synchronized(1000)))
+        );
+        // restored values are the basis for event detection
+        assert!(!other.inner.unwritten_classes(session).unwrap().any());
+    }
+
+    #[test]
+    fn rejects_invalid_snapshots_without_modifying_database() {
+        let mut db = create_database();
+        db.add(0, None, CounterConfig::default());
+
+        let mut snapshot = create_database().snapshot();
+        snapshot.version = DatabaseSnapshot::VERSION + 1;
+        assert_eq!(
+            db.restore(&snapshot),
+            Err(SnapshotError::UnsupportedVersion(
+                DatabaseSnapshot::VERSION + 1
+            ))
+        );
+
+        snapshot.version = DatabaseSnapshot::VERSION;
+        let point = PointSnapshot::new(4, None, BinaryConfig::default(), Binary::default());
+        snapshot.binary = vec![point.clone(), point];
+        assert_eq!(db.restore(&snapshot), Err(SnapshotError::DuplicateIndex(4)));
+
+        assert!(Get::<Counter>::get(&db, 0).is_some());
+    }
+
+    #[test]
+    fn reports_points_that_cannot_be_added() {
+        let mut db = create_database();
+        let point = PointSnapshot::new(2, None, CounterConfig::default(), Counter::default());
+        assert_eq!(
+            db.restore_points(&[point.clone(), point]),
+            Err(SnapshotError::DuplicateIndex(2))
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn restores_snapshot_serialized_as_json() {
+        let mut db = create_database();
+        db.add(0, Some(EventClass::Class1), BinaryConfig::default());
+        db.add(2, Some(EventClass::Class3), CounterConfig::default());
+        db.update(
+            2,
+            &Counter::new(17, Flags::ONLINE, Time::synchronized(500)),
+            UpdateOptions::initialize(),
+        );
+
+        let json = serde_json::to_string(&db.snapshot()).unwrap();
+        let snapshot: DatabaseSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot, db.snapshot());
+
+        let mut other = create_database();
+        other.restore(&snapshot).unwrap();
+        assert_eq!(other.snapshot(), db.snapshot());
+        assert_eq!(
+            Get::<Counter>::get(&other, 2),
+            Some(Counter::new(17, Flags::ONLINE, Time::synchronized(500)))
+        );
+    }
+}
@@ -85,14 +85,7 @@ impl DeferredRead {
         match self.info {
             None => None,
             Some(x) => {
-                let iin2 = database.transaction(|db| {
-                    db.inner.reset();
-                    let mut iin2 = Iin2::default();
-                    for header in self.vec.iter() {
-                        iin2 |= db.inner.select_by_header(*header);
-                    }
-                    iin2
-                });
+                let iin2 = database.select_headers(&self.vec);
                 self.clear();
                 Some(x.merge(iin2))
             }
@@ -1,9 +1,15 @@
+pub use audit::*;
+pub use clock::*;
 pub use config::*;
+pub use pending::*;
 pub use traits::*;
 
 use crate::app::Shutdown;
-use crate::decode::DecodeLevel;
-use crate::outstation::database::{Database, DatabaseHandle};
+use crate::capture::{CaptureSink, SharedCaptureSink};
+use crate::decode::{DecodeEventSink, DecodeLevel};
+use crate::outstation::database::{
+    Database, DatabaseHandle, DatabaseSnapshot, EventCounts, SnapshotError,
+};
 use crate::outstation::task::{ConfigurationChange, OutstationMessage};
 use crate::util::channel::Sender;
 
@@ -14,13 +20,18 @@ pub mod database;
 
 /// wraps an outstation task so that it can switch communication sessions
 pub(crate) mod adapter;
+mod audit;
+mod clock;
 mod config;
 /// functionality for processing control requests
 pub(crate) mod control;
 /// handling of deferred read requests
 pub(crate) mod deferred;
+mod pending;
 /// outstation session
 pub(crate) mod session;
+/// callbacks shared between multiple outstation tasks
+pub(crate) mod shared;
 /// async outstation task
 pub(crate) mod task;
 mod traits;
@@ -33,6 +44,8 @@ mod tests;
 pub struct OutstationHandle {
     database: DatabaseHandle,
     sender: Sender<OutstationMessage>,
+    // tasks serving additional concurrent sessions of the same outstation
+    peers: Vec<OutstationHandle>,
 }
 
 impl OutstationHandle {
@@ -44,14 +57,117 @@ impl OutstationHandle {
         self.database.transaction(func)
     }
 
+    /// Capture the static values and point configuration of the database
+    pub fn snapshot(&self) -> DatabaseSnapshot {
+        self.database.transaction(|db| db.snapshot())
+    }
+
+    /// Replace the contents of the database with a previously captured snapshot
+    ///
+    /// Restoring a snapshot does not generate any events
+    pub fn restore(&self, snapshot: &DatabaseSnapshot) -> Result<(), SnapshotError> {
+        self.database.transaction(|db| db.restore(snapshot))
+    }
+
+    /// Count the events currently buffered for this outstation by class and type
+    ///
+    /// When a TCP server accepts concurrent sessions with [ConnectionPolicy::AcceptConcurrent](crate::tcp::ConnectionPolicy::AcceptConcurrent),
+    /// every session has its own event buffer and the counts are summed across all of them. An update that
+    /// produces an event in several buffers is therefore counted once per session.
+    ///
+    /// Fails with `Shutdown` once an outstation task has been dropped and its events discarded
+    pub fn event_counts(&self) -> Result<EventCounts, Shutdown> {
+        let mut counts = self.database.event_counts().map_err(|_| Shutdown)?;
+        for peer in self.peers.iter() {
+            counts.add(&peer.event_counts()?);
+        }
+        Ok(counts)
+    }
+
+    /// Retrieve the state of the outstation clock, if enabled in the `OutstationConfig`
+    pub fn clock_status(&self) -> Option<ClockStatus> {
+        self.database.transaction(|db| db.clock_status())
+    }
+
     /// Set the decode level of the outstation
     pub async fn set_decode_level(&mut self, decode_level: DecodeLevel) -> Result<(), Shutdown> {
         self.sender
             .send(ConfigurationChange::SetDecodeLevel(decode_level).into())
             .await?;
+        for peer in self.peers.iter_mut() {
+            peer.sender
+                .send(ConfigurationChange::SetDecodeLevel(decode_level).into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Install a sink that receives an audit record for every control processed by the outstation
+    ///
+    /// Replaces any previously installed sink
+    pub async fn set_audit_sink(
+        &mut self,
+        sink: Box<dyn CommandAuditSink>,
+    ) -> Result<(), Shutdown> {
+        let sink: SharedAuditSink = std::sync::Arc::new(std::sync::Mutex::new(sink));
+        self.sender
+            .send(ConfigurationChange::SetAuditSink(sink.clone()).into())
+            .await?;
+        for peer in self.peers.iter_mut() {
+            peer.sender
+                .send(ConfigurationChange::SetAuditSink(sink.clone()).into())
+                .await?;
+        }
         Ok(())
     }
 
+    /// Install a sink that receives every physical layer read and write of the outstation, or remove it with `None`
+    ///
+    /// Replaces any previously installed sink. When the outstation shares a TCP connection or serial
+    /// line with other outstations, the sink receives all of the traffic on the shared physical layer.
+    /// Outstations sharing the same sink record each read and write only once.
+    pub async fn set_capture_sink(
+        &mut self,
+        sink: Option<Box<dyn CaptureSink>>,
+    ) -> Result<(), Shutdown> {
+        let sink: Option<SharedCaptureSink> =
+            sink.map(|x| std::sync::Arc::new(std::sync::Mutex::new(x)));
+        self.sender
+            .send(ConfigurationChange::SetCaptureSink(sink.clone()).into())
+            .await?;
+        for peer in self.peers.iter_mut() {
+            peer.sender
+                .send(ConfigurationChange::SetCaptureSink(sink.clone()).into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Install a sink that receives the structured events decoded from the traffic of the outstation, or remove it with `None`
+    ///
+    /// Replaces any previously installed decode event sink. It is independent of the sink installed with
+    /// [`set_capture_sink`](Self::set_capture_sink), so a capture and decode events may be active at once.
+    /// Like the capture sink, it receives all of the traffic on a shared physical layer.
+    pub async fn set_decode_event_sink(
+        &mut self,
+        sink: Option<Box<dyn DecodeEventSink>>,
+    ) -> Result<(), Shutdown> {
+        let sink = sink.map(crate::decode::shared_event_sink);
+        self.sender
+            .send(ConfigurationChange::SetDecodeEventSink(sink.clone()).into())
+            .await?;
+        for peer in self.peers.iter_mut() {
+            peer.sender
+                .send(ConfigurationChange::SetDecodeEventSink(sink.clone()).into())
+                .await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_peer(&mut self, peer: &OutstationHandle) {
+        self.peers.push(peer.clone());
+    }
+
     pub(crate) async fn shutdown(&mut self) -> Result<(), Shutdown> {
         self.sender.send(OutstationMessage::Shutdown).await?;
         Ok(())
@@ -0,0 +1,61 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::app::control::CommandStatus;
+
+/// Result of beginning a control operation
+pub enum OperateResult {
+    /// the operation completed immediately with the specified status
+    Complete(CommandStatus),
+    /// the operation is still in progress and completes asynchronously
+    Pending(PendingOperate),
+}
+
+/// Control operation that completes asynchronously, e.g. while waiting for feedback from hardware
+///
+/// The outstation defers its response until every pending control in the request completes
+/// or `OutstationConfig::operate_timeout` elapses. Controls that don't complete in time are
+/// reported with `CommandStatus::Timeout`.
+pub struct PendingOperate {
+    future: Pin<Box<dyn Future<Output = CommandStatus> + Send>>,
+}
+
+/// Completes a [PendingOperate](crate::outstation::PendingOperate) created with [PendingOperate::channel]
+///
+/// If dropped without being completed, the operation completes with `CommandStatus::Canceled`.
+pub struct OperateCompletion {
+    tx: crate::tokio::sync::oneshot::Sender<CommandStatus>,
+}
+
+impl PendingOperate {
+    /// create a pending operation that completes when the future does
+    ///
+    /// The future is polled by the outstation task, so it should not block.
+    pub fn new<F>(future: F) -> Self
+    where
+        F: Future<Output = CommandStatus> + Send + 'static,
+    {
+        Self {
+            future: Box::pin(future),
+        }
+    }
+
+    /// create a pending operation that is completed from another task or thread
+    pub fn channel() -> (Self, OperateCompletion) {
+        let (tx, rx) = crate::tokio::sync::oneshot::channel();
+        let pending = Self::new(async move { rx.await.unwrap_or(CommandStatus::Canceled) });
+        (pending, OperateCompletion { tx })
+    }
+
+    pub(crate) fn poll_status(&mut self, cx: &mut Context) -> Poll<CommandStatus> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl OperateCompletion {
+    /// complete the operation with the specified status
+    pub fn complete(self, status: CommandStatus) {
+        self.tx.send(status).ok();
+    }
+}
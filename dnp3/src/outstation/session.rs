@@ -1,4 +1,5 @@
 use std::borrow::BorrowMut;
+use std::net::SocketAddr;
 
 use tracing::Instrument;
 use xxhash_rust::xxh64::xxh64;
@@ -13,16 +14,19 @@ use crate::app::parse::parser::{HeaderCollection, HeaderDetails, Request};
 use crate::app::variations::{Group50Var3, Group52Var1, Group52Var2};
 use crate::app::*;
 use crate::app::{ControlField, Iin, Iin1, Iin2, ResponseFunction, ResponseHeader};
+use crate::capture::CaptureSlot;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::BroadcastConfirmMode;
 use crate::link::EndpointAddress;
 use crate::master::EventClasses;
+use crate::outstation::audit::CommandAudit;
 use crate::outstation::config::OutstationConfig;
-use crate::outstation::config::{BufferSize, Feature};
+use crate::outstation::config::{BufferSize, Feature, UnsolicitedHoldConfig};
 use crate::outstation::control::collection::{ControlCollection, ControlTransaction};
+use crate::outstation::control::pending::PendingControls;
 use crate::outstation::control::select::SelectState;
-use crate::outstation::database::{DatabaseHandle, ResponseInfo};
+use crate::outstation::database::{DatabaseHandle, EventClass, ResponseInfo};
 use crate::outstation::deferred::DeferredRead;
 use crate::outstation::task::{ConfigurationChange, OutstationMessage};
 use crate::outstation::traits::*;
@@ -141,10 +145,12 @@ pub(crate) struct SessionConfig {
     master_address: EndpointAddress,
     confirm_timeout: std::time::Duration,
     select_timeout: std::time::Duration,
+    operate_timeout: std::time::Duration,
     broadcast: Feature,
     unsolicited: Feature,
     max_unsolicited_retries: Option<usize>,
     unsolicited_retry_delay: std::time::Duration,
+    unsolicited_hold: UnsolicitedHoldConfig,
     keep_alive_timeout: Option<std::time::Duration>,
     max_controls_per_request: Option<u16>,
 }
@@ -162,10 +168,12 @@ impl From<OutstationConfig> for SessionConfig {
             master_address: config.master_address,
             confirm_timeout: config.confirm_timeout,
             select_timeout: config.select_timeout,
+            operate_timeout: config.operate_timeout,
             broadcast: config.features.broadcast,
             unsolicited: config.features.unsolicited,
             max_unsolicited_retries: config.max_unsolicited_retries,
             unsolicited_retry_delay: config.unsolicited_retry_delay,
+            unsolicited_hold: config.unsolicited_hold,
             keep_alive_timeout: config.keep_alive_timeout,
             max_controls_per_request: config.max_controls_per_request,
         }
@@ -199,6 +207,8 @@ struct SessionState {
     select: Option<SelectState>,
     unsolicited: UnsolicitedState,
     unsolicited_seq: Sequence,
+    // time at which unreported events of each class were first detected
+    held_since: [Option<crate::tokio::time::Instant>; 3],
     deferred_read: DeferredRead,
     last_recorded_time: Option<crate::tokio::time::Instant>,
     last_broadcast_type: Option<BroadcastConfirmMode>,
@@ -213,6 +223,7 @@ impl SessionState {
             select: None,
             unsolicited: UnsolicitedState::NullRequired,
             unsolicited_seq: Sequence::default(),
+            held_since: [None; 3],
             deferred_read: DeferredRead::new(max_read_headers),
             last_recorded_time: None,
             last_broadcast_type: None,
@@ -223,6 +234,7 @@ impl SessionState {
     fn reset(&mut self) {
         self.last_valid_request = None;
         self.select = None;
+        self.held_since = [None; 3];
         self.deferred_read.clear();
     }
 }
@@ -236,6 +248,11 @@ pub(crate) struct OutstationSession {
     application: Box<dyn OutstationApplication>,
     info: Box<dyn OutstationInformation>,
     control_handler: Box<dyn ControlHandler>,
+    pending_controls: PendingControls,
+    audit: CommandAudit,
+    capture: CaptureSlot,
+    // id and address of the current TCP session, if any
+    connection: Option<(u64, SocketAddr)>,
     next_link_status: Option<crate::tokio::time::Instant>,
 }
 
@@ -318,6 +335,10 @@ impl OutstationSession {
             application,
             info: information,
             control_handler,
+            pending_controls: PendingControls::new(),
+            audit: CommandAudit::new(),
+            capture: CaptureSlot::default(),
+            connection: None,
             next_link_status,
         }
     }
@@ -336,6 +357,7 @@ impl OutstationSession {
         writer: &mut TransportWriter,
         database: &mut DatabaseHandle,
     ) -> RunError {
+        io.set_capture(self.capture.clone());
         loop {
             if let Err(err) = self.run_idle_state(io, reader, writer, database).await {
                 return err;
@@ -345,6 +367,12 @@ impl OutstationSession {
 
     pub(crate) fn reset(&mut self) {
         self.state.reset();
+        self.connection = None;
+    }
+
+    /// set the TCP session that subsequent requests are received on
+    pub(crate) fn set_connection(&mut self, id: u64, addr: SocketAddr) {
+        self.connection = Some((id, addr));
     }
 
     async fn write_unsolicited(
@@ -435,6 +463,9 @@ impl OutstationSession {
         self.handle_one_request_from_idle(io, reader, writer, database)
             .await?;
 
+        // produce events for points that must be reported periodically or whose chatter filter has cleared
+        let report_deadline = database.check_report_deadlines();
+
         // check to see if we should perform unsolicited
         let deadline = self.check_unsolicited(io, reader, writer, database).await?;
 
@@ -445,15 +476,11 @@ impl OutstationSession {
         // check to see if we should perform a link status check
         self.check_link_status(io, writer).await?;
 
-        let deadline = match deadline {
-            Some(deadline) => match self.next_link_status {
-                Some(link_deadline) => {
-                    Some(crate::tokio::time::Instant::min(deadline, link_deadline))
-                }
-                None => Some(deadline),
-            },
-            None => self.next_link_status,
-        };
+        let deadline = [deadline, self.next_link_status, report_deadline]
+            .iter()
+            .flatten()
+            .min()
+            .copied();
 
         // wait for an event
         crate::tokio::select! {
@@ -508,6 +535,11 @@ impl OutstationSession {
                     }
                 }
 
+                // hold events until a class reaches its hold time or hold count
+                if let Some(deadline) = self.check_unsolicited_hold(database) {
+                    return Ok(Some(deadline));
+                }
+
                 // perform regular unsolicited
                 match self
                     .maybe_perform_unsolicited(io, reader, writer, database)
@@ -532,6 +564,47 @@ impl OutstationSession {
         }
     }
 
+    /// returns the time at which to check again if all unreported events are being held
+    fn check_unsolicited_hold(
+        &mut self,
+        database: &DatabaseHandle,
+    ) -> Option<crate::tokio::time::Instant> {
+        let now = crate::tokio::time::Instant::now();
+        let mut ready = false;
+        let mut deadline: Option<crate::tokio::time::Instant> = None;
+
+        let classes = [EventClass::Class1, EventClass::Class2, EventClass::Class3];
+        for (class, held_since) in classes.iter().zip(self.state.held_since.iter_mut()) {
+            let count = if self.state.enabled_unsolicited_classes.matches(*class) {
+                database.unwritten_count(*class)
+            } else {
+                0
+            };
+
+            if count == 0 {
+                *held_since = None;
+                continue;
+            }
+
+            let hold = self.config.unsolicited_hold.get(*class);
+            let expiration = *held_since.get_or_insert(now) + hold.hold_time;
+            if count >= hold.hold_count.max(1) || now >= expiration {
+                ready = true;
+            } else {
+                deadline = Some(match deadline {
+                    Some(x) => crate::tokio::time::Instant::min(x, expiration),
+                    None => expiration,
+                });
+            }
+        }
+
+        if ready {
+            None
+        } else {
+            deadline
+        }
+    }
+
     async fn check_link_status(
         &mut self,
         io: &mut PhysLayer,
@@ -730,31 +803,22 @@ impl OutstationSession {
 
         match self.classify(info, request) {
             FragmentType::UnsolicitedConfirm(seq) => {
-                if seq == uns_ecsn {
-                    self.state.last_broadcast_type = None;
-                    self.info.unsolicited_confirmed(seq);
+                if self.handle_unsolicited_confirm(uns_ecsn, seq) {
                     Ok(UnsolicitedWaitResult::Complete(
                         UnsolicitedResult::Confirmed,
                     ))
                 } else {
-                    tracing::warn!(
-                        "ignoring unsolicited confirm with wrong sequence number ({})",
-                        seq.value()
-                    );
                     Ok(UnsolicitedWaitResult::ReadNext)
                 }
             }
             FragmentType::SolicitedConfirm(_) => {
-                if let Some(BroadcastConfirmMode::Mandatory) = self.state.last_broadcast_type {
-                    self.state.last_broadcast_type = None
-                } else {
-                    tracing::warn!("ignoring solicited confirm");
-                }
+                self.handle_solicited_confirm_during_unsolicited();
                 Ok(UnsolicitedWaitResult::ReadNext)
             }
             FragmentType::Broadcast(mode) => {
                 self.state.deferred_read.clear();
-                self.process_broadcast(database, mode, request);
+                self.process_broadcast(database, info, mode, request);
+                self.audit.flush();
                 Ok(UnsolicitedWaitResult::ReadNext)
             }
             FragmentType::MalformedRequest(_, err) => {
@@ -768,27 +832,35 @@ impl OutstationSession {
             }
             FragmentType::NewNonRead(hash, objects) => {
                 self.state.deferred_read.clear();
-                let mut response = self.handle_non_read(
-                    database,
-                    request.header.function,
-                    request.header.control.seq,
-                    info.id,
-                    objects,
-                );
-                if let Some(response) = &mut response {
+                let context = self.request_context(info, request.header);
+                let response = self.handle_non_read(database, &context, info.id, objects);
+                let mut result = LastValidRequest::new(context.seq, hash, response, None);
+                drop(guard);
+
+                let mut confirmed = false;
+                if !self.pending_controls.is_empty() {
+                    confirmed = self
+                        .wait_for_pending_controls(io, reader, Some(&mut result), Some(uns_ecsn))
+                        .await?;
+                }
+                self.audit.flush();
+
+                if let Some(response) = &mut result.response {
                     *response = self
                         .write_solicited(io, writer, *response, database)
                         .await?;
                 }
-                self.state.last_valid_request = Some(LastValidRequest::new(
-                    request.header.control.seq,
-                    hash,
-                    response,
-                    None,
-                ));
+                self.state.last_valid_request = Some(result);
+
+                // the unsolicited response was confirmed while the controls were pending
+                if confirmed {
+                    return Ok(UnsolicitedWaitResult::Complete(
+                        UnsolicitedResult::Confirmed,
+                    ));
+                }
 
                 // Cancel unsolicited series if it's a DISABLE_UNSOLICITED
-                if request.header.function == FunctionCode::DisableUnsolicited {
+                if context.function == FunctionCode::DisableUnsolicited {
                     return Ok(UnsolicitedWaitResult::Complete(
                         UnsolicitedResult::ReturnToIdle,
                     ));
@@ -883,6 +955,16 @@ impl OutstationSession {
                 tracing::info!("decode level changed to: {:?}", level);
                 self.config.decode_level = level;
             }
+            ConfigurationChange::SetAuditSink(sink) => {
+                tracing::info!("command audit sink installed");
+                self.audit.set_sink(sink);
+            }
+            ConfigurationChange::SetCaptureSink(sink) => {
+                self.capture.set(sink);
+            }
+            ConfigurationChange::SetDecodeEventSink(sink) => {
+                self.capture.set_events(sink);
+            }
         }
     }
 
@@ -931,7 +1013,17 @@ impl OutstationSession {
         match guard.get() {
             Some(TransportRequest::Request(info, request)) => {
                 self.on_link_activity();
-                if let Some(mut result) = self.process_request_from_idle(info, request, database) {
+                let mut result = self.process_request_from_idle(info, request, database);
+                // the request has been fully processed and borrows nothing from the reader
+                drop(guard);
+
+                if !self.pending_controls.is_empty() {
+                    self.wait_for_pending_controls(io, reader, result.as_mut(), None)
+                        .await?;
+                }
+                self.audit.flush();
+
+                if let Some(mut result) = result {
                     // optional response
                     if let Some(response) = &mut result.response {
                         *response = self
@@ -949,7 +1041,6 @@ impl OutstationSession {
 
                     // maybe start a response series
                     if let Some(series) = result.series {
-                        drop(guard);
                         // enter the solicited confirm wait state
                         self.sol_confirm_wait(io, reader, writer, database, series)
                             .instrument(tracing::info_span!(
@@ -973,6 +1064,166 @@ impl OutstationSession {
         Ok(())
     }
 
+    /// wait for controls that complete asynchronously, then write their final status into the response
+    ///
+    /// Reading continues while waiting: link-layer traffic is processed, confirms are handled as
+    /// they arrive, repeats of the request are ignored and malformed fragments are discarded. The
+    /// first new request is retained so that it's processed after the response is sent, which
+    /// pauses reading until the wait ends.
+    ///
+    /// Returns true if the unsolicited response with sequence `uns_ecsn` was confirmed.
+    async fn wait_for_pending_controls(
+        &mut self,
+        io: &mut PhysLayer,
+        reader: &mut TransportReader,
+        request: Option<&mut LastValidRequest>,
+        uns_ecsn: Option<Sequence>,
+    ) -> Result<bool, RunError> {
+        enum Event {
+            Complete,
+            Timeout,
+            Read,
+        }
+
+        let mut pending = std::mem::replace(&mut self.pending_controls, PendingControls::new());
+        let deadline = crate::tokio::time::Instant::now() + self.config.operate_timeout;
+        let mut reading = true;
+        let mut confirmed = false;
+
+        loop {
+            let decode_level = self.config.decode_level;
+            let event = crate::tokio::select! {
+                _ = pending.wait() => Event::Complete,
+                res = self.sleep_until(Some(deadline)) => {
+                    res?;
+                    Event::Timeout
+                }
+                res = reader.read(io, decode_level), if reading => {
+                    res?;
+                    Event::Read
+                }
+            };
+
+            match event {
+                Event::Complete => break,
+                Event::Timeout => {
+                    tracing::warn!("timeout waiting for pending controls to complete");
+                    break;
+                }
+                Event::Read => {
+                    let mut guard = reader.pop_request();
+                    let (info, fragment) = match guard.get() {
+                        None => continue,
+                        Some(TransportRequest::Request(info, fragment)) => {
+                            self.on_link_activity();
+                            (info, fragment)
+                        }
+                        Some(TransportRequest::LinkLayerMessage(_)) => {
+                            self.on_link_activity();
+                            continue;
+                        }
+                        Some(TransportRequest::Error(err)) => {
+                            self.on_link_activity();
+                            // the pending response occupies the transmit buffer, so no error
+                            // response can be written until it has been sent
+                            tracing::warn!(
+                                "discarding fragment received while controls are pending: {:?}",
+                                err
+                            );
+                            continue;
+                        }
+                    };
+
+                    let header = fragment.header;
+                    if header.function == FunctionCode::Confirm {
+                        if self.handle_confirm_with_pending_controls(header.control, uns_ecsn) {
+                            confirmed = true;
+                        }
+                        continue;
+                    }
+
+                    let is_repeat = match &request {
+                        Some(request) => {
+                            info.broadcast.is_none()
+                                && request.seq == header.control.seq
+                                && request.request_hash == xxh64(fragment.raw_fragment, 0)
+                        }
+                        None => false,
+                    };
+
+                    if is_repeat {
+                        tracing::info!("ignoring repeat of request with pending controls");
+                    } else {
+                        // process the new request once the pending controls complete
+                        guard.retain();
+                        reading = false;
+                    }
+                }
+            }
+        }
+
+        pending.update_audit(&mut self.audit);
+
+        if let Some(response) = request.and_then(|x| x.response.as_mut()) {
+            let mut cursor = self.sol_tx_buffer.write_cursor();
+            if let Ok(CommandStatus::NotSupported) = pending.write_statuses(&mut cursor) {
+                response.header.iin |= Iin2::PARAMETER_ERROR;
+            }
+        }
+
+        Ok(confirmed)
+    }
+
+    /// returns true if the confirm completes the unsolicited response with sequence `uns_ecsn`
+    fn handle_confirm_with_pending_controls(
+        &mut self,
+        control: ControlField,
+        uns_ecsn: Option<Sequence>,
+    ) -> bool {
+        match uns_ecsn {
+            Some(ecsn) if control.uns => self.handle_unsolicited_confirm(ecsn, control.seq),
+            Some(_) => {
+                self.handle_solicited_confirm_during_unsolicited();
+                false
+            }
+            None => {
+                tracing::warn!(
+                    "ignoring {} CONFIRM while controls are pending with seq: {}",
+                    if control.uns {
+                        "unsolicited"
+                    } else {
+                        "solicited"
+                    },
+                    control.seq.value()
+                );
+                false
+            }
+        }
+    }
+
+    /// returns true if the confirm completes the unsolicited response with sequence `uns_ecsn`
+    fn handle_unsolicited_confirm(&mut self, uns_ecsn: Sequence, seq: Sequence) -> bool {
+        if seq == uns_ecsn {
+            self.state.last_broadcast_type = None;
+            self.info.unsolicited_confirmed(seq);
+            true
+        } else {
+            tracing::warn!(
+                "ignoring unsolicited confirm with wrong sequence number ({})",
+                seq.value()
+            );
+            false
+        }
+    }
+
+    fn handle_solicited_confirm_during_unsolicited(&mut self) {
+        if let Some(BroadcastConfirmMode::Mandatory) = self.state.last_broadcast_type {
+            self.state.last_broadcast_type = None
+        } else {
+            tracing::warn!("ignoring solicited confirm");
+        }
+    }
+
     fn process_request_from_idle(
         &mut self,
         info: FragmentInfo,
@@ -1003,8 +1254,8 @@ impl OutstationSession {
                 Some(LastValidRequest::new(seq, hash, Some(response), series))
             }
             FragmentType::NewNonRead(hash, objects) => {
-                let response =
-                    self.handle_non_read(database, request.header.function, seq, info.id, objects);
+                let context = self.request_context(info, request.header);
+                let response = self.handle_non_read(database, &context, info.id, objects);
                 Some(LastValidRequest::new(seq, hash, response, None))
             }
             FragmentType::RepeatNonRead(hash, last_response) => {
@@ -1017,7 +1268,7 @@ impl OutstationSession {
                 Some(LastValidRequest::new(seq, hash, last_response, None))
             }
             FragmentType::Broadcast(mode) => {
-                self.process_broadcast(database, mode, request);
+                self.process_broadcast(database, info, mode, request);
                 None
             }
             FragmentType::SolicitedConfirm(seq) => {
@@ -1095,13 +1346,14 @@ impl OutstationSession {
     fn handle_non_read(
         &mut self,
         database: &mut DatabaseHandle,
-        function: FunctionCode,
-        seq: Sequence,
+        context: &RequestContext,
         frame_id: u32,
         object_headers: HeaderCollection,
     ) -> Option<Response> {
+        let seq = context.seq;
+        let function = context.function;
         let mut result = match function {
-            FunctionCode::Write => Some(self.handle_write(seq, object_headers)),
+            FunctionCode::Write => Some(self.handle_write(database, seq, object_headers, context)),
             // these function don't process objects
             FunctionCode::DelayMeasure => Some(self.handle_delay_measure(seq)),
             FunctionCode::RecordCurrentTime => Some(self.handle_record_current_time(seq)),
@@ -1115,16 +1367,16 @@ impl OutstationSession {
             }
             // controls
             FunctionCode::Select => {
-                Some(self.handle_select(database, seq, frame_id, object_headers))
+                Some(self.handle_select(database, seq, frame_id, object_headers, context))
             }
             FunctionCode::Operate => {
-                Some(self.handle_operate(database, seq, frame_id, object_headers))
+                Some(self.handle_operate(database, seq, frame_id, object_headers, context))
             }
             FunctionCode::DirectOperate => {
-                Some(self.handle_direct_operate(database, seq, object_headers))
+                Some(self.handle_direct_operate(database, seq, object_headers, context))
             }
             FunctionCode::DirectOperateNoResponse => {
-                self.handle_direct_operate_no_ack(database, object_headers);
+                self.handle_direct_operate_no_ack(database, object_headers, context);
                 None
             }
             FunctionCode::ImmediateFreeze => self.handle_freeze(
@@ -1133,6 +1385,7 @@ impl OutstationSession {
                 object_headers,
                 FreezeType::ImmediateFreeze,
                 true,
+                context,
             ),
             FunctionCode::ImmediateFreezeNoResponse => self.handle_freeze(
                 database,
@@ -1140,6 +1393,7 @@ impl OutstationSession {
                 object_headers,
                 FreezeType::ImmediateFreeze,
                 false,
+                context,
             ),
             FunctionCode::FreezeClear => self.handle_freeze(
                 database,
@@ -1147,6 +1401,7 @@ impl OutstationSession {
                 object_headers,
                 FreezeType::FreezeAndClear,
                 true,
+                context,
             ),
             FunctionCode::FreezeClearNoResponse => self.handle_freeze(
                 database,
@@ -1154,6 +1409,7 @@ impl OutstationSession {
                 object_headers,
                 FreezeType::FreezeAndClear,
                 false,
+                context,
             ),
             FunctionCode::EnableUnsolicited => {
                 Some(self.handle_enable_or_disable_unsolicited(true, seq, object_headers))
@@ -1191,7 +1447,13 @@ impl OutstationSession {
         }
     }
 
-    fn handle_write(&mut self, seq: Sequence, object_headers: HeaderCollection) -> Response {
+    fn handle_write(
+        &mut self,
+        database: &mut DatabaseHandle,
+        seq: Sequence,
+        object_headers: HeaderCollection,
+        context: &RequestContext,
+    ) -> Response {
         let iin2 = if let Some(header) = object_headers.get_only_header() {
             match header.details {
                 HeaderDetails::OneByteStartStop(_, _, RangedVariation::Group80Var1(seq)) => {
@@ -1220,18 +1482,14 @@ impl OutstationSession {
                 }
                 HeaderDetails::OneByteCount(_, CountVariation::Group50Var1(seq)) => {
                     if let Some(value) = seq.single() {
-                        match self.application.write_absolute_time(value.time) {
-                            WriteTimeResult::NotSupported => Iin2::NO_FUNC_CODE_SUPPORT,
-                            WriteTimeResult::InvalidValue => Iin2::PARAMETER_ERROR,
-                            WriteTimeResult::Ok => Iin2::default(),
-                        }
+                        self.write_time(database, value.time, context)
                     } else {
                         tracing::warn!("request didn't have a single g50v1");
                         Iin2::PARAMETER_ERROR
                     }
                 }
                 HeaderDetails::OneByteCount(_, CountVariation::Group50Var3(seq)) => {
-                    self.handle_g50v3(seq)
+                    self.handle_g50v3(database, seq, context)
                 }
                 _ => {
                     tracing::warn!(
@@ -1250,7 +1508,12 @@ impl OutstationSession {
         Response::empty_solicited(seq, Iin::default() | iin2)
     }
 
-    fn handle_g50v3(&mut self, seq: CountSequence<Group50Var3>) -> Iin2 {
+    fn handle_g50v3(
+        &mut self,
+        database: &mut DatabaseHandle,
+        seq: CountSequence<Group50Var3>,
+        context: &RequestContext,
+    ) -> Iin2 {
         let value = if let Some(value) = seq.single() {
             value
         } else {
@@ -1281,7 +1544,23 @@ impl OutstationSession {
         };
 
         self.state.last_recorded_time = None;
-        match self.application.write_absolute_time(timestamp) {
+        self.write_time(database, timestamp, context)
+    }
+
+    fn write_time(
+        &mut self,
+        database: &mut DatabaseHandle,
+        time: Timestamp,
+        context: &RequestContext,
+    ) -> Iin2 {
+        let result = self.application.write_absolute_time(time, context);
+
+        // if the outstation maintains the clock, the application only needs to reject invalid values
+        if result != WriteTimeResult::InvalidValue && database.write_time(time) {
+            return Iin2::default();
+        }
+
+        match result {
             WriteTimeResult::NotSupported => Iin2::NO_FUNC_CODE_SUPPORT,
             WriteTimeResult::InvalidValue => Iin2::PARAMETER_ERROR,
             WriteTimeResult::Ok => Iin2::default(),
@@ -1350,6 +1629,7 @@ impl OutstationSession {
         database: &mut DatabaseHandle,
         seq: Sequence,
         object_headers: HeaderCollection,
+        context: &RequestContext,
     ) -> Response {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1368,8 +1648,13 @@ impl OutstationSession {
             let mut cursor = self.sol_tx_buffer.write_cursor();
             let _ = cursor.skip(ResponseHeader::LENGTH);
 
-            let mut control_tx = ControlTransaction::new(self.control_handler.borrow_mut());
+            let mut control_tx = ControlTransaction::new(
+                self.control_handler.borrow_mut(),
+                *context,
+                &mut self.audit,
+            );
             let max_controls_per_request = self.config.max_controls_per_request;
+            let pending = &mut self.pending_controls;
 
             let result = database.transaction(|database| {
                 controls.operate_with_response(
@@ -1378,6 +1663,7 @@ impl OutstationSession {
                     &mut control_tx,
                     database,
                     max_controls_per_request,
+                    pending,
                 )
             });
 
@@ -1445,6 +1731,7 @@ impl OutstationSession {
         &mut self,
         database: &mut DatabaseHandle,
         object_headers: HeaderCollection,
+        context: &RequestContext,
     ) {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1458,11 +1745,13 @@ impl OutstationSession {
             Ok(controls) => controls,
         };
 
-        let mut control_tx = ControlTransaction::new(self.control_handler.borrow_mut());
+        let mut control_tx =
+            ControlTransaction::new(self.control_handler.borrow_mut(), *context, &mut self.audit);
         let max_controls_per_request = self.config.max_controls_per_request;
+        let pending = &mut self.pending_controls;
 
         let _ = database.transaction(|database| {
-            controls.operate_no_ack(&mut control_tx, database, max_controls_per_request)
+            controls.operate_no_ack(&mut control_tx, database, max_controls_per_request, pending)
         });
     }
 
@@ -1472,6 +1761,7 @@ impl OutstationSession {
         seq: Sequence,
         frame_id: u32,
         object_headers: HeaderCollection,
+        context: &RequestContext,
     ) -> Response {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1490,7 +1780,11 @@ impl OutstationSession {
             let mut cursor = self.sol_tx_buffer.write_cursor();
             let _ = cursor.skip(ResponseHeader::LENGTH);
 
-            let mut transaction = ControlTransaction::new(self.control_handler.borrow_mut());
+            let mut transaction = ControlTransaction::new(
+                self.control_handler.borrow_mut(),
+                *context,
+                &mut self.audit,
+            );
             let max_controls_per_request = self.config.max_controls_per_request;
 
             let result: Result<CommandStatus, WriteError> = database.transaction(|database| {
@@ -1536,6 +1830,7 @@ impl OutstationSession {
         seq: Sequence,
         frame_id: u32,
         object_headers: HeaderCollection,
+        context: &RequestContext,
     ) -> Response {
         let controls = match ControlCollection::from(object_headers) {
             Err(err) => {
@@ -1564,13 +1859,24 @@ impl OutstationSession {
                         object_headers.hash(),
                     ) {
                         Err(status) => {
-                            controls.respond_with_status(&mut cursor, status).unwrap();
+                            let mut control_tx = ControlTransaction::new(
+                                self.control_handler.borrow_mut(),
+                                *context,
+                                &mut self.audit,
+                            );
+                            controls
+                                .respond_with_status(&mut cursor, &mut control_tx, status)
+                                .unwrap();
                             status
                         }
                         Ok(()) => {
-                            let mut control_tx =
-                                ControlTransaction::new(self.control_handler.borrow_mut());
+                            let mut control_tx = ControlTransaction::new(
+                                self.control_handler.borrow_mut(),
+                                *context,
+                                &mut self.audit,
+                            );
                             let max_controls_per_request = self.config.max_controls_per_request;
+                            let pending = &mut self.pending_controls;
                             database
                                 .transaction(|db| {
                                     controls.operate_with_response(
@@ -1579,6 +1885,7 @@ impl OutstationSession {
                                         &mut control_tx,
                                         db,
                                         max_controls_per_request,
+                                        pending,
                                     )
                                 })
                                 .unwrap()
@@ -1587,7 +1894,14 @@ impl OutstationSession {
                 }
                 None => {
                     let status = CommandStatus::NoSelect;
-                    controls.respond_with_status(&mut cursor, status).unwrap();
+                    let mut control_tx = ControlTransaction::new(
+                        self.control_handler.borrow_mut(),
+                        *context,
+                        &mut self.audit,
+                    );
+                    controls
+                        .respond_with_status(&mut cursor, &mut control_tx, status)
+                        .unwrap();
                     status
                 }
             };
@@ -1617,20 +1931,25 @@ impl OutstationSession {
         object_headers: HeaderCollection,
         freeze_type: FreezeType,
         respond: bool,
+        context: &RequestContext,
     ) -> Option<Response> {
         let mut iin = Iin::default();
         database.transaction(|db| {
             for header in object_headers.iter() {
                 match header.details {
                     HeaderDetails::AllObjects(AllObjectsVariation::Group20Var0) => {
-                        iin |= self
-                            .application
-                            .freeze_counter(FreezeIndices::All, freeze_type, db);
+                        iin |= self.application.freeze_counter(
+                            FreezeIndices::All,
+                            freeze_type,
+                            context,
+                            db,
+                        );
                     }
                     HeaderDetails::OneByteStartStop(start, stop, RangedVariation::Group20Var0) => {
                         iin |= self.application.freeze_counter(
                             FreezeIndices::Range(start as u16, stop as u16),
                             freeze_type,
+                            context,
                             db,
                         );
                     }
@@ -1638,6 +1957,7 @@ impl OutstationSession {
                         iin |= self.application.freeze_counter(
                             FreezeIndices::Range(start, stop),
                             freeze_type,
+                            context,
                             db,
                         );
                     }
@@ -1692,17 +2012,24 @@ impl OutstationSession {
         // Application-controlled IIN bits
         iin |= self.application.get_application_iin();
 
+        // Outstation-maintained clock
+        if database.need_time() {
+            iin |= Iin1::NEED_TIME;
+        }
+
         iin
     }
 
     fn process_broadcast(
         &mut self,
         database: &mut DatabaseHandle,
+        info: FragmentInfo,
         mode: BroadcastConfirmMode,
         request: Request,
     ) {
         self.state.last_broadcast_type = Some(mode);
-        let action = self.process_broadcast_get_action(database, request);
+        let context = self.request_context(info, request.header);
+        let action = self.process_broadcast_get_action(database, request, &context);
         self.info
             .broadcast_received(request.header.function, action)
     }
@@ -1711,6 +2038,7 @@ impl OutstationSession {
         &mut self,
         database: &mut DatabaseHandle,
         request: Request,
+        context: &RequestContext,
     ) -> BroadcastAction {
         if self.config.broadcast.is_disabled() {
             tracing::warn!(
@@ -1735,19 +2063,33 @@ impl OutstationSession {
 
         match request.header.function {
             FunctionCode::Write => {
-                self.handle_write(seq, objects);
+                self.handle_write(database, seq, objects, context);
                 BroadcastAction::Processed
             }
             FunctionCode::DirectOperateNoResponse => {
-                self.handle_direct_operate_no_ack(database, objects);
+                self.handle_direct_operate_no_ack(database, objects, context);
                 BroadcastAction::Processed
             }
             FunctionCode::ImmediateFreezeNoResponse => {
-                self.handle_freeze(database, seq, objects, FreezeType::ImmediateFreeze, false);
+                self.handle_freeze(
+                    database,
+                    seq,
+                    objects,
+                    FreezeType::ImmediateFreeze,
+                    false,
+                    context,
+                );
                 BroadcastAction::Processed
             }
             FunctionCode::FreezeClearNoResponse => {
-                self.handle_freeze(database, seq, objects, FreezeType::FreezeAndClear, false);
+                self.handle_freeze(
+                    database,
+                    seq,
+                    objects,
+                    FreezeType::FreezeAndClear,
+                    false,
+                    context,
+                );
                 BroadcastAction::Processed
             }
             FunctionCode::RecordCurrentTime => {
@@ -1958,6 +2300,17 @@ impl OutstationSession {
         }
     }
 
+    fn request_context(&self, info: FragmentInfo, header: RequestHeader) -> RequestContext {
+        RequestContext {
+            master_address: info.source,
+            broadcast: info.broadcast,
+            session_id: self.connection.map(|(id, _)| id),
+            remote_addr: self.connection.map(|(_, addr)| addr),
+            seq: header.control.seq,
+            function: header.function,
+        }
+    }
+
     fn on_link_activity(&mut self) {
         self.next_link_status = self
             .config
@@ -0,0 +1,562 @@
+use std::sync::{Arc, Mutex};
+
+use crate::app::control::CommandStatus;
+use crate::app::{FunctionCode, RequestHeader, Sequence, Timestamp};
+use crate::outstation::database::Database;
+use crate::outstation::traits::*;
+use crate::outstation::OperateResult;
+
+/// user callbacks required to create an outstation task
+pub(crate) struct Callbacks {
+    pub(crate) application: Box<dyn OutstationApplication>,
+    pub(crate) information: Box<dyn OutstationInformation>,
+    pub(crate) control_handler: Box<dyn ControlHandler>,
+}
+
+impl Callbacks {
+    pub(crate) fn new(
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+    ) -> Self {
+        Self {
+            application,
+            information,
+            control_handler,
+        }
+    }
+
+    /// split the callbacks into `count` copies that share the underlying implementations
+    pub(crate) fn share(self, count: usize) -> Vec<Callbacks> {
+        if count <= 1 {
+            return vec![self];
+        }
+
+        let application = Shared::new(self.application);
+        let information = Shared::new(self.information);
+        let control_handler = Shared::new(self.control_handler);
+
+        (0..count)
+            .map(|_| Callbacks {
+                application: Box::new(application.clone()),
+                information: Box::new(information.clone()),
+                control_handler: Box::new(control_handler.clone()),
+            })
+            .collect()
+    }
+}
+
+/// callback implementation shared between multiple outstation tasks
+struct Shared<T: ?Sized> {
+    inner: Arc<Mutex<Box<T>>>,
+}
+
+impl<T: ?Sized> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> Shared<T> {
+    fn new(inner: Box<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+}
+
+impl OutstationApplication for Shared<dyn OutstationApplication> {
+    fn get_processing_delay_ms(&self) -> u16 {
+        self.inner.lock().unwrap().get_processing_delay_ms()
+    }
+
+    fn write_absolute_time(
+        &mut self,
+        time: Timestamp,
+        context: &RequestContext,
+    ) -> WriteTimeResult {
+        self.inner
+            .lock()
+            .unwrap()
+            .write_absolute_time(time, context)
+    }
+
+    fn get_application_iin(&self) -> ApplicationIin {
+        self.inner.lock().unwrap().get_application_iin()
+    }
+
+    fn cold_restart(&mut self) -> Option<RestartDelay> {
+        self.inner.lock().unwrap().cold_restart()
+    }
+
+    fn warm_restart(&mut self) -> Option<RestartDelay> {
+        self.inner.lock().unwrap().warm_restart()
+    }
+
+    fn freeze_counter(
+        &mut self,
+        indices: FreezeIndices,
+        freeze_type: FreezeType,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> FreezeResult {
+        self.inner
+            .lock()
+            .unwrap()
+            .freeze_counter(indices, freeze_type, context, database)
+    }
+}
+
+impl OutstationInformation for Shared<dyn OutstationInformation> {
+    fn process_request_from_idle(&mut self, header: RequestHeader) {
+        self.inner.lock().unwrap().process_request_from_idle(header)
+    }
+
+    fn broadcast_received(&mut self, function: FunctionCode, action: BroadcastAction) {
+        self.inner
+            .lock()
+            .unwrap()
+            .broadcast_received(function, action)
+    }
+
+    fn enter_solicited_confirm_wait(&mut self, ecsn: Sequence) {
+        self.inner
+            .lock()
+            .unwrap()
+            .enter_solicited_confirm_wait(ecsn)
+    }
+
+    fn solicited_confirm_timeout(&mut self, ecsn: Sequence) {
+        self.inner.lock().unwrap().solicited_confirm_timeout(ecsn)
+    }
+
+    fn solicited_confirm_received(&mut self, ecsn: Sequence) {
+        self.inner.lock().unwrap().solicited_confirm_received(ecsn)
+    }
+
+    fn solicited_confirm_wait_new_request(&mut self) {
+        self.inner
+            .lock()
+            .unwrap()
+            .solicited_confirm_wait_new_request()
+    }
+
+    fn wrong_solicited_confirm_seq(&mut self, ecsn: Sequence, seq: Sequence) {
+        self.inner
+            .lock()
+            .unwrap()
+            .wrong_solicited_confirm_seq(ecsn, seq)
+    }
+
+    fn unexpected_confirm(&mut self, unsolicited: bool, seq: Sequence) {
+        self.inner
+            .lock()
+            .unwrap()
+            .unexpected_confirm(unsolicited, seq)
+    }
+
+    fn enter_unsolicited_confirm_wait(&mut self, ecsn: Sequence) {
+        self.inner
+            .lock()
+            .unwrap()
+            .enter_unsolicited_confirm_wait(ecsn)
+    }
+
+    fn unsolicited_confirm_timeout(&mut self, ecsn: Sequence, retry: bool) {
+        self.inner
+            .lock()
+            .unwrap()
+            .unsolicited_confirm_timeout(ecsn, retry)
+    }
+
+    fn unsolicited_confirmed(&mut self, ecsn: Sequence) {
+        self.inner.lock().unwrap().unsolicited_confirmed(ecsn)
+    }
+
+    fn clear_restart_iin(&mut self) {
+        self.inner.lock().unwrap().clear_restart_iin()
+    }
+}
+
+impl<T> ControlSupport<T> for Shared<dyn ControlHandler>
+where
+    dyn ControlHandler: ControlSupport<T>,
+{
+    fn select(
+        &mut self,
+        control: T,
+        index: u16,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> CommandStatus {
+        self.inner
+            .lock()
+            .unwrap()
+            .select(control, index, context, database)
+    }
+
+    fn operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> CommandStatus {
+        self.inner
+            .lock()
+            .unwrap()
+            .operate(control, index, op_type, context, database)
+    }
+
+    fn begin_operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> OperateResult {
+        self.inner
+            .lock()
+            .unwrap()
+            .begin_operate(control, index, op_type, context, database)
+    }
+}
+
+impl ControlHandler for Shared<dyn ControlHandler> {
+    fn begin_fragment(&mut self, context: &RequestContext) {
+        self.inner.lock().unwrap().begin_fragment(context)
+    }
+
+    fn end_fragment(&mut self, context: &RequestContext) {
+        self.inner.lock().unwrap().end_fragment(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::control::*;
+    use crate::app::ControlField;
+    use crate::link::EndpointAddress;
+
+    // records the name of every method invoked on it
+    #[derive(Clone, Default)]
+    struct Recorder {
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Recorder {
+        fn record(&self, name: &'static str) {
+            self.calls.lock().unwrap().push(name);
+        }
+
+        fn take(&self) -> Vec<&'static str> {
+            std::mem::take(&mut self.calls.lock().unwrap())
+        }
+    }
+
+    impl OutstationApplication for Recorder {
+        fn get_processing_delay_ms(&self) -> u16 {
+            self.record("get_processing_delay_ms");
+            42
+        }
+
+        fn write_absolute_time(
+            &mut self,
+            _time: Timestamp,
+            _context: &RequestContext,
+        ) -> WriteTimeResult {
+            self.record("write_absolute_time");
+            WriteTimeResult::Ok
+        }
+
+        fn get_application_iin(&self) -> ApplicationIin {
+            self.record("get_application_iin");
+            ApplicationIin {
+                need_time: true,
+                ..ApplicationIin::default()
+            }
+        }
+
+        fn cold_restart(&mut self) -> Option<RestartDelay> {
+            self.record("cold_restart");
+            Some(RestartDelay::Seconds(1))
+        }
+
+        fn warm_restart(&mut self) -> Option<RestartDelay> {
+            self.record("warm_restart");
+            Some(RestartDelay::Milliseconds(2))
+        }
+
+        fn freeze_counter(
+            &mut self,
+            _indices: FreezeIndices,
+            _freeze_type: FreezeType,
+            _context: &RequestContext,
+            _database: &mut Database,
+        ) -> FreezeResult {
+            self.record("freeze_counter");
+            FreezeResult::Success
+        }
+    }
+
+    impl OutstationInformation for Recorder {
+        fn process_request_from_idle(&mut self, _header: RequestHeader) {
+            self.record("process_request_from_idle")
+        }
+
+        fn broadcast_received(&mut self, _function: FunctionCode, _action: BroadcastAction) {
+            self.record("broadcast_received")
+        }
+
+        fn enter_solicited_confirm_wait(&mut self, _ecsn: Sequence) {
+            self.record("enter_solicited_confirm_wait")
+        }
+
+        fn solicited_confirm_timeout(&mut self, _ecsn: Sequence) {
+            self.record("solicited_confirm_timeout")
+        }
+
+        fn solicited_confirm_received(&mut self, _ecsn: Sequence) {
+            self.record("solicited_confirm_received")
+        }
+
+        fn solicited_confirm_wait_new_request(&mut self) {
+            self.record("solicited_confirm_wait_new_request")
+        }
+
+        fn wrong_solicited_confirm_seq(&mut self, _ecsn: Sequence, _seq: Sequence) {
+            self.record("wrong_solicited_confirm_seq")
+        }
+
+        fn unexpected_confirm(&mut self, _unsolicited: bool, _seq: Sequence) {
+            self.record("unexpected_confirm")
+        }
+
+        fn enter_unsolicited_confirm_wait(&mut self, _ecsn: Sequence) {
+            self.record("enter_unsolicited_confirm_wait")
+        }
+
+        fn unsolicited_confirm_timeout(&mut self, _ecsn: Sequence, _retry: bool) {
+            self.record("unsolicited_confirm_timeout")
+        }
+
+        fn unsolicited_confirmed(&mut self, _ecsn: Sequence) {
+            self.record("unsolicited_confirmed")
+        }
+
+        fn clear_restart_iin(&mut self) {
+            self.record("clear_restart_iin")
+        }
+    }
+
+    impl<T> ControlSupport<T> for Recorder {
+        fn select(
+            &mut self,
+            _control: T,
+            _index: u16,
+            _context: &RequestContext,
+            _database: &mut Database,
+        ) -> CommandStatus {
+            self.record("select");
+            CommandStatus::Success
+        }
+
+        fn operate(
+            &mut self,
+            _control: T,
+            _index: u16,
+            _op_type: OperateType,
+            _context: &RequestContext,
+            _database: &mut Database,
+        ) -> CommandStatus {
+            self.record("operate");
+            CommandStatus::Success
+        }
+
+        fn begin_operate(
+            &mut self,
+            _control: T,
+            _index: u16,
+            _op_type: OperateType,
+            _context: &RequestContext,
+            _database: &mut Database,
+        ) -> OperateResult {
+            self.record("begin_operate");
+            OperateResult::Complete(CommandStatus::Success)
+        }
+    }
+
+    impl ControlHandler for Recorder {
+        fn begin_fragment(&mut self, _context: &RequestContext) {
+            self.record("begin_fragment")
+        }
+
+        fn end_fragment(&mut self, _context: &RequestContext) {
+            self.record("end_fragment")
+        }
+    }
+
+    fn context() -> RequestContext {
+        RequestContext {
+            master_address: EndpointAddress::from(1).unwrap(),
+            broadcast: None,
+            session_id: None,
+            remote_addr: None,
+            seq: Sequence::default(),
+            function: FunctionCode::DirectOperate,
+        }
+    }
+
+    fn shared_callbacks(recorder: &Recorder) -> Callbacks {
+        let mut callbacks = Callbacks::new(
+            Box::new(recorder.clone()),
+            Box::new(recorder.clone()),
+            Box::new(recorder.clone()),
+        )
+        .share(2);
+        // drop the other copy so that only the shared implementations remain
+        callbacks.pop().unwrap()
+    }
+
+    fn control<T: Copy>(handler: &mut dyn ControlHandler, value: T)
+    where
+        dyn ControlHandler: ControlSupport<T>,
+    {
+        let mut database = Database::new();
+        let context = context();
+        assert_eq!(
+            handler.select(value, 0, &context, &mut database),
+            CommandStatus::Success
+        );
+        assert_eq!(
+            handler.operate(
+                value,
+                0,
+                OperateType::DirectOperate,
+                &context,
+                &mut database
+            ),
+            CommandStatus::Success
+        );
+        assert!(matches!(
+            handler.begin_operate(
+                value,
+                0,
+                OperateType::DirectOperate,
+                &context,
+                &mut database
+            ),
+            OperateResult::Complete(CommandStatus::Success)
+        ));
+    }
+
+    #[test]
+    fn forwards_application_callbacks() {
+        let recorder = Recorder::default();
+        let mut application = shared_callbacks(&recorder).application;
+        let mut database = Database::new();
+
+        assert_eq!(application.get_processing_delay_ms(), 42);
+        assert_eq!(
+            application.write_absolute_time(Timestamp::new(0), &context()),
+            WriteTimeResult::Ok
+        );
+        assert!(application.get_application_iin().need_time);
+        assert_eq!(application.cold_restart(), Some(RestartDelay::Seconds(1)));
+        assert_eq!(
+            application.warm_restart(),
+            Some(RestartDelay::Milliseconds(2))
+        );
+        assert_eq!(
+            application.freeze_counter(
+                FreezeIndices::All,
+                FreezeType::ImmediateFreeze,
+                &context(),
+                &mut database
+            ),
+            FreezeResult::Success
+        );
+
+        assert_eq!(
+            recorder.take(),
+            [
+                "get_processing_delay_ms",
+                "write_absolute_time",
+                "get_application_iin",
+                "cold_restart",
+                "warm_restart",
+                "freeze_counter",
+            ]
+        );
+    }
+
+    #[test]
+    fn forwards_information_callbacks() {
+        let recorder = Recorder::default();
+        let mut information = shared_callbacks(&recorder).information;
+        let seq = Sequence::default();
+
+        information.process_request_from_idle(RequestHeader::new(
+            ControlField::request(seq),
+            FunctionCode::Read,
+        ));
+        information.broadcast_received(
+            FunctionCode::DirectOperateNoResponse,
+            BroadcastAction::Processed,
+        );
+        information.enter_solicited_confirm_wait(seq);
+        information.solicited_confirm_timeout(seq);
+        information.solicited_confirm_received(seq);
+        information.solicited_confirm_wait_new_request();
+        information.wrong_solicited_confirm_seq(seq, seq);
+        information.unexpected_confirm(true, seq);
+        information.enter_unsolicited_confirm_wait(seq);
+        information.unsolicited_confirm_timeout(seq, false);
+        information.unsolicited_confirmed(seq);
+        information.clear_restart_iin();
+
+        assert_eq!(
+            recorder.take(),
+            [
+                "process_request_from_idle",
+                "broadcast_received",
+                "enter_solicited_confirm_wait",
+                "solicited_confirm_timeout",
+                "solicited_confirm_received",
+                "solicited_confirm_wait_new_request",
+                "wrong_solicited_confirm_seq",
+                "unexpected_confirm",
+                "enter_unsolicited_confirm_wait",
+                "unsolicited_confirm_timeout",
+                "unsolicited_confirmed",
+                "clear_restart_iin",
+            ]
+        );
+    }
+
+    #[test]
+    fn forwards_control_callbacks() {
+        let recorder = Recorder::default();
+        let mut handler = shared_callbacks(&recorder).control_handler;
+
+        handler.begin_fragment(&context());
+        handler.end_fragment(&context());
+        assert_eq!(recorder.take(), ["begin_fragment", "end_fragment"]);
+
+        let handler = handler.as_mut();
+        control(handler, Group12Var1::from_op_type(OpType::LatchOn));
+        control(handler, Group41Var1::new(1));
+        control(handler, Group41Var2::new(2));
+        control(handler, Group41Var3::new(3.0));
+        control(handler, Group41Var4::new(4.0));
+        assert_eq!(
+            recorder.take(),
+            ["select", "operate", "begin_operate"].repeat(5)
+        );
+    }
+}
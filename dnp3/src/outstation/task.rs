@@ -1,6 +1,10 @@
+use std::net::SocketAddr;
+
 use crate::app::Shutdown;
+use crate::capture::SharedCaptureSink;
 use crate::decode::DecodeLevel;
 use crate::link::LinkErrorMode;
+use crate::outstation::audit::SharedAuditSink;
 use crate::outstation::config::*;
 use crate::outstation::database::{DatabaseHandle, EventBufferConfig};
 use crate::outstation::session::{OutstationSession, RunError};
@@ -9,8 +13,12 @@ use crate::outstation::OutstationHandle;
 use crate::transport::{TransportReader, TransportWriter};
 use crate::util::phys::PhysLayer;
 
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum ConfigurationChange {
     SetDecodeLevel(DecodeLevel),
+    SetAuditSink(SharedAuditSink),
+    SetCaptureSink(Option<SharedCaptureSink>),
+    SetDecodeEventSink(Option<SharedCaptureSink>),
 }
 
 impl From<ConfigurationChange> for OutstationMessage {
@@ -33,8 +41,12 @@ pub(crate) struct OutstationTask {
 
 impl OutstationTask {
     /// create an `OutstationTask` and return it along with a `DatabaseHandle` for updating it
+    ///
+    /// If `shared` is specified, the task uses the same static database as the provided outstation
+    /// but maintains its own event buffer
     pub(crate) fn create(
         link_error_mode: LinkErrorMode,
+        shared: Option<&OutstationHandle>,
         config: OutstationConfig,
         event_config: EventBufferConfig,
         application: Box<dyn OutstationApplication>,
@@ -42,16 +54,27 @@ impl OutstationTask {
         control_handler: Box<dyn ControlHandler>,
     ) -> (Self, OutstationHandle) {
         let (tx, rx) = crate::util::channel::request_channel();
-        let handle = DatabaseHandle::new(
-            config.max_read_request_headers,
-            config.class_zero,
-            event_config,
-        );
+        let handle = match shared {
+            Some(shared) => shared.database.attach(
+                config.max_read_request_headers,
+                config.class_zero,
+                event_config,
+            ),
+            None => DatabaseHandle::new(
+                config.max_read_request_headers,
+                config.class_zero,
+                event_config,
+            ),
+        };
+        if let Some(clock) = config.clock {
+            handle.enable_clock(clock);
+        }
         let (reader, writer) = crate::transport::create_outstation_transport_layer(
             link_error_mode,
             config.outstation_address,
             config.features.self_address,
             config.rx_buffer_size,
+            config.link_confirm,
         );
         let task = Self {
             session: OutstationSession::new(
@@ -71,6 +94,7 @@ impl OutstationTask {
             OutstationHandle {
                 database: handle,
                 sender: tx,
+                peers: Vec::new(),
             },
         )
     }
@@ -89,6 +113,11 @@ impl OutstationTask {
         }
     }
 
+    /// set the TCP session on which requests are received until the next `reset`
+    pub(crate) fn set_connection(&mut self, id: u64, addr: SocketAddr) {
+        self.session.set_connection(id, addr);
+    }
+
     pub(crate) fn reset(&mut self) {
         self.session.reset();
         self.reader.reset();
@@ -100,3 +129,10 @@ impl OutstationTask {
         &mut self.reader
     }
 }
+
+impl Drop for OutstationTask {
+    fn drop(&mut self) {
+        // events are no longer buffered for this session
+        self.database.detach();
+    }
+}
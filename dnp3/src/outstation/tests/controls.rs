@@ -1,11 +1,17 @@
+use std::sync::{Arc, Mutex};
+
 use tokio::time::Duration;
 
+use crate::app::control::{CommandStatus, CommandValue};
 use crate::app::variations::Group41Var2;
-use crate::app::FunctionCode;
+use crate::app::{FunctionCode, Sequence};
 use crate::link::header::BroadcastConfirmMode;
 use crate::outstation::config::Feature;
+use crate::outstation::session::RunError;
 use crate::outstation::tests::harness::*;
-use crate::outstation::traits::{BroadcastAction, OperateType};
+use crate::outstation::traits::{BroadcastAction, OperateType, RequestContext};
+use crate::outstation::{AuditOperation, CommandAuditRecord, CommandAuditSink};
+use crate::tokio::test::*;
 
 const G41V2_INDEX_7: Control = Control::G41V2(Group41Var2::new(513), 7);
 // select, seq == 0, g41v2 - count == 1, index == 7, value = 513, status == SUCCESS,
@@ -18,9 +24,17 @@ const OPERATE_SEQ1_G41V2_INDEX_8: &[u8] = &[0xC1, 0x04, 41, 2, 0x17, 0x01, 0x08,
 const OPERATE_SEQ2_G41V2: &[u8] = &[0xC2, 0x04, 41, 2, 0x17, 0x01, 0x07, 0x01, 0x02, 0x00];
 // direct operate, seq == 0, g41v2 - count == 1, index == 7, value = 513, status == SUCCESS
 const DIRECT_OPERATE_SEQ0_G41V2: &[u8] = &[0xC0, 0x05, 41, 2, 0x17, 0x01, 0x07, 0x01, 0x02, 0x00];
+// read, seq == 1, no object headers
+const EMPTY_READ_SEQ1: &[u8] = &[0xC1, 0x01];
+// response, seq == 1, restart IIN
+const EMPTY_RESPONSE_SEQ1: &[u8] = &[0xC1, 0x81, 0x80, 0x00];
 // direct operate no ack, seq == 0, g41v2 - count == 1, index == 7, value = 513, status == SUCCESS
 const DIRECT_OPERATE_NO_ACK_SEQ0_G41V2: &[u8] =
     &[0xC0, 0x06, 41, 2, 0x17, 0x01, 0x07, 0x01, 0x02, 0x00];
+// response, seq == 0, restart IIN + echo of request headers but with STATUS == 1 (TIMEOUT)
+const RESPONSE_SEQ0_G41V2_TIMEOUT: &[u8] = &[
+    0xC0, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x01,
+];
 // response, seq == 0, restart IIN + echo of request headers
 const RESPONSE_SEQ0_G41V2_SUCCESS: &[u8] = &[
     0xC0, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x00,
@@ -223,3 +237,231 @@ fn accept_two_identical_selects_before_operate() {
         Event::EndControls,
     ]);
 }
+
+#[test]
+fn defers_response_until_pending_operate_completes() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    // repeats of the request are ignored while the control is pending
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+    harness.check_no_events();
+
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn keeps_reading_confirms_while_operate_is_pending() {
+    // solicited confirm, seq == 0
+    const CONFIRM_SEQ0: &[u8] = &[0xC0, 0x00];
+
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    // the confirm is discarded and the following repeat is still recognized
+    harness.test_request_no_response(CONFIRM_SEQ0);
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+    harness.check_no_events();
+
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn pending_operate_times_out() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    crate::tokio::time::advance(get_default_config().operate_timeout);
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_TIMEOUT);
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn processes_new_request_after_pending_operate_completes() {
+    let mut harness = new_harness(get_default_config());
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    // the READ is held until the control completes
+    harness.send(EMPTY_READ_SEQ1);
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+
+    harness.expect_response(&[RESPONSE_SEQ0_G41V2_SUCCESS, EMPTY_RESPONSE_SEQ1].concat());
+    harness.check_all_io_consumed();
+}
+
+#[test]
+fn control_handler_receives_request_context() {
+    let config = get_default_config();
+    let mut harness = new_harness(config);
+
+    harness.test_request_response(DIRECT_OPERATE_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+
+    let context = harness.control_data.lock().unwrap().last_context;
+    assert_eq!(
+        context,
+        Some(RequestContext {
+            master_address: config.master_address,
+            broadcast: None,
+            session_id: None,
+            remote_addr: None,
+            seq: Sequence::new(0),
+            function: FunctionCode::DirectOperate,
+        })
+    );
+}
+
+struct AuditRecords(Arc<Mutex<Vec<CommandAuditRecord>>>);
+
+impl CommandAuditSink for AuditRecords {
+    fn record(&mut self, record: &CommandAuditRecord) {
+        self.0.lock().unwrap().push(*record);
+    }
+}
+
+fn install_audit_sink<T>(
+    harness: &mut OutstationTestHarness<T>,
+) -> Arc<Mutex<Vec<CommandAuditRecord>>>
+where
+    T: std::future::Future<Output = RunError>,
+{
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let mut future = spawn(
+        harness
+            .handle
+            .set_audit_sink(Box::new(AuditRecords(records.clone()))),
+    );
+    assert_ready!(future.poll()).unwrap();
+    drop(future);
+    harness.poll_pending();
+    records
+}
+
+#[test]
+fn audits_direct_operate() {
+    let mut harness = new_harness(get_default_config());
+    let records = install_audit_sink(&mut harness);
+
+    harness.test_request_response(DIRECT_OPERATE_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = records[0];
+    assert_eq!(record.context.function, FunctionCode::DirectOperate);
+    assert_eq!(
+        record.operation,
+        AuditOperation::Operate(OperateType::DirectOperate)
+    );
+    assert_eq!(record.index, 7);
+    assert_eq!(record.value, CommandValue::G41V2(Group41Var2::new(513)));
+    assert_eq!(record.status, CommandStatus::Success);
+}
+
+#[test]
+fn audits_rejected_operate() {
+    let mut harness = new_harness(get_default_config());
+    let records = install_audit_sink(&mut harness);
+
+    harness.test_request_response(SELECT_SEQ0_G41V2, RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.test_request_response(OPERATE_SEQ2_G41V2, RESPONSE_SEQ2_G41V2_NO_SELECT);
+
+    let records = records.lock().unwrap();
+    let summary: Vec<(AuditOperation, CommandStatus)> =
+        records.iter().map(|x| (x.operation, x.status)).collect();
+    assert_eq!(
+        summary,
+        vec![
+            (AuditOperation::Select, CommandStatus::Success),
+            (
+                AuditOperation::Operate(OperateType::SelectBeforeOperate),
+                CommandStatus::NoSelect
+            ),
+        ]
+    );
+    assert_eq!(records[1].context.seq, Sequence::new(2));
+}
+
+#[test]
+fn shared_control_handler_defers_response_until_pending_operate_completes() {
+    let mut harness = new_harness_with_shared_callbacks(get_default_config());
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(G41V2_INDEX_7, OperateType::DirectOperate),
+        Event::EndControls,
+    ]);
+
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+
+    harness.expect_response(RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.check_all_io_consumed();
+}
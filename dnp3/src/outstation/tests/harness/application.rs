@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 use crate::app::Timestamp;
 use crate::outstation::database::Database;
 use crate::outstation::tests::harness::{Event, EventHandle};
-use crate::outstation::traits::{OutstationApplication, RestartDelay};
+use crate::outstation::traits::{OutstationApplication, RequestContext, RestartDelay};
 use crate::outstation::{FreezeIndices, FreezeResult, FreezeType, WriteTimeResult};
 
 pub(crate) struct MockOutstationApplication {
@@ -14,6 +14,7 @@ pub(crate) struct MockOutstationApplication {
 pub(crate) struct ApplicationData {
     pub(crate) processing_delay: u16,
     pub(crate) restart_delay: Option<RestartDelay>,
+    pub(crate) last_context: Option<RequestContext>,
 }
 
 impl ApplicationData {
@@ -21,6 +22,7 @@ impl ApplicationData {
         Self {
             processing_delay: 0,
             restart_delay: None,
+            last_context: None,
         }
     }
 }
@@ -35,7 +37,12 @@ impl MockOutstationApplication {
 }
 
 impl OutstationApplication for MockOutstationApplication {
-    fn write_absolute_time(&mut self, time: Timestamp) -> WriteTimeResult {
+    fn write_absolute_time(
+        &mut self,
+        time: Timestamp,
+        context: &RequestContext,
+    ) -> WriteTimeResult {
+        self.data.lock().unwrap().last_context = Some(*context);
         self.events.push(Event::WriteAbsoluteTime(time));
         WriteTimeResult::Ok
     }
@@ -60,6 +67,7 @@ impl OutstationApplication for MockOutstationApplication {
         &mut self,
         indices: FreezeIndices,
         freeze_type: FreezeType,
+        _context: &RequestContext,
         _db: &mut Database,
     ) -> FreezeResult {
         self.events.push(Event::Freeze(indices, freeze_type));
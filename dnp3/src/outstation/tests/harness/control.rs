@@ -1,21 +1,49 @@
+use std::sync::{Arc, Mutex};
+
 use crate::app::control::CommandStatus;
 use crate::app::variations::{Group12Var1, Group41Var1, Group41Var2, Group41Var3, Group41Var4};
 use crate::outstation::database::Database;
 use crate::outstation::tests::harness::{Control, Event, EventHandle};
-use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType};
+use crate::outstation::traits::{ControlHandler, ControlSupport, OperateType, RequestContext};
+use crate::outstation::{OperateCompletion, OperateResult, PendingOperate};
 
 pub(crate) struct MockControlHandler {
     events: EventHandle,
+    data: Arc<Mutex<ControlData>>,
+}
+
+pub(crate) struct ControlData {
+    /// if true, g41v2 operations complete asynchronously
+    pub(crate) defer_operate: bool,
+    pub(crate) completions: Vec<OperateCompletion>,
+    pub(crate) last_context: Option<RequestContext>,
+}
+
+impl ControlData {
+    fn new() -> Self {
+        Self {
+            defer_operate: false,
+            completions: Vec::new(),
+            last_context: None,
+        }
+    }
 }
 
 impl MockControlHandler {
-    pub(crate) fn new(events: EventHandle) -> Box<dyn ControlHandler> {
-        Box::new(Self { events })
+    pub(crate) fn new(events: EventHandle) -> (Arc<Mutex<ControlData>>, Box<dyn ControlHandler>) {
+        let data = Arc::new(Mutex::new(ControlData::new()));
+        (data.clone(), Box::new(Self { events, data }))
     }
 }
 
 impl ControlSupport<Group12Var1> for MockControlHandler {
-    fn select(&mut self, control: Group12Var1, index: u16, _: &mut Database) -> CommandStatus {
+    fn select(
+        &mut self,
+        control: Group12Var1,
+        index: u16,
+        _: &RequestContext,
+        _: &mut Database,
+    ) -> CommandStatus {
         self.events
             .push(Event::Select(Control::G12V1(control, index)));
         CommandStatus::Success
@@ -26,6 +54,7 @@ impl ControlSupport<Group12Var1> for MockControlHandler {
         control: Group12Var1,
         index: u16,
         op_type: OperateType,
+        _: &RequestContext,
         _: &mut Database,
     ) -> CommandStatus {
         self.events
@@ -35,7 +64,13 @@ impl ControlSupport<Group12Var1> for MockControlHandler {
 }
 
 impl ControlSupport<Group41Var1> for MockControlHandler {
-    fn select(&mut self, control: Group41Var1, index: u16, _: &mut Database) -> CommandStatus {
+    fn select(
+        &mut self,
+        control: Group41Var1,
+        index: u16,
+        _: &RequestContext,
+        _: &mut Database,
+    ) -> CommandStatus {
         self.events
             .push(Event::Select(Control::G41V1(control, index)));
         CommandStatus::Success
@@ -46,6 +81,7 @@ impl ControlSupport<Group41Var1> for MockControlHandler {
         control: Group41Var1,
         index: u16,
         op_type: OperateType,
+        _: &RequestContext,
         _: &mut Database,
     ) -> CommandStatus {
         self.events
@@ -55,7 +91,13 @@ impl ControlSupport<Group41Var1> for MockControlHandler {
 }
 
 impl ControlSupport<Group41Var2> for MockControlHandler {
-    fn select(&mut self, control: Group41Var2, index: u16, _: &mut Database) -> CommandStatus {
+    fn select(
+        &mut self,
+        control: Group41Var2,
+        index: u16,
+        _: &RequestContext,
+        _: &mut Database,
+    ) -> CommandStatus {
         self.events
             .push(Event::Select(Control::G41V2(control, index)));
         CommandStatus::Success
@@ -66,16 +108,44 @@ impl ControlSupport<Group41Var2> for MockControlHandler {
         control: Group41Var2,
         index: u16,
         op_type: OperateType,
+        _: &RequestContext,
         _: &mut Database,
     ) -> CommandStatus {
         self.events
             .push(Event::Operate(Control::G41V2(control, index), op_type));
         CommandStatus::Success
     }
+
+    fn begin_operate(
+        &mut self,
+        control: Group41Var2,
+        index: u16,
+        op_type: OperateType,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> OperateResult {
+        if !self.data.lock().unwrap().defer_operate {
+            return OperateResult::Complete(
+                self.operate(control, index, op_type, context, database),
+            );
+        }
+
+        self.events
+            .push(Event::Operate(Control::G41V2(control, index), op_type));
+        let (pending, completion) = PendingOperate::channel();
+        self.data.lock().unwrap().completions.push(completion);
+        OperateResult::Pending(pending)
+    }
 }
 
 impl ControlSupport<Group41Var3> for MockControlHandler {
-    fn select(&mut self, control: Group41Var3, index: u16, _: &mut Database) -> CommandStatus {
+    fn select(
+        &mut self,
+        control: Group41Var3,
+        index: u16,
+        _: &RequestContext,
+        _: &mut Database,
+    ) -> CommandStatus {
         self.events
             .push(Event::Select(Control::G41V3(control, index)));
         CommandStatus::Success
@@ -86,6 +156,7 @@ impl ControlSupport<Group41Var3> for MockControlHandler {
         control: Group41Var3,
         index: u16,
         op_type: OperateType,
+        _: &RequestContext,
         _: &mut Database,
     ) -> CommandStatus {
         self.events
@@ -95,7 +166,13 @@ impl ControlSupport<Group41Var3> for MockControlHandler {
 }
 
 impl ControlSupport<Group41Var4> for MockControlHandler {
-    fn select(&mut self, control: Group41Var4, index: u16, _: &mut Database) -> CommandStatus {
+    fn select(
+        &mut self,
+        control: Group41Var4,
+        index: u16,
+        _: &RequestContext,
+        _: &mut Database,
+    ) -> CommandStatus {
         self.events
             .push(Event::Select(Control::G41V4(control, index)));
         CommandStatus::Success
@@ -106,6 +183,7 @@ impl ControlSupport<Group41Var4> for MockControlHandler {
         control: Group41Var4,
         index: u16,
         op_type: OperateType,
+        _: &RequestContext,
         _: &mut Database,
     ) -> CommandStatus {
         self.events
@@ -115,11 +193,12 @@ impl ControlSupport<Group41Var4> for MockControlHandler {
 }
 
 impl ControlHandler for MockControlHandler {
-    fn begin_fragment(&mut self) {
+    fn begin_fragment(&mut self, context: &RequestContext) {
+        self.data.lock().unwrap().last_context = Some(*context);
         self.events.push(Event::BeginControls);
     }
 
-    fn end_fragment(&mut self) {
+    fn end_fragment(&mut self, _: &RequestContext) {
         self.events.push(Event::EndControls);
     }
 }
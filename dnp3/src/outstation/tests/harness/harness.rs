@@ -6,10 +6,11 @@ use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::config::{Feature, OutstationConfig};
 use crate::outstation::database::EventBufferConfig;
 use crate::outstation::session::RunError;
+use crate::outstation::shared::Callbacks;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::tests::harness::{
-    ApplicationData, Event, EventHandle, MockControlHandler, MockOutstationApplication,
-    MockOutstationInformation,
+    ApplicationData, ControlData, Event, EventHandle, MockControlHandler,
+    MockOutstationApplication, MockOutstationInformation,
 };
 use crate::outstation::OutstationHandle;
 use crate::tokio::test::*;
@@ -41,6 +42,7 @@ where
     task: Spawn<T>,
     events: EventHandle,
     pub(crate) application_data: Arc<Mutex<ApplicationData>>,
+    pub(crate) control_data: Arc<Mutex<ControlData>>,
 }
 
 impl<T> OutstationTestHarness<T>
@@ -110,39 +112,66 @@ where
 pub(crate) fn new_harness(
     config: OutstationConfig,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, None, None)
+    new_harness_impl(None, config, None, None, false)
 }
 
 pub(crate) fn new_harness_with_custom_event_buffers(
     config: OutstationConfig,
     event_config: EventBufferConfig,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, Some(event_config), None)
+    new_harness_impl(None, config, Some(event_config), None, false)
 }
 
 pub(crate) fn new_harness_for_broadcast(
     config: OutstationConfig,
     broadcast: BroadcastConfirmMode,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
-    new_harness_impl(config, None, Some(broadcast))
+    new_harness_impl(None, config, None, Some(broadcast), false)
+}
+
+pub(crate) fn new_shared_harness(
+    shared: &OutstationHandle,
+    config: OutstationConfig,
+) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
+    new_harness_impl(Some(shared), config, None, None, false)
+}
+
+/// the callbacks are wrapped as they are when shared between the sessions of a server
+pub(crate) fn new_harness_with_shared_callbacks(
+    config: OutstationConfig,
+) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
+    new_harness_impl(None, config, None, None, true)
 }
 
 fn new_harness_impl(
+    shared: Option<&OutstationHandle>,
     config: OutstationConfig,
     event_config: Option<EventBufferConfig>,
     broadcast: Option<BroadcastConfirmMode>,
+    share_callbacks: bool,
 ) -> OutstationTestHarness<impl std::future::Future<Output = RunError>> {
     let events = EventHandle::new();
 
     let (data, application) = MockOutstationApplication::new(events.clone());
+    let (control_data, control_handler) = MockControlHandler::new(events.clone());
+
+    let mut callbacks = Callbacks::new(
+        application,
+        MockOutstationInformation::new(events.clone()),
+        control_handler,
+    );
+    if share_callbacks {
+        callbacks = callbacks.share(2).remove(0);
+    }
 
     let (task, handle) = OutstationTask::create(
         LinkErrorMode::Close,
+        shared,
         config,
         event_config.unwrap_or(EventBufferConfig::all_types(5)),
-        application,
-        MockOutstationInformation::new(events.clone()),
-        MockControlHandler::new(events.clone()),
+        callbacks.application,
+        callbacks.information,
+        callbacks.control_handler,
     );
 
     let mut task = Box::new(task);
@@ -157,7 +186,7 @@ fn new_harness_impl(
 
     let (io, io_handle) = io::mock();
 
-    let mut io = PhysLayer::Mock(io);
+    let mut io = PhysLayer::mock(io);
 
     OutstationTestHarness {
         handle,
@@ -165,5 +194,6 @@ fn new_harness_impl(
         task: spawn(async move { task.run(&mut io).await }),
         events,
         application_data: data,
+        control_data,
     }
 }
@@ -10,6 +10,8 @@ mod iin;
 mod read_states;
 /// clear restart IIN + cold/warm restart
 mod restart;
+/// multiple sessions sharing a database
+mod shared;
 /// time synchronization
 mod time;
 /// unsolicited responses
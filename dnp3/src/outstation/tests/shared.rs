@@ -0,0 +1,89 @@
+use crate::app::measurement::*;
+use crate::app::*;
+use crate::outstation::database::*;
+use crate::outstation::tests::harness::*;
+
+const READ_CLASS_123_SEQ0: &[u8] = &[
+    0xC0, 0x01, 0x3C, 0x02, 0x06, 0x3C, 0x03, 0x06, 0x3C, 0x04, 0x06,
+];
+const READ_CLASS_123_SEQ1: &[u8] = &[
+    0xC1, 0x01, 0x3C, 0x02, 0x06, 0x3C, 0x03, 0x06, 0x3C, 0x04, 0x06,
+];
+const CONFIRM_SEQ_0: &[u8] = &[0xC0, 0x00];
+const BINARY_EVENT_RESPONSE_SEQ0: &[u8] = &[
+    0xE0, 0x81, 0x80, 0x00, 0x02, 0x01, 0x28, 0x01, 0x00, 0x00, 0x00, 0x81,
+];
+const BINARY_EVENT_RESPONSE_SEQ1: &[u8] = &[
+    0xE1, 0x81, 0x80, 0x00, 0x02, 0x01, 0x28, 0x01, 0x00, 0x00, 0x00, 0x81,
+];
+const READ_CLASS_0_SEQ0: &[u8] = &[0xC0, 0x01, 0x3C, 0x01, 0x06];
+const READ_CLASS_0_SEQ1: &[u8] = &[0xC1, 0x01, 0x3C, 0x01, 0x06];
+const CLASS_0_RESPONSE_SEQ0_WITH_PENDING_EVENTS: &[u8] = &[
+    0xC0, 0x81, 0x82, 0x00, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+const CLASS_0_RESPONSE_SEQ1: &[u8] = &[
+    0xC1, 0x81, 0x80, 0x00, 0x01, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+fn create_binary_and_event(database: &mut Database) {
+    database.add(0, Some(EventClass::Class1), BinaryConfig::default());
+    database.update(
+        0,
+        &Binary::new(true, Flags::ONLINE, Time::Synchronized(Timestamp::new(0))),
+        UpdateOptions::default(),
+    );
+}
+
+#[test]
+fn sessions_share_static_values_but_buffer_events_independently() {
+    let mut first = new_harness(get_default_config());
+    let mut second = new_shared_harness(&first.handle, get_default_config());
+
+    first.handle.transaction(create_binary_and_event);
+
+    // the second session reads and confirms the event
+    second.test_request_response(READ_CLASS_123_SEQ0, BINARY_EVENT_RESPONSE_SEQ0);
+    second.check_events(&[Event::EnterSolicitedConfirmWait(0)]);
+    second.send(CONFIRM_SEQ_0);
+    second.check_events(&[Event::SolicitedConfirmReceived(0)]);
+    second.test_request_response(READ_CLASS_0_SEQ1, CLASS_0_RESPONSE_SEQ1);
+
+    // the first session reports the same static value and still has the event buffered
+    first.test_request_response(READ_CLASS_0_SEQ0, CLASS_0_RESPONSE_SEQ0_WITH_PENDING_EVENTS);
+    first.test_request_response(READ_CLASS_123_SEQ1, BINARY_EVENT_RESPONSE_SEQ1);
+    first.check_events(&[Event::EnterSolicitedConfirmWait(1)]);
+}
+
+#[test]
+fn event_counts_fail_after_the_task_of_the_session_is_dropped() {
+    let first = new_harness(get_default_config());
+    let second = new_shared_harness(&first.handle, get_default_config());
+    let handle = second.handle.clone();
+
+    assert!(handle.event_counts().is_ok());
+    drop(second);
+    assert!(handle.event_counts().is_err());
+    assert!(first.handle.event_counts().is_ok());
+}
+
+#[test]
+fn event_counts_are_summed_across_concurrent_sessions() {
+    let mut first = new_harness(get_default_config());
+    let mut second = new_shared_harness(&first.handle, get_default_config());
+    // this is how a TCP server links the handles of concurrent sessions
+    first.handle.add_peer(&second.handle);
+
+    first.handle.transaction(create_binary_and_event);
+    assert_eq!(first.handle.event_counts().unwrap().class_1, 2);
+
+    // the second session reads and confirms its copy of the event
+    second.test_request_response(READ_CLASS_123_SEQ0, BINARY_EVENT_RESPONSE_SEQ0);
+    second.check_events(&[Event::EnterSolicitedConfirmWait(0)]);
+    second.send(CONFIRM_SEQ_0);
+    second.check_events(&[Event::SolicitedConfirmReceived(0)]);
+    assert_eq!(first.handle.event_counts().unwrap().class_1, 1);
+    assert_eq!(second.handle.event_counts().unwrap().class_1, 0);
+
+    drop(second);
+    assert!(first.handle.event_counts().is_err());
+}
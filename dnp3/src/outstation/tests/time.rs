@@ -1,4 +1,8 @@
-use crate::app::Timestamp;
+use crate::app::measurement::*;
+use crate::app::{FunctionCode, Sequence, Timestamp};
+use crate::outstation::config::OutstationConfig;
+use crate::outstation::database::*;
+use crate::outstation::ClockConfig;
 
 use super::harness::*;
 
@@ -37,6 +41,24 @@ fn non_lan_procedure() {
     harness.check_events(&[Event::WriteAbsoluteTime(Timestamp::new(1614271096000))]);
 }
 
+#[test]
+fn time_write_receives_request_context() {
+    let config = get_default_config();
+    let mut harness = new_harness(config);
+
+    harness.test_request_response(WRITE_ABSOLUTE_TIME, EMPTY_RESPONSE_SEQ1);
+
+    let context = harness
+        .application_data
+        .lock()
+        .unwrap()
+        .last_context
+        .unwrap();
+    assert_eq!(context.master_address, config.master_address);
+    assert_eq!(context.seq, Sequence::new(1));
+    assert_eq!(context.function, FunctionCode::Write);
+}
+
 #[test]
 fn lan_procedure() {
     let mut harness = new_harness(get_default_config());
@@ -48,3 +70,70 @@ fn lan_procedure() {
 
     harness.check_events(&[Event::WriteAbsoluteTime(Timestamp::new(0 + 0xCAFE))]);
 }
+
+fn get_clock_config() -> OutstationConfig {
+    let mut config = get_default_config();
+    config.clock = Some(ClockConfig::new(Some(std::time::Duration::from_secs(60))));
+    config
+}
+
+#[test]
+fn clock_manages_need_time_and_stamps_updates() {
+    let mut harness = new_harness(get_clock_config());
+
+    harness.application_data.lock().unwrap().processing_delay = 0xCAFE;
+
+    // NEED_TIME is asserted until the first synchronization
+    harness.test_request_response(
+        super::data::DELAY_MEASURE,
+        &[0xC0, 0x81, 0x90, 0x00, 0x34, 0x02, 0x07, 0x01, 0xFE, 0xCA],
+    );
+    harness.test_request_response(WRITE_ABSOLUTE_TIME, EMPTY_RESPONSE_SEQ1);
+    harness.check_events(&[Event::WriteAbsoluteTime(Timestamp::new(1614271096000))]);
+
+    let status = harness.handle.clock_status().unwrap();
+    assert!(!status.need_time);
+    assert_eq!(
+        status.time,
+        Time::Synchronized(Timestamp::new(1614271096000))
+    );
+
+    // values without a time are stamped by the clock
+    crate::tokio::time::advance(std::time::Duration::from_secs(1));
+    let value = harness.handle.transaction(|db| {
+        db.add(0, Some(EventClass::Class1), BinaryConfig::default());
+        db.update(
+            0,
+            &Binary::new(true, Flags::ONLINE, Time::synchronized(0)),
+            UpdateOptions::default(),
+        );
+        db.add(1, None, BinaryConfig::default());
+        db.update(
+            1,
+            &Binary {
+                value: false,
+                flags: Flags::ONLINE,
+                time: None,
+            },
+            UpdateOptions::default(),
+        );
+        (
+            Get::<Binary>::get(db, 0).unwrap().time,
+            Get::<Binary>::get(db, 1).unwrap().time,
+        )
+    });
+    assert_eq!(
+        value,
+        (
+            Some(Time::synchronized(0)),
+            Some(Time::Synchronized(Timestamp::new(1614271097000)))
+        )
+    );
+
+    // NEED_TIME is asserted again once the interval elapses (class 1 events are also pending)
+    crate::tokio::time::advance(std::time::Duration::from_secs(60));
+    harness.test_request_response(
+        super::data::DELAY_MEASURE,
+        &[0xC0, 0x81, 0x92, 0x00, 0x34, 0x02, 0x07, 0x01, 0xFE, 0xCA],
+    );
+}
@@ -1,7 +1,10 @@
+use crate::app::control::CommandStatus;
+use crate::app::variations::Group41Var2;
 use crate::app::{measurement::*, Timestamp};
-use crate::outstation::config::OutstationConfig;
+use crate::outstation::config::{OutstationConfig, UnsolicitedHold};
 use crate::outstation::database::*;
 use crate::outstation::session::RunError;
+use crate::outstation::traits::OperateType;
 
 use super::harness::*;
 
@@ -234,6 +237,51 @@ fn handles_non_read_during_unsolicited_confirm_wait() {
     harness.check_all_io_consumed();
 }
 
+#[test]
+fn waits_for_pending_control_during_unsolicited_confirm_wait() {
+    const DIRECT_OPERATE_SEQ0_G41V2: &[u8] =
+        &[0xC0, 0x05, 41, 2, 0x17, 0x01, 0x07, 0x01, 0x02, 0x00];
+    const RESPONSE_SEQ0_G41V2_SUCCESS: &[u8] = &[
+        0xC0, 0x81, 0x80, 0x00, 41, 2, 0x17, 0x1, 0x07, 0x01, 0x02, 0x00,
+    ];
+
+    let mut harness = new_harness(get_default_unsolicited_config());
+    confirm_null_unsolicited(&mut harness);
+    enable_unsolicited(&mut harness);
+    harness.control_data.lock().unwrap().defer_operate = true;
+
+    generate_binary_event(&mut harness.handle.database);
+    harness.expect_response(UNSOL_G2V1_SEQ1);
+    harness.check_events(&[Event::EnterUnsolicitedConfirmWait(1)]);
+
+    // operate a control that completes asynchronously while still in unsolicited confirm wait
+    harness.test_request_no_response(DIRECT_OPERATE_SEQ0_G41V2);
+    harness.check_events(&[
+        Event::BeginControls,
+        Event::Operate(
+            Control::G41V2(Group41Var2::new(513), 7),
+            OperateType::DirectOperate,
+        ),
+        Event::EndControls,
+    ]);
+
+    // the confirm is processed while the control is still pending
+    harness.send(UNS_CONFIRM_SEQ_1);
+    harness.check_events(&[Event::UnsolicitedConfirmReceived(1)]);
+
+    let completion = harness
+        .control_data
+        .lock()
+        .unwrap()
+        .completions
+        .pop()
+        .unwrap();
+    completion.complete(CommandStatus::Success);
+    harness.expect_response(RESPONSE_SEQ0_G41V2_SUCCESS);
+    harness.check_no_events();
+    harness.check_all_io_consumed();
+}
+
 #[test]
 fn handles_invalid_request_during_unsolicited_confirm_wait() {
     let mut harness = new_harness(get_default_unsolicited_config());
@@ -339,3 +387,56 @@ fn buffer_overflow_issue() {
         ],
     );
 }
+
+fn config_with_class_1_hold(hold_time: std::time::Duration, hold_count: usize) -> OutstationConfig {
+    let mut config = get_default_unsolicited_config();
+    config.unsolicited_hold.class1 = UnsolicitedHold::new(hold_time, hold_count);
+    config
+}
+
+#[test]
+fn unsolicited_is_held_until_hold_time_elapses() {
+    let hold_time = std::time::Duration::from_secs(30);
+    let mut harness = new_harness(config_with_class_1_hold(hold_time, 50));
+    confirm_null_unsolicited(&mut harness);
+    enable_unsolicited(&mut harness);
+    generate_binary_event(&mut harness.handle.database);
+
+    harness.poll_pending();
+    harness.check_all_io_consumed();
+
+    crate::tokio::time::advance(hold_time - std::time::Duration::from_secs(1));
+    harness.poll_pending();
+    harness.check_all_io_consumed();
+
+    crate::tokio::time::advance(std::time::Duration::from_secs(1));
+    harness.expect_response(UNSOL_G2V1_SEQ1);
+    harness.check_events(&[Event::EnterUnsolicitedConfirmWait(1)]);
+}
+
+#[test]
+fn unsolicited_is_sent_when_hold_count_is_reached() {
+    let mut harness = new_harness(config_with_class_1_hold(
+        std::time::Duration::from_secs(30),
+        2,
+    ));
+    confirm_null_unsolicited(&mut harness);
+    enable_unsolicited(&mut harness);
+    generate_binary_event(&mut harness.handle.database);
+
+    harness.poll_pending();
+    harness.check_all_io_consumed();
+
+    harness.handle.database.transaction(|db| {
+        db.update(
+            0,
+            &Binary::new(false, Flags::ONLINE, Time::synchronized(0)),
+            UpdateOptions::default(),
+        )
+    });
+
+    harness.expect_response(&[
+        0xF1, 0x82, 0x80, 0x00, 0x02, 0x01, 0x28, 0x02, 0x00, 0x00, 0x00, 0x81, 0x00, 0x00, 0x01,
+    ]);
+    harness.check_events(&[Event::EnterUnsolicitedConfirmWait(1)]);
+}
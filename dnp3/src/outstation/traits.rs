@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+
 use crate::app::parse::count::CountSequence;
 use crate::app::parse::prefix::Prefix;
 use crate::app::parse::traits::{FixedSizeVariation, Index};
@@ -5,7 +7,9 @@ use crate::app::FunctionCode;
 use crate::app::RequestHeader;
 use crate::app::Sequence;
 use crate::app::{control::*, Timestamp};
+use crate::link::{BroadcastConfirmMode, EndpointAddress};
 use crate::outstation::database::Database;
+use crate::outstation::OperateResult;
 
 /// Application-controlled IIN bits
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -60,6 +64,28 @@ pub enum ConnectionState {
     Disconnected,
 }
 
+/// Information about the request that caused a callback to be invoked
+///
+/// This is provided to control, time write, and freeze callbacks so that applications
+/// can audit or authorize operations based on who issued them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RequestContext {
+    /// link-layer address of the master that sent the request
+    pub master_address: EndpointAddress,
+    /// broadcast mode if the request was sent to a broadcast address
+    pub broadcast: Option<BroadcastConfirmMode>,
+    /// id of the TCP session that received the request, if any
+    ///
+    /// This matches the id reported in [ConnectionEvent](crate::tcp::ConnectionEvent)
+    pub session_id: Option<u64>,
+    /// address of the remote TCP peer, if any
+    pub remote_addr: Option<SocketAddr>,
+    /// application-layer sequence number of the request
+    pub seq: Sequence,
+    /// function code of the request
+    pub function: FunctionCode,
+}
+
 /// dynamic information required by the outstation from the user application
 pub trait OutstationApplication: Sync + Send + 'static {
     /// The value returned by this method is used in conjunction with the `Delay Measurement`
@@ -78,8 +104,14 @@ pub trait OutstationApplication: Sync + Send + 'static {
 
     /// Handle a write of the absolute time.
     ///
-    /// This is used during time synchronization procedures.
-    fn write_absolute_time(&mut self, _time: Timestamp) -> WriteTimeResult {
+    /// This is used during time synchronization procedures. If the outstation clock is enabled
+    /// in `OutstationConfig`, the time is applied to the clock unless this method returns
+    /// `WriteTimeResult::InvalidValue`.
+    fn write_absolute_time(
+        &mut self,
+        _time: Timestamp,
+        _context: &RequestContext,
+    ) -> WriteTimeResult {
         WriteTimeResult::NotSupported
     }
 
@@ -119,6 +151,7 @@ pub trait OutstationApplication: Sync + Send + 'static {
         &mut self,
         _indices: FreezeIndices,
         _freeze_type: FreezeType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> FreezeResult {
         FreezeResult::NotSupported
@@ -192,13 +225,20 @@ pub trait ControlSupport<T> {
     ///
     /// * `control` value of the control
     /// * `index` index of the control
+    /// * `context` information about the request and who sent it
     /// * `database` reference to the database
     ///
     /// returns:
     ///
     /// `CommandStatus` enumeration returning either `CommandStatus::Success` if the operation is
     /// supported, or an error variant otherwise.
-    fn select(&mut self, control: T, index: u16, database: &mut Database) -> CommandStatus;
+    fn select(
+        &mut self,
+        control: T,
+        index: u16,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> CommandStatus;
 
     /// Operate a control point
     ///
@@ -208,6 +248,7 @@ pub trait ControlSupport<T> {
     /// * `op_type` enumeration describing how the master requested the control operation. Most implementations
     ///             should just ignore this argument as the behavior is the same regardless.
     /// * `index` index of the control
+    /// * `context` information about the request and who sent it
     /// * `database` reference to the database
     ///
     /// returns:
@@ -218,8 +259,29 @@ pub trait ControlSupport<T> {
         control: T,
         index: u16,
         op_type: OperateType,
+        context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus;
+
+    /// Begin operating a control point that may complete asynchronously
+    ///
+    /// This is called by the outstation instead of `operate`. The default implementation
+    /// calls `operate` and completes immediately. Override it to return `OperateResult::Pending`
+    /// for operations that take time to complete, e.g. waiting for breaker feedback. The
+    /// outstation keeps processing link traffic and confirms while the controls complete, and
+    /// defers its response until then.
+    ///
+    /// arguments are the same as `operate`
+    fn begin_operate(
+        &mut self,
+        control: T,
+        index: u16,
+        op_type: OperateType,
+        context: &RequestContext,
+        database: &mut Database,
+    ) -> OperateResult {
+        OperateResult::Complete(self.operate(control, index, op_type, context, database))
+    }
 }
 
 /// Indices used by freeze operations
@@ -264,9 +326,9 @@ pub trait ControlHandler:
     + 'static
 {
     /// called before any controls are processed
-    fn begin_fragment(&mut self) {}
+    fn begin_fragment(&mut self, _context: &RequestContext) {}
     /// called after all controls have been processed
-    fn end_fragment(&mut self) {}
+    fn end_fragment(&mut self, _context: &RequestContext) {}
 }
 
 /// Struct with a default implementation [OutstationApplication](crate::outstation::OutstationApplication)
@@ -323,6 +385,7 @@ impl ControlSupport<Group12Var1> for DefaultControlHandler {
         &mut self,
         _control: Group12Var1,
         _index: u16,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -333,6 +396,7 @@ impl ControlSupport<Group12Var1> for DefaultControlHandler {
         _control: Group12Var1,
         _index: u16,
         _op_type: OperateType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -344,6 +408,7 @@ impl ControlSupport<Group41Var1> for DefaultControlHandler {
         &mut self,
         _control: Group41Var1,
         _index: u16,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -354,6 +419,7 @@ impl ControlSupport<Group41Var1> for DefaultControlHandler {
         _control: Group41Var1,
         _index: u16,
         _op_type: OperateType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -365,6 +431,7 @@ impl ControlSupport<Group41Var2> for DefaultControlHandler {
         &mut self,
         _control: Group41Var2,
         _index: u16,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -375,6 +442,7 @@ impl ControlSupport<Group41Var2> for DefaultControlHandler {
         _control: Group41Var2,
         _index: u16,
         _op_type: OperateType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -386,6 +454,7 @@ impl ControlSupport<Group41Var3> for DefaultControlHandler {
         &mut self,
         _control: Group41Var3,
         _index: u16,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -396,6 +465,7 @@ impl ControlSupport<Group41Var3> for DefaultControlHandler {
         _control: Group41Var3,
         _index: u16,
         _op_type: OperateType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -407,6 +477,7 @@ impl ControlSupport<Group41Var4> for DefaultControlHandler {
         &mut self,
         _control: Group41Var4,
         _index: u16,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -417,6 +488,7 @@ impl ControlSupport<Group41Var4> for DefaultControlHandler {
         _control: Group41Var4,
         _index: u16,
         _op_type: OperateType,
+        _context: &RequestContext,
         _database: &mut Database,
     ) -> CommandStatus {
         self.status
@@ -436,6 +508,7 @@ where
         &mut self,
         seq: CountSequence<Prefix<I, T>>,
         op_type: OperateType,
+        context: &RequestContext,
         database: &mut Database,
         mut func: F,
     ) where
@@ -450,6 +523,7 @@ where
                         item.value,
                         item.index.widen_to_u16(),
                         op_type,
+                        context,
                         database,
                     )
                 } else {
@@ -463,6 +537,7 @@ where
     fn select<I, F>(
         &mut self,
         seq: CountSequence<Prefix<I, T>>,
+        context: &RequestContext,
         database: &mut Database,
         mut func: F,
     ) where
@@ -476,6 +551,7 @@ where
                         self,
                         item.value,
                         item.index.widen_to_u16(),
+                        context,
                         database,
                     )
                 } else {
@@ -0,0 +1,231 @@
+use std::future::Future;
+
+use tracing::Instrument;
+
+use crate::decode::PhysDecodeLevel;
+use crate::link::router::{Route, RoutedStream, Router, Transmission};
+use crate::link::{EndpointAddress, LinkErrorMode};
+use crate::outstation::database::EventBufferConfig;
+use crate::outstation::task::OutstationTask;
+use crate::outstation::{
+    ControlHandler, OutstationApplication, OutstationConfig, OutstationHandle,
+    OutstationInformation,
+};
+use crate::serial::SerialSettings;
+use crate::tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use crate::util::phys::PhysLayer;
+
+/// Error returned when an outstation is added to a [`SerialOutstationHost`] with a link address
+/// that is already in use by another outstation on the same port
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DuplicateAddressError {
+    /// address that is already in use
+    pub address: EndpointAddress,
+}
+
+impl std::fmt::Display for DuplicateAddressError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "outstation address ({}) is already in use on this port",
+            self.address
+        )
+    }
+}
+
+impl std::error::Error for DuplicateAddressError {}
+
+/// A builder for hosting several outstations with distinct link addresses on one serial port,
+/// e.g. a device that emulates multiple logical outstations on an RS-485 bus
+///
+/// Frames received on the port are routed to the outstation matching their destination address.
+/// Broadcasts are delivered to every outstation, and frames sent to the self address are delivered to
+/// every outstation that enables it. Frames transmitted by the outstations are written to the port one
+/// at a time, and a response that spans several frames is written completely before the frames of
+/// any other outstation.
+///
+/// The port is logged at the most verbose physical layer [`DecodeLevel`](crate::decode::DecodeLevel)
+/// of the outstations. The other layers are logged by each outstation at its own level.
+pub struct SerialOutstationHost {
+    outstations: Vec<HostedOutstation>,
+    decode_level: PhysDecodeLevel,
+    outgoing: UnboundedSender<Transmission>,
+    receiver: UnboundedReceiver<Transmission>,
+}
+
+struct HostedOutstation {
+    route: Route,
+    task: OutstationTask,
+    stream: RoutedStream,
+}
+
+impl SerialOutstationHost {
+    /// create a host without any outstations
+    pub fn new() -> Self {
+        let (outgoing, receiver) = unbounded_channel();
+        Self {
+            outstations: Vec::new(),
+            decode_level: PhysDecodeLevel::Nothing,
+            outgoing,
+            receiver,
+        }
+    }
+
+    /// add an outstation to the host
+    ///
+    /// The outstation starts running when the host is spawned.
+    pub fn add_outstation(
+        &mut self,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+    ) -> Result<OutstationHandle, DuplicateAddressError> {
+        self.add_outstation_impl(
+            None,
+            config,
+            event_config,
+            application,
+            information,
+            control_handler,
+        )
+    }
+
+    /// add an outstation to the host that shares the static database of another outstation
+    ///
+    /// The other outstation may be hosted on any port or transport. The new outstation maintains
+    /// its own event buffer, unsolicited response state, and confirmation tracking.
+    pub fn add_shared_outstation(
+        &mut self,
+        shared: &OutstationHandle,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+    ) -> Result<OutstationHandle, DuplicateAddressError> {
+        self.add_outstation_impl(
+            Some(shared),
+            config,
+            event_config,
+            application,
+            information,
+            control_handler,
+        )
+    }
+
+    fn add_outstation_impl(
+        &mut self,
+        shared: Option<&OutstationHandle>,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+    ) -> Result<OutstationHandle, DuplicateAddressError> {
+        let address = config.outstation_address;
+        if self
+            .outstations
+            .iter()
+            .any(|x| x.route.address() == address)
+        {
+            return Err(DuplicateAddressError { address });
+        }
+
+        let (task, handle) = OutstationTask::create(
+            // frames have already been validated when they were read from the port
+            LinkErrorMode::Close,
+            shared,
+            config,
+            event_config,
+            application,
+            information,
+            control_handler,
+        );
+
+        let (route, stream) = Route::create(
+            address,
+            config.features.self_address.is_enabled(),
+            self.outgoing.clone(),
+        );
+        self.outstations.push(HostedOutstation {
+            route,
+            task,
+            stream,
+        });
+        self.decode_level = self.decode_level.max(config.decode_level.physical);
+
+        Ok(handle)
+    }
+
+    /// Consume the builder and spawn the host and its outstations onto the `Tokio` runtime. The host
+    /// runs until all of the outstation handles are dropped or a serial port error occurs.
+    ///
+    /// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+    pub fn spawn(self, path: &str, settings: SerialSettings) -> std::io::Result<()> {
+        let future = self.create(path, settings)?;
+        crate::tokio::spawn(future);
+        Ok(())
+    }
+
+    /// Consume the builder, open the serial port, and return a future that runs the host and its
+    /// outstations. The future spawns each outstation onto the runtime when it is first polled.
+    pub fn create(
+        self,
+        path: &str,
+        settings: SerialSettings,
+    ) -> std::io::Result<impl Future<Output = ()> + 'static> {
+        let serial = crate::serial::open(path, settings)?;
+        let log_path = path.to_owned();
+
+        let Self {
+            outstations,
+            decode_level,
+            outgoing,
+            receiver,
+        } = self;
+
+        // only the outstations may keep the outgoing channel open
+        drop(outgoing);
+
+        let future = async move {
+            // other devices may share the line, so frames for unknown addresses are ignored
+            let mut router = Router::new(decode_level, false);
+            for outstation in outstations {
+                let address = outstation.route.address().raw_value();
+                let path = log_path.clone();
+                let mut task = outstation.task;
+                let mut io = PhysLayer::routed(outstation.stream);
+                crate::tokio::spawn(async move {
+                    let _ = task
+                        .run(&mut io)
+                        .instrument(tracing::info_span!(
+                            "DNP3-Outstation-Serial",
+                            "port" = ?path,
+                            "addr" = address
+                        ))
+                        .await;
+                });
+                router.add(outstation.route);
+            }
+
+            let mut io = PhysLayer::serial(serial);
+            if let Err(err) = router
+                .run(&mut io, receiver)
+                .instrument(tracing::info_span!("DNP3-Outstation-Host", "port" = ?log_path))
+                .await
+            {
+                tracing::warn!("serial port error: {}", err);
+            }
+        };
+
+        Ok(future)
+    }
+}
+
+impl Default for SerialOutstationHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
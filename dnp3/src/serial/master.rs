@@ -87,6 +87,7 @@ impl MasterTask {
             LinkErrorMode::Discard,
             config.master_address,
             config.rx_buffer_size,
+            config.link_confirm,
         );
         let task = Self {
             path: path.to_string(),
@@ -129,7 +130,7 @@ impl MasterTask {
                     self.session.wait_for_retry(self.retry_delay).await?;
                 }
                 Ok(serial) => {
-                    let mut io = PhysLayer::Serial(serial);
+                    let mut io = PhysLayer::serial(serial);
                     tracing::info!("serial port open");
                     self.listener.update(PortState::Open);
                     match self
@@ -13,6 +13,8 @@ pub struct SerialSettings {
     pub stop_bits: StopBits,
     /// parity setting
     pub parity: Parity,
+    /// half-duplex timing, e.g. for RS-485 and radio links
+    pub timing: SerialTiming,
 }
 
 impl SerialSettings {
@@ -37,21 +39,26 @@ impl Default for SerialSettings {
             flow_control: FlowControl::None,
             stop_bits: StopBits::One,
             parity: Parity::None,
+            timing: SerialTiming::default(),
         }
     }
 }
 
-pub(crate) fn open(path: &str, settings: SerialSettings) -> tokio_serial::Result<TTYPort> {
+pub(crate) fn open(path: &str, settings: SerialSettings) -> tokio_serial::Result<Port> {
     let builder = settings.apply(tokio_serial::new(path, settings.baud_rate));
-    TTYPort::open(&builder)
+    Ok(Port::new(TTYPort::open(&builder)?, settings))
 }
 
+pub use host::*;
 pub use master::*;
 pub use outstation::*;
+pub use timing::*;
 use tokio_serial::TTYPort;
 
+mod host;
 mod master;
 mod outstation;
+mod timing;
 
 /// State of the serial port
 #[derive(Copy, Clone, Debug, PartialEq)]
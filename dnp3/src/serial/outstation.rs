@@ -55,10 +55,93 @@ pub fn create_outstation_serial(
     application: Box<dyn OutstationApplication>,
     information: Box<dyn OutstationInformation>,
     control_handler: Box<dyn ControlHandler>,
+) -> std::io::Result<(impl Future<Output = ()> + 'static, OutstationHandle)> {
+    create_outstation_serial_impl(
+        None,
+        path,
+        settings,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    )
+}
+
+/// Spawn an outstation task that shares the static database of another outstation onto the `Tokio` runtime
+///
+/// The other outstation may use any transport, e.g. it may be an outstation of a `TcpServer`. The new
+/// outstation maintains its own event buffer, unsolicited response state, and confirmation tracking.
+/// See [spawn_outstation_serial] for details.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_shared_outstation_serial(
+    shared: &OutstationHandle,
+    path: &str,
+    settings: SerialSettings,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> std::io::Result<OutstationHandle> {
+    let (future, handle) = create_shared_outstation_serial(
+        shared,
+        path,
+        settings,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    )?;
+    crate::tokio::spawn(future);
+    Ok(handle)
+}
+
+/// Create an outstation future that shares the static database of another outstation, along with a
+/// controlling handle
+///
+/// See [spawn_shared_outstation_serial] and [create_outstation_serial] for details.
+#[allow(clippy::too_many_arguments)]
+pub fn create_shared_outstation_serial(
+    shared: &OutstationHandle,
+    path: &str,
+    settings: SerialSettings,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+) -> std::io::Result<(impl Future<Output = ()> + 'static, OutstationHandle)> {
+    create_outstation_serial_impl(
+        Some(shared),
+        path,
+        settings,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_outstation_serial_impl(
+    shared: Option<&OutstationHandle>,
+    path: &str,
+    settings: SerialSettings,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
 ) -> std::io::Result<(impl Future<Output = ()> + 'static, OutstationHandle)> {
     let serial = crate::serial::open(path, settings)?;
     let (mut task, handle) = OutstationTask::create(
         LinkErrorMode::Discard,
+        shared,
         config,
         event_config,
         application,
@@ -68,7 +151,7 @@ pub fn create_outstation_serial(
 
     let log_path = path.to_owned();
     let future = async move {
-        let mut io = PhysLayer::Serial(serial);
+        let mut io = PhysLayer::serial(serial);
         let _ = task
             .run(&mut io)
             .instrument(tracing::info_span!("DNP3-Master-Serial", "port" = ?log_path))
@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use tokio_serial::{DataBits, Parity, SerialPort, StopBits, TTYPort};
+
+use crate::serial::SerialSettings;
+use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::tokio::time::Instant;
+
+/// Modem control line used to key a half-duplex transmitter
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum KeyingLine {
+    /// Request To Send
+    Rts,
+    /// Data Terminal Ready
+    Dtr,
+}
+
+/// Asserts a control line while transmitting, e.g. to enable an RS-485 driver or key a radio
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TransmitKeying {
+    /// line asserted for the duration of each transmission
+    pub line: KeyingLine,
+    /// delay between asserting the line and transmitting the first character
+    pub pre_delay: Duration,
+    /// delay between transmitting the last character and releasing the line
+    ///
+    /// The time required to shift the characters out at the configured baud rate is always added to this delay
+    pub post_delay: Duration,
+}
+
+impl TransmitKeying {
+    /// Key a line without any pre or post transmit delays
+    pub fn new(line: KeyingLine) -> Self {
+        Self {
+            line,
+            pre_delay: Duration::from_secs(0),
+            post_delay: Duration::from_secs(0),
+        }
+    }
+}
+
+/// Timing controls for half-duplex links such as RS-485 and radios
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct SerialTiming {
+    /// maximum gap between two characters of the same frame
+    ///
+    /// Any partially received frame is discarded when a larger gap is observed.
+    pub inter_character_timeout: Option<Duration>,
+    /// minimum delay between receiving the last character and transmitting
+    pub turnaround_delay: Option<Duration>,
+    /// optional keying of a control line for each transmission
+    pub keying: Option<TransmitKeying>,
+    /// duration for which the line must be idle immediately before each transmission
+    ///
+    /// This avoids collisions between unsolicited responses and other traffic on a shared line.
+    pub listen_before_talk: Option<Duration>,
+}
+
+/// Tracks received characters to detect gaps that delimit frames
+#[derive(Copy, Clone, Debug, Default)]
+struct RxTimer {
+    last_rx: Option<Instant>,
+    read_started: Option<Instant>,
+}
+
+impl RxTimer {
+    fn on_read_started(&mut self, now: Instant) {
+        self.read_started = Some(now);
+    }
+
+    // returns true if the characters arrived after a gap longer than the timeout
+    fn on_received(&mut self, now: Instant, timeout: Option<Duration>) -> bool {
+        // data that was waiting while no read was in progress does not count towards the gap
+        let reference = match (self.last_rx, self.read_started) {
+            (Some(rx), Some(started)) => Some(std::cmp::max(rx, started)),
+            (Some(rx), None) => Some(rx),
+            (None, _) => None,
+        };
+        self.last_rx = Some(now);
+        match (reference, timeout) {
+            (Some(reference), Some(timeout)) => now.duration_since(reference) > timeout,
+            _ => false,
+        }
+    }
+}
+
+/// A serial port that applies the half-duplex timing in the `SerialSettings`
+pub(crate) struct Port {
+    port: TTYPort,
+    timing: SerialTiming,
+    character_time: Duration,
+    rx: RxTimer,
+    gap: bool,
+    // characters received while listening before talking
+    pending: Vec<u8>,
+}
+
+impl Port {
+    pub(crate) fn new(port: TTYPort, settings: SerialSettings) -> Self {
+        Self {
+            port,
+            timing: settings.timing,
+            character_time: character_time(&settings),
+            rx: RxTimer::default(),
+            gap: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// returns true once if a gap longer than the inter-character timeout preceded the last read
+    pub(crate) fn take_gap(&mut self) -> bool {
+        std::mem::replace(&mut self.gap, false)
+    }
+
+    pub(crate) async fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        if !self.pending.is_empty() {
+            let count = std::cmp::min(buffer.len(), self.pending.len());
+            buffer[..count].copy_from_slice(&self.pending[..count]);
+            self.pending.drain(..count);
+            return Ok(count);
+        }
+
+        self.rx.on_read_started(Instant::now());
+        let count = self.port.read(buffer).await?;
+        self.on_received(count);
+        Ok(count)
+    }
+
+    pub(crate) async fn write(&mut self, data: &[u8]) -> std::io::Result<()> {
+        if let Some(delay) = self.timing.turnaround_delay {
+            if let Some(last_rx) = self.rx.last_rx {
+                crate::tokio::time::sleep_until(last_rx + delay).await;
+            }
+        }
+
+        if let Some(idle) = self.timing.listen_before_talk {
+            self.listen(idle).await?;
+        }
+
+        match self.timing.keying {
+            None => self.transmit(data).await,
+            Some(keying) => {
+                self.set_line(keying.line, true)?;
+                crate::tokio::time::sleep(keying.pre_delay).await;
+                let result = self.transmit(data).await;
+                // wait for the characters to be shifted out before releasing the line
+                let drain = self.character_time * data.len() as u32;
+                crate::tokio::time::sleep(drain + keying.post_delay).await;
+                self.set_line(keying.line, false)?;
+                result
+            }
+        }
+    }
+
+    async fn transmit(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.port.write_all(data).await?;
+        self.port.flush().await
+    }
+
+    // wait until no characters have been received for the specified duration
+    async fn listen(&mut self, idle: Duration) -> std::io::Result<()> {
+        let mut buffer = [0; 64];
+        let mut deadline = Instant::now() + idle;
+        loop {
+            let result = crate::tokio::select! {
+                _ = crate::tokio::time::sleep_until(deadline) => None,
+                x = self.port.read(&mut buffer) => Some(x?),
+            };
+
+            match result {
+                None => return Ok(()),
+                Some(0) => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+                Some(count) => {
+                    tracing::debug!("line busy, deferring transmission");
+                    self.pending.extend_from_slice(&buffer[..count]);
+                    self.on_received(count);
+                    deadline = Instant::now() + idle;
+                }
+            }
+        }
+    }
+
+    fn on_received(&mut self, count: usize) {
+        if count > 0
+            && self
+                .rx
+                .on_received(Instant::now(), self.timing.inter_character_timeout)
+        {
+            self.gap = true;
+        }
+    }
+
+    fn set_line(&mut self, line: KeyingLine, level: bool) -> std::io::Result<()> {
+        let result = match line {
+            KeyingLine::Rts => self.port.write_request_to_send(level),
+            KeyingLine::Dtr => self.port.write_data_terminal_ready(level),
+        };
+        result.map_err(std::io::Error::from)
+    }
+}
+
+// time required to transmit a single character including start, parity and stop bits
+fn character_time(settings: &SerialSettings) -> Duration {
+    let data = match settings.data_bits {
+        DataBits::Five => 5,
+        DataBits::Six => 6,
+        DataBits::Seven => 7,
+        DataBits::Eight => 8,
+    };
+    let parity = match settings.parity {
+        Parity::None => 0,
+        _ => 1,
+    };
+    let stop = match settings.stop_bits {
+        StopBits::One => 1,
+        StopBits::Two => 2,
+    };
+    let bits: u64 = 1 + data + parity + stop;
+    Duration::from_micros(bits * 1_000_000 / std::cmp::max(settings.baud_rate as u64, 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn calculates_character_time() {
+        let settings = SerialSettings {
+            baud_rate: 9600,
+            ..SerialSettings::default()
+        };
+        // 10 bits per character
+        assert_eq!(character_time(&settings), Duration::from_micros(1041));
+
+        let settings = SerialSettings {
+            baud_rate: 1200,
+            parity: Parity::Even,
+            stop_bits: StopBits::Two,
+            ..SerialSettings::default()
+        };
+        // 12 bits per character
+        assert_eq!(character_time(&settings), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn detects_inter_character_gaps() {
+        let timeout = Some(Duration::from_millis(10));
+        let start = Instant::now();
+        let mut timer = RxTimer::default();
+
+        timer.on_read_started(start);
+        assert!(!timer.on_received(start + Duration::from_millis(50), timeout));
+        timer.on_read_started(start + Duration::from_millis(50));
+        assert!(!timer.on_received(start + Duration::from_millis(55), timeout));
+        timer.on_read_started(start + Duration::from_millis(55));
+        assert!(timer.on_received(start + Duration::from_millis(70), timeout));
+    }
+
+    #[test]
+    fn ignores_data_buffered_between_reads() {
+        let timeout = Some(Duration::from_millis(10));
+        let start = Instant::now();
+        let mut timer = RxTimer::default();
+
+        timer.on_read_started(start);
+        assert!(!timer.on_received(start, timeout));
+        // the session was busy for a while before reading again
+        timer.on_read_started(start + Duration::from_millis(100));
+        assert!(!timer.on_received(start + Duration::from_millis(101), timeout));
+    }
+
+    #[test]
+    fn never_detects_gaps_without_timeout() {
+        let start = Instant::now();
+        let mut timer = RxTimer::default();
+
+        timer.on_read_started(start);
+        assert!(!timer.on_received(start, None));
+        assert!(!timer.on_received(start + Duration::from_secs(10), None));
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+use crate::tokio::time::{Instant, Sleep};
+
+/// Faults injected into the bytes written to either side of a loopback link
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoopbackFaults {
+    /// probability in the range `[0.0, 1.0]` that an individual write is silently discarded
+    pub drop_probability: f64,
+    /// probability in the range `[0.0, 1.0]` that a single bit of each written byte is flipped
+    pub corrupt_probability: f64,
+}
+
+impl LoopbackFaults {
+    /// No faults are injected
+    pub fn none() -> Self {
+        Self {
+            drop_probability: 0.0,
+            corrupt_probability: 0.0,
+        }
+    }
+}
+
+impl Default for LoopbackFaults {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// Configuration of an in-memory loopback link
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoopbackConfig {
+    /// maximum number of bytes buffered in each direction before writes wait for the peer to read
+    pub buffer_size: usize,
+    /// optional delay between writing bytes and the peer being able to read them
+    pub latency: Option<Duration>,
+    /// faults injected in both directions
+    pub faults: LoopbackFaults,
+    /// seed of the pseudo-random generator used to inject faults, making runs reproducible
+    pub seed: u64,
+}
+
+impl LoopbackConfig {
+    /// Create a configuration with a given buffer size, no latency and no faults
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            buffer_size,
+            latency: None,
+            faults: LoopbackFaults::none(),
+            seed: 0,
+        }
+    }
+}
+
+impl Default for LoopbackConfig {
+    fn default() -> Self {
+        Self::new(4096)
+    }
+}
+
+/// Create a pair of connected in-memory streams
+///
+/// Bytes written to one stream are read from the other after applying the configured latency and faults.
+/// Each side is typically wrapped in [`single_stream`](crate::stream::single_stream) and passed to
+/// [`spawn_master_custom`](crate::stream::spawn_master_custom) and
+/// [`spawn_outstation_custom`](crate::stream::spawn_outstation_custom) respectively, running the real link and
+/// transport layers without binding any sockets.
+pub fn loopback(config: LoopbackConfig) -> (LoopbackStream, LoopbackStream) {
+    let (first, second) = crate::tokio::io::duplex(config.buffer_size);
+    let first_to_second = Deliveries::default();
+    let second_to_first = Deliveries::default();
+    (
+        LoopbackStream::new(
+            first,
+            config,
+            config.seed,
+            first_to_second.clone(),
+            second_to_first.clone(),
+        ),
+        // use a different sequence in each direction
+        LoopbackStream::new(
+            second,
+            config,
+            !config.seed,
+            second_to_first,
+            first_to_second,
+        ),
+    )
+}
+
+/// One side of an in-memory loopback link created with [`loopback`]
+pub struct LoopbackStream {
+    inner: DuplexStream,
+    latency: Option<Duration>,
+    faults: LoopbackFaults,
+    rng: XorShift,
+    // corrupted copy of a write that the peer has not yet accepted
+    pending: Option<Vec<u8>>,
+    // delivery times of the bytes written by this side
+    sent: Deliveries,
+    // delivery times of the bytes written by the peer
+    received: Deliveries,
+    // waiting for the oldest received bytes to be delivered
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+type Deliveries = Arc<Mutex<VecDeque<Delivery>>>;
+
+// bytes written to the peer in one operation, timestamped when they were written
+struct Delivery {
+    time: Instant,
+    remaining: usize,
+}
+
+impl std::fmt::Debug for LoopbackStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("LoopbackStream")
+            .field("latency", &self.latency)
+            .field("faults", &self.faults)
+            .finish()
+    }
+}
+
+impl LoopbackStream {
+    fn new(
+        inner: DuplexStream,
+        config: LoopbackConfig,
+        seed: u64,
+        sent: Deliveries,
+        received: Deliveries,
+    ) -> Self {
+        Self {
+            inner,
+            latency: config.latency,
+            faults: config.faults,
+            rng: XorShift::new(seed),
+            pending: None,
+            sent,
+            received,
+            delay: None,
+        }
+    }
+
+    // returns None if the write is dropped
+    fn prepare(&mut self, buf: &[u8]) -> Option<Vec<u8>> {
+        if self.rng.chance(self.faults.drop_probability) {
+            tracing::debug!("loopback dropped {} bytes", buf.len());
+            return None;
+        }
+
+        let mut data = buf.to_vec();
+        if self.faults.corrupt_probability > 0.0 {
+            for byte in data.iter_mut() {
+                if self.rng.chance(self.faults.corrupt_probability) {
+                    *byte ^= 1 << (self.rng.next() % 8);
+                }
+            }
+        }
+
+        Some(data)
+    }
+}
+
+impl AsyncRead for LoopbackStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+
+        if this.latency.is_none() {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        }
+
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                if delay.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                this.delay = None;
+            }
+
+            // the peer holds the lock while writing, so every byte in the pipe has a delivery time
+            let mut received = this.received.lock().unwrap();
+            let delivery = match received.front_mut() {
+                Some(x) => x,
+                // nothing is in flight, but the peer may have shut down its side
+                None => return Pin::new(&mut this.inner).poll_read(cx, buf),
+            };
+
+            if delivery.time > Instant::now() {
+                this.delay = Some(Box::pin(crate::tokio::time::sleep_until(delivery.time)));
+                continue;
+            }
+
+            // only read the bytes that are due
+            let mut data = vec![0; delivery.remaining.min(buf.remaining())];
+            let mut limited = ReadBuf::new(&mut data);
+            let result = Pin::new(&mut this.inner).poll_read(cx, &mut limited);
+            let count = limited.filled().len();
+            buf.put_slice(limited.filled());
+
+            delivery.remaining -= count;
+            if delivery.remaining == 0 {
+                received.pop_front();
+            }
+
+            return result;
+        }
+    }
+}
+
+impl AsyncWrite for LoopbackStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+
+        if this.pending.is_none() {
+            match this.prepare(buf) {
+                Some(data) => this.pending = Some(data),
+                None => return Poll::Ready(Ok(buf.len())),
+            }
+        }
+
+        let data = match this.pending.as_ref() {
+            Some(x) => x,
+            None => return Poll::Ready(Ok(0)),
+        };
+
+        // timestamp the bytes as they are written so that the peer delays reading them
+        let result = match this.latency {
+            None => Pin::new(&mut this.inner).poll_write(cx, data),
+            Some(latency) => {
+                let mut sent = this.sent.lock().unwrap();
+                let result = Pin::new(&mut this.inner).poll_write(cx, data);
+                if let Poll::Ready(Ok(count)) = result {
+                    if count > 0 {
+                        sent.push_back(Delivery {
+                            time: Instant::now() + latency,
+                            remaining: count,
+                        });
+                    }
+                }
+                result
+            }
+        };
+
+        if result.is_ready() {
+            this.pending = None;
+        }
+
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+// small deterministic generator so that fault injection does not require an external dependency
+struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // the state of xorshift may never be zero
+        Self {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            return false;
+        }
+        if probability >= 1.0 {
+            return true;
+        }
+        // use the upper 53 bits to produce a value in [0.0, 1.0)
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use crate::tokio::test::*;
+
+    fn transfer(config: LoopbackConfig, data: &[u8]) -> Vec<u8> {
+        let (mut first, mut second) = loopback(config);
+        assert_ready!(spawn(first.write_all(data)).poll()).unwrap();
+        // closing the writer lets the reader observe the end of the stream
+        assert_ready!(spawn(first.shutdown()).poll()).unwrap();
+        let mut received = Vec::new();
+        assert_ready!(spawn(second.read_to_end(&mut received)).poll()).unwrap();
+        received
+    }
+
+    #[test]
+    fn passes_bytes_through_without_faults() {
+        assert_eq!(
+            transfer(LoopbackConfig::default(), &[0x05, 0x64, 0x01]),
+            vec![0x05, 0x64, 0x01]
+        );
+    }
+
+    #[test]
+    fn drops_writes() {
+        let mut config = LoopbackConfig::default();
+        config.faults.drop_probability = 1.0;
+        assert!(transfer(config, &[0x05, 0x64, 0x01]).is_empty());
+    }
+
+    #[test]
+    fn corrupts_bytes() {
+        let mut config = LoopbackConfig::default();
+        config.faults.corrupt_probability = 1.0;
+        let received = transfer(config, &[0x05, 0x64, 0x01]);
+        assert_eq!(received.len(), 3);
+        for (x, y) in received.iter().zip([0x05u8, 0x64, 0x01].iter()) {
+            // exactly one bit is flipped in every byte
+            assert_eq!((x ^ y).count_ones(), 1);
+        }
+    }
+
+    #[test]
+    fn delays_reads_until_latency_elapses() {
+        let config = LoopbackConfig {
+            latency: Some(Duration::from_millis(100)),
+            ..LoopbackConfig::default()
+        };
+        let (mut first, mut second) = loopback(config);
+
+        // writes complete immediately
+        assert_ready!(spawn(first.write_all(&[0x05, 0x64])).poll()).unwrap();
+
+        let mut buffer = [0; 8];
+        {
+            let mut read = spawn(second.read(&mut buffer));
+            assert_pending!(read.poll());
+            crate::tokio::time::advance(Duration::from_millis(99));
+            assert_pending!(read.poll());
+            crate::tokio::time::advance(Duration::from_millis(1));
+            assert_eq!(assert_ready!(read.poll()).unwrap(), 2);
+        }
+        assert_eq!(&buffer[..2], &[0x05, 0x64]);
+    }
+}
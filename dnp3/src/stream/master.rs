@@ -0,0 +1,165 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::app::{Listener, Shutdown};
+use crate::link::LinkErrorMode;
+use crate::master::session::{MasterSession, RunError, StateChange};
+use crate::master::*;
+use crate::stream::{StreamFactory, StreamState};
+use crate::transport::TransportReader;
+use crate::transport::TransportWriter;
+use crate::util::phys::PhysLayer;
+
+/// Spawn a master task onto the `Tokio` runtime that communicates over streams produced by a
+/// user-provided factory. The task runs until the returned handle, and any `AssociationHandle`
+/// created from it, are dropped.
+///
+/// The factory is invoked each time the channel is enabled and again after any stream error,
+/// waiting `retry_delay` between attempts.
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create(..)` when using `[tokio::main]`.
+pub fn spawn_master_custom<F: StreamFactory>(
+    config: MasterChannelConfig,
+    link_error_mode: LinkErrorMode,
+    factory: F,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<StreamState>>,
+) -> MasterChannel {
+    let (future, handle) =
+        create_master_custom(config, link_error_mode, factory, retry_delay, listener);
+    crate::tokio::spawn(future);
+    handle
+}
+
+/// Create a master future that communicates over streams produced by a user-provided factory,
+/// which can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// and any `AssociationHandle` created from it are dropped.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+pub fn create_master_custom<F: StreamFactory>(
+    config: MasterChannelConfig,
+    link_error_mode: LinkErrorMode,
+    factory: F,
+    retry_delay: Duration,
+    listener: Box<dyn Listener<StreamState>>,
+) -> (impl Future<Output = ()> + 'static, MasterChannel) {
+    let (mut task, handle) = MasterTask::new(
+        config,
+        link_error_mode,
+        Box::new(factory),
+        retry_delay,
+        listener,
+    );
+    let future = async move {
+        let _ = task
+            .run()
+            .instrument(tracing::info_span!("DNP3-Master-Custom"))
+            .await;
+    };
+    (future, handle)
+}
+
+struct MasterTask {
+    factory: Box<dyn StreamFactory>,
+    retry_delay: Duration,
+    session: MasterSession,
+    reader: TransportReader,
+    writer: TransportWriter,
+    listener: Box<dyn Listener<StreamState>>,
+}
+
+impl MasterTask {
+    fn new(
+        config: MasterChannelConfig,
+        link_error_mode: LinkErrorMode,
+        factory: Box<dyn StreamFactory>,
+        retry_delay: Duration,
+        listener: Box<dyn Listener<StreamState>>,
+    ) -> (Self, MasterChannel) {
+        let (tx, rx) = crate::util::channel::request_channel();
+        let session = MasterSession::new(
+            false,
+            config.decode_level,
+            config.response_timeout,
+            config.tx_buffer_size,
+            rx,
+        );
+        let (reader, writer) = crate::transport::create_master_transport_layer(
+            link_error_mode,
+            config.master_address,
+            config.rx_buffer_size,
+            config.link_confirm,
+        );
+        let task = Self {
+            factory,
+            retry_delay,
+            session,
+            reader,
+            writer,
+            listener,
+        };
+        (task, MasterChannel::new(tx))
+    }
+
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.session.shutdown().await;
+        self.listener.update(StreamState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            self.listener.update(StreamState::Disabled);
+            self.session.wait_for_enabled().await?;
+            if let Err(StateChange::Shutdown) = self.run_enabled().await {
+                return Err(Shutdown);
+            }
+        }
+    }
+
+    async fn run_enabled(&mut self) -> Result<(), StateChange> {
+        loop {
+            match self.factory.open().await {
+                Err(err) => {
+                    tracing::warn!(
+                        "{} - waiting {} ms to re-open stream",
+                        err,
+                        self.retry_delay.as_millis()
+                    );
+                    self.listener.update(StreamState::Wait(self.retry_delay));
+                    self.session.wait_for_retry(self.retry_delay).await?;
+                }
+                Ok(stream) => {
+                    let mut io = PhysLayer::custom(stream);
+                    tracing::info!("stream open");
+                    self.listener.update(StreamState::Open);
+                    match self
+                        .session
+                        .run(&mut io, &mut self.writer, &mut self.reader)
+                        .await
+                    {
+                        RunError::State(x) => {
+                            return Err(x);
+                        }
+                        RunError::Link(err) => {
+                            tracing::warn!("stream error: {}", err);
+                            tracing::info!(
+                                "waiting {} ms to re-open",
+                                self.retry_delay.as_millis()
+                            );
+                            self.listener.update(StreamState::Wait(self.retry_delay));
+                            self.session.wait_for_retry(self.retry_delay).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::tokio::io::{AsyncRead, AsyncWrite};
+
+pub use loopback::*;
+pub use master::*;
+pub use outstation::*;
+
+mod loopback;
+mod master;
+mod outstation;
+
+/// A user-provided byte stream over which the library can run a master or outstation
+///
+/// This trait is automatically implemented for any type that is `AsyncRead + AsyncWrite + Send + Unpin`,
+/// e.g. a radio modem driver, an SSH channel, or a vendor serial-over-IP connection.
+pub trait PhysStream: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<T> PhysStream for T where T: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+/// Future returned by a [`StreamFactory`] when opening a new stream
+pub type OpenFuture<'a> =
+    Pin<Box<dyn Future<Output = std::io::Result<Box<dyn PhysStream>>> + Send + 'a>>;
+
+/// Produces a new [`PhysStream`] each time a master or outstation (re)opens its physical layer
+///
+/// This trait is automatically implemented for closures of the form `FnMut() -> Future<Output = std::io::Result<S>>`
+/// where `S` implements [`PhysStream`].
+pub trait StreamFactory: Send + 'static {
+    /// Open a new stream
+    fn open(&mut self) -> OpenFuture<'_>;
+}
+
+impl<F, Fut, S> StreamFactory for F
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = std::io::Result<S>> + Send + 'static,
+    S: PhysStream,
+{
+    fn open(&mut self) -> OpenFuture<'_> {
+        let future = self();
+        Box::pin(async move {
+            let stream: Box<dyn PhysStream> = Box::new(future.await?);
+            Ok(stream)
+        })
+    }
+}
+
+/// Create a [`StreamFactory`] that produces the provided stream once and fails on any subsequent open
+///
+/// This is useful for streams that cannot be re-established, e.g. one side of a [`loopback`] link.
+pub fn single_stream<S: PhysStream>(stream: S) -> impl StreamFactory {
+    let mut stream = Some(stream);
+    move || {
+        let stream = stream.take();
+        async move {
+            stream.ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotConnected,
+                    "stream may only be opened once",
+                )
+            })
+        }
+    }
+}
+
+/// State of a user-provided stream
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StreamState {
+    /// Disabled and idle until enabled
+    Disabled,
+    /// waiting to perform an open retry
+    Wait(std::time::Duration),
+    /// Stream is open
+    Open,
+    /// Task has been shut down
+    Shutdown,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::decode::PhysDecodeLevel;
+    use crate::tokio::test::*;
+    use crate::util::phys::PhysLayer;
+
+    #[test]
+    fn factory_streams_are_used_as_the_physical_layer() {
+        let (io, mut handle) = io::mock();
+        let mut io = Some(io);
+        let mut factory = move || {
+            let io = io.take();
+            async move { io.ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected)) }
+        };
+
+        let stream = assert_ready!(spawn(StreamFactory::open(&mut factory)).poll()).unwrap();
+        let mut phys = PhysLayer::custom(stream);
+
+        handle.write(&[0x05, 0x64]);
+        assert_ready!(spawn(phys.write(&[0x05, 0x64], PhysDecodeLevel::Nothing)).poll()).unwrap();
+
+        handle.read(&[0x01, 0x02, 0x03]);
+        let mut buffer = [0; 8];
+        let count =
+            assert_ready!(spawn(phys.read(&mut buffer, PhysDecodeLevel::Nothing)).poll()).unwrap();
+        assert_eq!(&buffer[..count], &[0x01, 0x02, 0x03]);
+
+        // the factory only produces a single stream
+        assert!(assert_ready!(spawn(StreamFactory::open(&mut factory)).poll()).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tracing::Instrument;
+
+use crate::app::{Listener, Shutdown};
+use crate::link::LinkErrorMode;
+use crate::outstation::database::EventBufferConfig;
+use crate::outstation::session::RunError;
+use crate::outstation::task::OutstationTask;
+use crate::outstation::{
+    ControlHandler, OutstationApplication, OutstationConfig, OutstationHandle,
+    OutstationInformation,
+};
+use crate::stream::{StreamFactory, StreamState};
+use crate::util::phys::PhysLayer;
+
+/// Spawn an outstation task onto the `Tokio` runtime that communicates over streams produced by a
+/// user-provided factory. The task runs until the returned handle is dropped.
+///
+/// The factory is invoked when the task starts and again after any stream error, waiting `retry_delay`
+/// after each error. A factory may implement an accept loop by waiting for the next incoming connection
+/// each time it's invoked. Outstations are always enabled, so the listener never reports
+/// [`StreamState::Disabled`].
+///
+/// **Note**: This function may only be called from within the runtime itself, and panics otherwise.
+/// It is preferable to use this method instead of `create_outstation_custom(..)` when using `[tokio::main]`.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_outstation_custom<F: StreamFactory>(
+    factory: F,
+    retry_delay: Duration,
+    link_error_mode: LinkErrorMode,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+    listener: Box<dyn Listener<StreamState>>,
+) -> OutstationHandle {
+    let (future, handle) = create_outstation_custom(
+        factory,
+        retry_delay,
+        link_error_mode,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+        listener,
+    );
+    crate::tokio::spawn(future);
+    handle
+}
+
+/// Create an outstation future that communicates over streams produced by a user-provided factory,
+/// which can be spawned onto a runtime, along with a controlling handle.
+///
+/// Once spawned or otherwise executed using the `run` method, the task runs until the handle
+/// is dropped. See [`spawn_outstation_custom`] for how the factory is used.
+///
+/// **Note**: This function is required instead of `spawn` when using a runtime to directly spawn
+/// tasks instead of within the context of a runtime, e.g. in applications that cannot use
+/// `[tokio::main]` such as C language bindings.
+#[allow(clippy::too_many_arguments)]
+pub fn create_outstation_custom<F: StreamFactory>(
+    factory: F,
+    retry_delay: Duration,
+    link_error_mode: LinkErrorMode,
+    config: OutstationConfig,
+    event_config: EventBufferConfig,
+    application: Box<dyn OutstationApplication>,
+    information: Box<dyn OutstationInformation>,
+    control_handler: Box<dyn ControlHandler>,
+    listener: Box<dyn Listener<StreamState>>,
+) -> (impl Future<Output = ()> + 'static, OutstationHandle) {
+    let (task, handle) = OutstationTask::create(
+        link_error_mode,
+        None,
+        config,
+        event_config,
+        application,
+        information,
+        control_handler,
+    );
+
+    let mut task = StreamOutstationTask {
+        factory: Box::new(factory),
+        retry_delay,
+        task,
+        listener,
+    };
+
+    let future = async move {
+        task.run()
+            .instrument(tracing::info_span!("DNP3-Outstation-Custom"))
+            .await;
+    };
+    (future, handle)
+}
+
+struct StreamOutstationTask {
+    factory: Box<dyn StreamFactory>,
+    retry_delay: Duration,
+    task: OutstationTask,
+    listener: Box<dyn Listener<StreamState>>,
+}
+
+impl StreamOutstationTask {
+    async fn run(&mut self) {
+        let _ = self.run_impl().await;
+        self.listener.update(StreamState::Shutdown);
+    }
+
+    async fn run_impl(&mut self) -> Result<(), Shutdown> {
+        loop {
+            // keep processing messages so that the task shuts down while waiting for a stream
+            let result = crate::tokio::select! {
+                x = self.factory.open() => x,
+                x = self.task.process_messages() => return x,
+            };
+
+            match result {
+                Err(err) => {
+                    tracing::warn!(
+                        "{} - waiting {} ms to re-open stream",
+                        err,
+                        self.retry_delay.as_millis()
+                    );
+                }
+                Ok(stream) => {
+                    let mut io = PhysLayer::custom(stream);
+                    tracing::info!("stream open");
+                    self.listener.update(StreamState::Open);
+                    match self.task.run(&mut io).await {
+                        RunError::Shutdown => return Err(Shutdown),
+                        RunError::Link(err) => {
+                            tracing::warn!("stream error: {}", err);
+                            tracing::info!(
+                                "waiting {} ms to re-open",
+                                self.retry_delay.as_millis()
+                            );
+                        }
+                    }
+                    self.task.reset();
+                }
+            }
+
+            self.listener.update(StreamState::Wait(self.retry_delay));
+            self.wait_for_retry().await?;
+        }
+    }
+
+    async fn wait_for_retry(&mut self) -> Result<(), Shutdown> {
+        crate::tokio::select! {
+            _ = crate::tokio::time::sleep(self.retry_delay) => Ok(()),
+            x = self.task.process_messages() => x,
+        }
+    }
+}
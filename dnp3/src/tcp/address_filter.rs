@@ -31,7 +31,7 @@ impl AddressFilter {
 /// error type returned when a filter conflicts with another filter
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum FilterError {
-    /// filter conflicts with an existing filter
+    /// filter conflicts with the filter of an existing outstation with the same link address
     Conflict,
 }
 
@@ -40,7 +40,9 @@ impl std::error::Error for FilterError {}
 impl std::fmt::Display for FilterError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            FilterError::Conflict => f.write_str("filter conflicts with an existing filter"),
+            FilterError::Conflict => {
+                f.write_str("filter conflicts with an existing filter for the same link address")
+            }
         }
     }
 }
@@ -0,0 +1,113 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use crate::app::{Listener, NullListener};
+
+/// Policy that determines how a `TcpServer` handles a connection for an outstation
+/// that already has one or more active sessions
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ConnectionPolicy {
+    /// Close the oldest active session and replace it with the new connection
+    #[default]
+    ReplaceOldest,
+    /// Reject new connections while a session is active
+    RejectNew,
+    /// Accept up to the specified number of concurrent sessions, rejecting any additional connections
+    ///
+    /// Each session has its own event buffer, unsolicited response state, and confirmation tracking
+    /// while sharing the outstation's database and callbacks.
+    AcceptConcurrent(u16),
+}
+
+impl ConnectionPolicy {
+    pub(crate) fn max_sessions(&self) -> usize {
+        match self {
+            ConnectionPolicy::ReplaceOldest => 1,
+            ConnectionPolicy::RejectNew => 1,
+            ConnectionPolicy::AcceptConcurrent(x) => (*x).max(1) as usize,
+        }
+    }
+}
+
+/// Reason a connection was accepted
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AcceptReason {
+    /// the outstation had capacity for another session
+    CapacityAvailable,
+    /// the oldest session was closed to make room for the connection
+    ReplacedOldest,
+}
+
+/// Reason a connection was rejected
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RejectReason {
+    /// no outstation has an `AddressFilter` that matches the remote address
+    NoMatchingOutstation,
+    /// the outstation already has the maximum number of sessions allowed by its `ConnectionPolicy`
+    SessionLimit,
+}
+
+/// Reason a connection was closed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CloseReason {
+    /// the session was closed to make room for a new connection
+    Replaced,
+    /// an I/O or framing error occurred, or the remote host closed the connection
+    LinkError,
+    /// the outstation was shut down
+    Shutdown,
+}
+
+/// Event reported by a `TcpServer` when a connection is accepted, rejected or closed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConnectionEvent {
+    /// a connection was accepted and assigned to an outstation
+    Accepted {
+        /// identifier assigned to the connection by the server
+        id: u64,
+        /// remote address of the connection
+        addr: SocketAddr,
+        /// why the connection was accepted
+        reason: AcceptReason,
+    },
+    /// a connection was rejected and closed immediately
+    Rejected {
+        /// identifier assigned to the connection by the server
+        id: u64,
+        /// remote address of the connection
+        addr: SocketAddr,
+        /// why the connection was rejected
+        reason: RejectReason,
+    },
+    /// a previously accepted connection was closed
+    Closed {
+        /// identifier assigned to the connection by the server
+        id: u64,
+        /// remote address of the connection
+        addr: SocketAddr,
+        /// why the connection was closed
+        reason: CloseReason,
+    },
+}
+
+/// listener that may be shared between the server and the outstation tasks
+#[derive(Clone)]
+pub(crate) struct SharedListener<T> {
+    inner: Arc<Mutex<Box<dyn Listener<T>>>>,
+}
+
+impl<T> SharedListener<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(NullListener::create())),
+        }
+    }
+
+    pub(crate) fn set(&self, listener: Box<dyn Listener<T>>) {
+        *self.inner.lock().unwrap() = listener;
+    }
+
+    pub(crate) fn update(&self, value: T) {
+        self.inner.lock().unwrap().update(value)
+    }
+}
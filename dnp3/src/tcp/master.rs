@@ -99,6 +99,7 @@ impl MasterTask {
             link_error_mode,
             config.master_address,
             config.rx_buffer_size,
+            config.link_confirm,
         );
         let task = Self {
             endpoints,
@@ -174,7 +175,7 @@ impl MasterTask {
     }
 
     async fn run_socket(&mut self, socket: TcpStream) -> Result<(), StateChange> {
-        let mut io = PhysLayer::Tcp(socket);
+        let mut io = PhysLayer::tcp(socket);
         match self
             .session
             .run(&mut io, &mut self.writer, &mut self.reader)
@@ -1,9 +1,11 @@
 pub use address_filter::*;
+pub use connection::*;
 pub use endpoint_list::*;
 pub use master::*;
 pub use outstation::*;
 
 mod address_filter;
+mod connection;
 mod endpoint_list;
 mod master;
 mod outstation;
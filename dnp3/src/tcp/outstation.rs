@@ -1,18 +1,37 @@
+use std::sync::{Arc, Mutex};
+
 use tracing::Instrument;
 
 use crate::app::{Listener, Shutdown};
-use crate::link::LinkErrorMode;
+use crate::decode::PhysDecodeLevel;
+use crate::link::router::{Route, Router};
+use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::database::EventBufferConfig;
 use crate::outstation::task::OutstationTask;
 use crate::outstation::OutstationHandle;
 use crate::outstation::*;
 use crate::tcp::{AddressFilter, FilterError};
 use crate::util::channel::Sender;
+use crate::util::phys::PhysLayer;
 
-use crate::outstation::adapter::{NewSession, OutstationTaskAdapter};
+use crate::outstation::adapter::{NewSession, OutstationTaskAdapter, SessionTracker};
+use crate::outstation::shared::Callbacks;
+use crate::tcp::{ConnectionEvent, ConnectionPolicy, RejectReason, SharedListener};
 
 struct OutstationInfo {
     filter: AddressFilter,
+    /// link address used to route frames when several outstations match a connection
+    address: EndpointAddress,
+    self_address: bool,
+    /// level at which a routed connection is logged at the physical layer
+    decode_level: PhysDecodeLevel,
+    /// which task is assigned each active connection
+    tracker: Arc<Mutex<SessionTracker>>,
+    /// one entry for each task that can serve a concurrent session
+    tasks: Vec<TaskInfo>,
+}
+
+struct TaskInfo {
     handle: OutstationHandle,
     /// how we notify the outstation adapter task to switch to new socket
     sender: Sender<NewSession>,
@@ -24,6 +43,8 @@ pub struct TcpServer {
     link_error_mode: LinkErrorMode,
     connection_id: u64,
     address: std::net::SocketAddr,
+    policy: ConnectionPolicy,
+    events: SharedListener<ConnectionEvent>,
     outstations: Vec<OutstationInfo>,
 }
 
@@ -40,11 +61,30 @@ impl TcpServer {
             link_error_mode,
             connection_id: 0,
             address,
+            policy: ConnectionPolicy::default(),
+            events: SharedListener::new(),
             outstations: Vec::new(),
         }
     }
 
+    /// set the policy used when a connection arrives for an outstation that already has an
+    /// active session. Defaults to `ConnectionPolicy::ReplaceOldest`.
+    ///
+    /// The policy applies to outstations added after this call.
+    pub fn set_connection_policy(&mut self, policy: ConnectionPolicy) {
+        self.policy = policy;
+    }
+
+    /// set a listener that is notified whenever a connection is accepted, rejected or closed
+    pub fn set_connection_listener(&mut self, listener: Box<dyn Listener<ConnectionEvent>>) {
+        self.events.set(listener);
+    }
+
     /// associate an outstation with the TcpServer, but do not spawn it
+    ///
+    /// Fails with [`FilterError::Conflict`] if the filter conflicts with the filter of an existing
+    /// outstation that has the same link address. Outstations with different link addresses may use
+    /// overlapping filters.
     #[allow(clippy::too_many_arguments)]
     pub fn add_outstation_no_spawn(
         &mut self,
@@ -56,39 +96,135 @@ impl TcpServer {
         listener: Box<dyn Listener<ConnectionState>>,
         filter: AddressFilter,
     ) -> Result<(OutstationHandle, impl std::future::Future<Output = ()>), FilterError> {
-        for item in self.outstations.iter() {
-            if filter.conflicts_with(&item.filter) {
-                return Err(FilterError::Conflict);
-            }
-        }
-
-        let (task, handle) = OutstationTask::create(
-            self.link_error_mode,
+        self.add_outstation_impl(
+            None,
             config,
             event_config,
             application,
             information,
             control_handler,
-        );
-
-        let (mut adapter, tx) = OutstationTaskAdapter::create(task, listener);
+            listener,
+            filter,
+        )
+    }
 
-        let outstation = OutstationInfo {
+    /// associate an outstation with the TcpServer that shares the static database of another
+    /// outstation, but do not spawn it
+    ///
+    /// The other outstation may belong to a different `TcpServer` or use another transport. The new
+    /// outstation maintains its own event buffer, unsolicited response state, and confirmation
+    /// tracking. Updates made through either handle are visible to both outstations and produce
+    /// events in both event buffers.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_shared_outstation_no_spawn(
+        &mut self,
+        shared: &OutstationHandle,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+        listener: Box<dyn Listener<ConnectionState>>,
+        filter: AddressFilter,
+    ) -> Result<(OutstationHandle, impl std::future::Future<Output = ()>), FilterError> {
+        self.add_outstation_impl(
+            Some(shared),
+            config,
+            event_config,
+            application,
+            information,
+            control_handler,
+            listener,
             filter,
-            handle: handle.clone(),
-            sender: tx,
-        };
-        self.outstations.push(outstation);
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_outstation_impl(
+        &mut self,
+        shared: Option<&OutstationHandle>,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+        listener: Box<dyn Listener<ConnectionState>>,
+        filter: AddressFilter,
+    ) -> Result<(OutstationHandle, impl std::future::Future<Output = ()>), FilterError> {
+        // outstations with overlapping filters share connections and are distinguished by link address
+        for item in self.outstations.iter() {
+            if filter.conflicts_with(&item.filter) && item.address == config.outstation_address {
+                return Err(FilterError::Conflict);
+            }
+        }
 
         let endpoint = self.address;
         let address = config.outstation_address.raw_value();
-        let future = async move {
+        let callbacks = Callbacks::new(application, information, control_handler)
+            .share(self.policy.max_sessions());
+        let tracker = Arc::new(Mutex::new(SessionTracker::new(self.policy, listener)));
+
+        let mut handle: Option<OutstationHandle> = None;
+        let mut tasks = Vec::new();
+        let mut adapters = Vec::new();
+        for (index, callbacks) in callbacks.into_iter().enumerate() {
+            // additional concurrent sessions share the database of the first task
+            let (task, task_handle) = OutstationTask::create(
+                self.link_error_mode,
+                handle.as_ref().or(shared),
+                config,
+                event_config,
+                callbacks.application,
+                callbacks.information,
+                callbacks.control_handler,
+            );
+
+            let (adapter, tx) =
+                OutstationTaskAdapter::create(task, index, tracker.clone(), self.events.clone());
+
+            match handle.as_mut() {
+                Some(first) => first.add_peer(&task_handle),
+                None => handle = Some(task_handle.clone()),
+            }
+
+            tasks.push(TaskInfo {
+                handle: task_handle,
+                sender: tx,
+            });
+            adapters.push(adapter);
+        }
+
+        self.outstations.push(OutstationInfo {
+            filter,
+            address: config.outstation_address,
+            self_address: config.features.self_address.is_enabled(),
+            decode_level: config.decode_level.physical,
+            tracker,
+            tasks,
+        });
+
+        let run = move |mut adapter: OutstationTaskAdapter| async move {
             let _ = adapter.run()
                 .instrument(
                     tracing::info_span!("DNP3-Outstation-TCP", "listen" = ?endpoint, "addr" = address),
                 )
                 .await;
         };
+
+        let mut adapters = adapters.into_iter();
+        let first = adapters.next();
+        let future = async move {
+            // tasks serving additional concurrent sessions run independently
+            for adapter in adapters {
+                crate::tokio::spawn(run(adapter));
+            }
+            if let Some(adapter) = first {
+                run(adapter).await;
+            }
+        };
+
+        // there is always at least one task
+        let handle = handle.unwrap();
         Ok((handle, future))
     }
 
@@ -119,6 +255,36 @@ impl TcpServer {
         Ok(handle)
     }
 
+    /// associate an outstation with the TcpServer that shares the static database of another
+    /// outstation and spawn it
+    ///
+    /// See [TcpServer::add_shared_outstation_no_spawn] for details. Must be called from within the Tokio runtime
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_shared_outstation(
+        &mut self,
+        shared: &OutstationHandle,
+        config: OutstationConfig,
+        event_config: EventBufferConfig,
+        application: Box<dyn OutstationApplication>,
+        information: Box<dyn OutstationInformation>,
+        control_handler: Box<dyn ControlHandler>,
+        listener: Box<dyn Listener<ConnectionState>>,
+        filter: AddressFilter,
+    ) -> Result<OutstationHandle, FilterError> {
+        let (handle, future) = self.add_shared_outstation_no_spawn(
+            shared,
+            config,
+            event_config,
+            application,
+            information,
+            control_handler,
+            listener,
+            filter,
+        )?;
+        crate::tokio::spawn(future);
+        Ok(handle)
+    }
+
     /// Consume the `TcpServer` builder object, bind it to pre-specified port, and return a (ServerHandle, Future)
     /// tuple.
     ///
@@ -173,8 +339,10 @@ impl TcpServer {
         tracing::info!("shutting down outstations");
 
         for x in self.outstations.iter_mut() {
-            // best effort to shutdown outstations before exiting
-            let _ = x.handle.shutdown().await;
+            for task in x.tasks.iter_mut() {
+                // best effort to shutdown outstations before exiting
+                let _ = task.handle.shutdown().await;
+            }
         }
 
         tracing::info!("shutdown");
@@ -217,24 +385,166 @@ impl TcpServer {
 
         tracing::info!("accepted connection {} from: {}", id, addr);
 
-        let first_match = self
+        let matches: Vec<usize> = self
             .outstations
-            .iter_mut()
-            .find(|x| x.filter.matches(addr.ip()));
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.filter.matches(addr.ip()))
+            .map(|(index, _)| index)
+            .collect();
 
-        match first_match {
-            None => {
-                tracing::warn!("no matching outstation for: {}", addr)
+        let outstation = match matches.as_slice() {
+            [] => {
+                tracing::warn!("no matching outstation for: {}", addr);
+                self.events.update(ConnectionEvent::Rejected {
+                    id,
+                    addr,
+                    reason: RejectReason::NoMatchingOutstation,
+                });
+                return;
             }
-            Some(x) => {
-                let _ = x
+            [index] => &mut self.outstations[*index],
+            _ => {
+                self.process_routed_connection(id, stream, addr, matches)
+                    .await;
+                return;
+            }
+        };
+
+        let assignment = outstation.tracker.lock().unwrap().assign(id);
+        match assignment {
+            Err(reason) => {
+                tracing::warn!("rejected connection {} from {}: {:?}", id, addr, reason);
+                self.events
+                    .update(ConnectionEvent::Rejected { id, addr, reason });
+            }
+            Ok((index, reason)) => {
+                self.events
+                    .update(ConnectionEvent::Accepted { id, addr, reason });
+                let _ = outstation.tasks[index]
                     .sender
-                    .send(NewSession::new(
-                        id,
-                        crate::util::phys::PhysLayer::Tcp(stream),
-                    ))
+                    .send(NewSession::new(id, addr, PhysLayer::tcp(stream)))
                     .await;
             }
         }
     }
+
+    /// route the frames of a connection matching several outstations by destination link address
+    async fn process_routed_connection(
+        &mut self,
+        id: u64,
+        stream: crate::tokio::net::TcpStream,
+        addr: std::net::SocketAddr,
+        matches: Vec<usize>,
+    ) {
+        let (outgoing, receiver) = crate::tokio::sync::mpsc::unbounded_channel();
+        let decode_level = matches
+            .iter()
+            .map(|index| self.outstations[*index].decode_level)
+            .max()
+            .unwrap_or(PhysDecodeLevel::Nothing);
+        // the connection is dedicated to these outstations, so unknown addresses are answered
+        let mut router = Router::new(decode_level, true);
+        let mut accepted = None;
+        let mut rejected = RejectReason::SessionLimit;
+
+        for index in matches {
+            let outstation = &mut self.outstations[index];
+            let assignment = outstation.tracker.lock().unwrap().assign(id);
+            match assignment {
+                Err(reason) => {
+                    tracing::warn!(
+                        "rejected connection {} from {} for outstation {}: {:?}",
+                        id,
+                        addr,
+                        outstation.address,
+                        reason
+                    );
+                    rejected = reason;
+                }
+                Ok((task, reason)) => {
+                    let (route, stream) = Route::create(
+                        outstation.address,
+                        outstation.self_address,
+                        outgoing.clone(),
+                    );
+                    router.add(route);
+                    accepted.get_or_insert(reason);
+                    let _ = outstation.tasks[task]
+                        .sender
+                        .send(NewSession::new(id, addr, PhysLayer::routed(stream)))
+                        .await;
+                }
+            }
+        }
+
+        // only the routed outstations may keep the outgoing channel open
+        drop(outgoing);
+
+        match accepted {
+            None => {
+                self.events.update(ConnectionEvent::Rejected {
+                    id,
+                    addr,
+                    reason: rejected,
+                });
+            }
+            Some(reason) => {
+                self.events
+                    .update(ConnectionEvent::Accepted { id, addr, reason });
+                crate::tokio::spawn(
+                    async move {
+                        let mut io = PhysLayer::tcp(stream);
+                        if let Err(err) = router.run(&mut io, receiver).await {
+                            tracing::warn!("connection error: {}", err);
+                        }
+                    }
+                    .instrument(tracing::info_span!("Router", "id" = id)),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::NullListener;
+
+    fn add(server: &mut TcpServer, address: u16, filter: AddressFilter) -> Result<(), FilterError> {
+        server
+            .add_outstation_no_spawn(
+                OutstationConfig::new(
+                    EndpointAddress::from(address).unwrap(),
+                    EndpointAddress::from(1).unwrap(),
+                ),
+                EventBufferConfig::no_events(),
+                DefaultOutstationApplication::create(),
+                DefaultOutstationInformation::create(),
+                DefaultControlHandler::create(),
+                NullListener::create(),
+                filter,
+            )
+            .map(|_| ())
+    }
+
+    #[test]
+    fn overlapping_filters_only_conflict_for_the_same_link_address() {
+        let mut server = TcpServer::new(LinkErrorMode::Close, "127.0.0.1:20000".parse().unwrap());
+        let ip = "127.0.0.1".parse().unwrap();
+
+        add(&mut server, 10, AddressFilter::Any).unwrap();
+        // conflicted before outstations could share a connection
+        add(&mut server, 11, AddressFilter::Any).unwrap();
+        add(&mut server, 12, AddressFilter::Exact(ip)).unwrap();
+
+        assert_eq!(
+            add(&mut server, 10, AddressFilter::Any),
+            Err(FilterError::Conflict)
+        );
+        assert_eq!(
+            add(&mut server, 11, AddressFilter::Exact(ip)),
+            Err(FilterError::Conflict)
+        );
+    }
 }
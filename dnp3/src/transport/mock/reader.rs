@@ -1,6 +1,7 @@
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::FrameInfo;
+use crate::link::primary::PrimaryHandle;
 use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::Feature;
 use crate::transport::{Fragment, FragmentInfo, TransportData};
@@ -16,7 +17,12 @@ pub(crate) struct MockReader {
 }
 
 impl MockReader {
-    pub(crate) fn master(_: LinkErrorMode, _: EndpointAddress, rx_buffer_size: usize) -> Self {
+    pub(crate) fn master(
+        _: LinkErrorMode,
+        _: EndpointAddress,
+        rx_buffer_size: usize,
+        _: Option<PrimaryHandle>,
+    ) -> Self {
         Self::new(rx_buffer_size)
     }
 
@@ -25,6 +31,7 @@ impl MockReader {
         _: EndpointAddress,
         _self_address: Feature,
         rx_buffer_size: usize,
+        _: Option<PrimaryHandle>,
     ) -> Self {
         Self::new(rx_buffer_size)
     }
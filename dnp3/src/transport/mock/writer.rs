@@ -1,18 +1,25 @@
+use std::sync::{Arc, Mutex};
+
 use crate::app::EndpointType;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::AnyAddress;
+use crate::link::primary::PrimaryHandle;
 use crate::link::EndpointAddress;
 use crate::util::phys::PhysLayer;
 
 pub(crate) struct MockWriter {
     num_writes: usize,
+    destinations: Arc<Mutex<Vec<AnyAddress>>>,
 }
 
 // same signature as the real transport writer
 impl MockWriter {
-    pub(crate) fn new(_: EndpointType, _: EndpointAddress) -> Self {
-        Self { num_writes: 0 }
+    pub(crate) fn new(_: EndpointType, _: EndpointAddress, _: Option<PrimaryHandle>) -> Self {
+        Self {
+            num_writes: 0,
+            destinations: Arc::new(Mutex::new(Vec::new())),
+        }
     }
 
     pub(crate) fn reset(&mut self) {}
@@ -21,15 +28,21 @@ impl MockWriter {
         self.num_writes
     }
 
+    /// destinations of the fragments written so far, shared so tests can inspect them after the writer is moved
+    pub(crate) fn destinations(&self) -> Arc<Mutex<Vec<AnyAddress>>> {
+        self.destinations.clone()
+    }
+
     // just write the fragment directly to the I/O
     pub(crate) async fn write(
         &mut self,
         io: &mut PhysLayer,
         level: DecodeLevel,
-        _: AnyAddress,
+        destination: AnyAddress,
         fragment: &[u8],
     ) -> Result<(), LinkError> {
         io.write(fragment, level.physical).await?;
+        self.destinations.lock().unwrap().push(destination);
         self.num_writes += 1;
         Ok(())
     }
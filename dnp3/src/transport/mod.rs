@@ -3,14 +3,15 @@ pub(crate) use types::*;
 pub(crate) use writer::*;
 
 use crate::app::EndpointType;
+use crate::link::primary::PrimaryHandle;
 use crate::link::EndpointAddress;
-use crate::link::LinkErrorMode;
+use crate::link::{LinkConfirmConfig, LinkErrorMode};
 use crate::master::session::MasterSession;
 use crate::outstation::Feature;
 
 #[cfg(test)]
 pub(crate) mod mock;
-#[cfg(not(test))]
+// always compiled since the offline decoder reuses the assembler
 pub(crate) mod real;
 
 mod reader;
@@ -21,6 +22,7 @@ pub(crate) fn create_master_transport_layer(
     link_error_mode: LinkErrorMode,
     address: EndpointAddress,
     rx_buffer_size: usize,
+    link_confirm: Option<LinkConfirmConfig>,
 ) -> (TransportReader, TransportWriter) {
     let rx_buffer_size = if rx_buffer_size < MasterSession::MIN_RX_BUFFER_SIZE {
         tracing::warn!("Minimum RX buffer size is {}. Defaulting to this value because the provided value ({}) is too low.", MasterSession::MIN_RX_BUFFER_SIZE, rx_buffer_size);
@@ -29,9 +31,11 @@ pub(crate) fn create_master_transport_layer(
         rx_buffer_size
     };
 
+    let primary =
+        link_confirm.map(|config| PrimaryHandle::new(EndpointType::Master, address, config));
     (
-        TransportReader::master(link_error_mode, address, rx_buffer_size),
-        TransportWriter::new(EndpointType::Master, address),
+        TransportReader::master(link_error_mode, address, rx_buffer_size, primary.clone()),
+        TransportWriter::new(EndpointType::Master, address, primary),
     )
 }
 
@@ -40,14 +44,18 @@ pub(crate) fn create_outstation_transport_layer(
     address: EndpointAddress,
     self_address: Feature,
     rx_buffer_size: crate::outstation::BufferSize,
+    link_confirm: Option<LinkConfirmConfig>,
 ) -> (TransportReader, TransportWriter) {
+    let primary =
+        link_confirm.map(|config| PrimaryHandle::new(EndpointType::Outstation, address, config));
     (
         TransportReader::outstation(
             link_error_mode,
             address,
             self_address,
             rx_buffer_size.value(),
+            primary.clone(),
         ),
-        TransportWriter::new(EndpointType::Outstation, address),
+        TransportWriter::new(EndpointType::Outstation, address, primary),
     )
 }
@@ -2,6 +2,7 @@ use crate::app::parse::parser::ParsedFragment;
 use crate::app::HeaderParseError;
 use crate::decode::{AppDecodeLevel, DecodeLevel};
 use crate::link::error::LinkError;
+use crate::link::primary::PrimaryHandle;
 use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::Feature;
 use crate::transport::{
@@ -55,9 +56,10 @@ impl TransportReader {
         link_error_mode: LinkErrorMode,
         address: EndpointAddress,
         rx_buffer_size: usize,
+        primary: Option<PrimaryHandle>,
     ) -> Self {
         Self {
-            inner: InnerReaderType::master(link_error_mode, address, rx_buffer_size),
+            inner: InnerReaderType::master(link_error_mode, address, rx_buffer_size, primary),
         }
     }
 
@@ -66,6 +68,7 @@ impl TransportReader {
         address: EndpointAddress,
         self_address: Feature,
         rx_buffer_size: usize,
+        primary: Option<PrimaryHandle>,
     ) -> Self {
         Self {
             inner: InnerReaderType::outstation(
@@ -73,6 +76,7 @@ impl TransportReader {
                 address,
                 self_address,
                 rx_buffer_size,
+                primary,
             ),
         }
     }
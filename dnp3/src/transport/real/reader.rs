@@ -3,6 +3,7 @@ use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::FrameType;
 use crate::link::parser::FramePayload;
+use crate::link::primary::PrimaryHandle;
 use crate::link::{EndpointAddress, LinkErrorMode};
 use crate::outstation::Feature;
 use crate::transport::real::assembler::{Assembler, AssemblyState};
@@ -22,6 +23,7 @@ impl Reader {
         link_error_mode: LinkErrorMode,
         source: EndpointAddress,
         max_tx_buffer: usize,
+        primary: Option<PrimaryHandle>,
     ) -> Self {
         Self {
             link: crate::link::layer::Layer::new(
@@ -29,6 +31,7 @@ impl Reader {
                 EndpointType::Master,
                 Feature::Disabled,
                 source,
+                primary,
             ),
             assembler: Assembler::new(max_tx_buffer),
             pending_link_layer_message: None,
@@ -40,6 +43,7 @@ impl Reader {
         source: EndpointAddress,
         self_address: Feature,
         max_rx_buffer: usize,
+        primary: Option<PrimaryHandle>,
     ) -> Self {
         Self {
             link: crate::link::layer::Layer::new(
@@ -47,6 +51,7 @@ impl Reader {
                 EndpointType::Outstation,
                 self_address,
                 source,
+                primary,
             ),
             assembler: Assembler::new(max_rx_buffer),
             pending_link_layer_message: None,
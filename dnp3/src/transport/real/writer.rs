@@ -3,6 +3,7 @@ use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::format::{format_data_frame, format_header_only, Payload};
 use crate::link::header::AnyAddress;
+use crate::link::primary::PrimaryHandle;
 use crate::link::EndpointAddress;
 use crate::transport::real::display::SegmentDisplay;
 use crate::transport::real::header::Header;
@@ -14,21 +15,30 @@ pub(crate) struct Writer {
     endpoint_type: EndpointType,
     local_address: EndpointAddress,
     seq: Sequence,
+    primary: Option<PrimaryHandle>,
     buffer: [u8; crate::link::constant::MAX_LINK_FRAME_LENGTH],
 }
 
 impl Writer {
-    pub(crate) fn new(endpoint_type: EndpointType, local_address: EndpointAddress) -> Self {
+    pub(crate) fn new(
+        endpoint_type: EndpointType,
+        local_address: EndpointAddress,
+        primary: Option<PrimaryHandle>,
+    ) -> Self {
         Self {
             endpoint_type,
             local_address,
             seq: Sequence::default(),
+            primary,
             buffer: [0; crate::link::constant::MAX_LINK_FRAME_LENGTH],
         }
     }
 
     pub(crate) fn reset(&mut self) {
         self.seq.reset();
+        if let Some(primary) = &self.primary {
+            primary.reset();
+        }
     }
 
     pub(crate) async fn write(
@@ -46,6 +56,12 @@ impl Writer {
             chunks.len() - 1
         };
 
+        // broadcasts are never confirmed at the link-layer
+        let confirmed = match (&self.primary, destination) {
+            (Some(primary), AnyAddress::Endpoint(address)) => Some((primary, address)),
+            _ => None,
+        };
+
         for (count, chunk) in chunks.enumerate() {
             let mut cursor = WriteCursor::new(&mut self.buffer);
             let header = Header::new(count == last, count == 0, self.seq.increment());
@@ -55,6 +71,11 @@ impl Writer {
                     SegmentDisplay::new(header, chunk, level.transport)
                 );
             }
+            if let Some((primary, address)) = confirmed {
+                // the link-layer transmits the remaining segments as each one is acknowledged
+                primary.enqueue(address, header.to_u8(), chunk);
+                continue;
+            }
             let link_header = crate::link::header::Header::unconfirmed_user_data(
                 self.endpoint_type.dir_bit(),
                 destination,
@@ -71,6 +92,12 @@ impl Writer {
             io.write(data.frame, level.physical).await?;
         }
 
+        if let Some((primary, _)) = confirmed {
+            if let Some(frame) = primary.poll_transmit(level)? {
+                io.write(&frame, level.physical).await?;
+            }
+        }
+
         Ok(())
     }
 
@@ -96,3 +123,68 @@ impl Writer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::link::header::BroadcastConfirmMode;
+    use crate::link::LinkConfirmConfig;
+    use crate::tokio::test::*;
+
+    const FRAGMENT: &[u8] = &[0xC0, 0x02];
+
+    fn local() -> EndpointAddress {
+        EndpointAddress::from(1).unwrap()
+    }
+
+    fn unconfirmed_frame(destination: AnyAddress) -> Vec<u8> {
+        let mut buffer = [0; crate::link::constant::MAX_LINK_FRAME_LENGTH];
+        let mut cursor = WriteCursor::new(&mut buffer);
+        let header =
+            crate::link::header::Header::unconfirmed_user_data(true, destination, local().wrap());
+        let transport = Header::new(true, true, Sequence::default());
+        format_data_frame(
+            header,
+            Payload::new(transport.to_u8(), FRAGMENT),
+            &mut cursor,
+        )
+        .unwrap()
+        .frame
+        .to_vec()
+    }
+
+    #[test]
+    fn broadcasts_are_written_unconfirmed_to_the_broadcast_address() {
+        for (mode, address) in [
+            (BroadcastConfirmMode::Optional, 0xFFFF),
+            (BroadcastConfirmMode::Mandatory, 0xFFFE),
+            (BroadcastConfirmMode::NotRequired, 0xFFFD),
+        ] {
+            let primary = PrimaryHandle::new(
+                EndpointType::Master,
+                local(),
+                LinkConfirmConfig::new(std::time::Duration::from_secs(1), 2),
+            );
+            let mut writer = Writer::new(EndpointType::Master, local(), Some(primary.clone()));
+            let (io, mut io_handle) = io::mock();
+            let mut io = PhysLayer::mock(io);
+
+            let frame = unconfirmed_frame(AnyAddress::Broadcast(mode));
+            // destination field of the link header
+            assert_eq!(frame[4..6], u16::to_le_bytes(address));
+            io_handle.write(&frame);
+
+            let mut task = spawn(writer.write(
+                &mut io,
+                DecodeLevel::nothing(),
+                AnyAddress::Broadcast(mode),
+                FRAGMENT,
+            ));
+            assert_ready!(task.poll()).unwrap();
+            drop(task);
+
+            // the confirmed data service is enabled, but never used for broadcasts
+            assert_eq!(primary.poll_transmit(DecodeLevel::nothing()).unwrap(), None);
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::app::EndpointType;
 use crate::decode::DecodeLevel;
 use crate::link::error::LinkError;
 use crate::link::header::AnyAddress;
+use crate::link::primary::PrimaryHandle;
 use crate::link::EndpointAddress;
 use crate::util::phys::PhysLayer;
 
@@ -18,9 +19,13 @@ pub(crate) struct TransportWriter {
 }
 
 impl TransportWriter {
-    pub(crate) fn new(endpoint_type: EndpointType, local_address: EndpointAddress) -> Self {
+    pub(crate) fn new(
+        endpoint_type: EndpointType,
+        local_address: EndpointAddress,
+        primary: Option<PrimaryHandle>,
+    ) -> Self {
         Self {
-            inner: InnerTransportWriter::new(endpoint_type, local_address),
+            inner: InnerTransportWriter::new(endpoint_type, local_address, primary),
         }
     }
 
@@ -28,6 +33,11 @@ impl TransportWriter {
         self.inner.reset()
     }
 
+    #[cfg(test)]
+    pub(crate) fn get_inner(&mut self) -> &mut InnerTransportWriter {
+        &mut self.inner
+    }
+
     pub(crate) async fn write(
         &mut self,
         io: &mut PhysLayer,
@@ -1,42 +1,120 @@
+use std::net::SocketAddr;
+use std::time::SystemTime;
+
+use crate::capture::{CaptureDirection, CaptureRecord, CaptureSlot};
 use crate::decode::PhysDecodeLevel;
 use crate::tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 // encapsulates all possible physical layers as an enum
-pub(crate) enum PhysLayer {
+enum PhysIo {
     Tcp(crate::tokio::net::TcpStream),
-    Serial(tokio_serial::TTYPort),
+    Serial(crate::serial::Port),
+    Custom(Box<dyn crate::stream::PhysStream>),
+    /// frames routed to one of several outstations sharing a physical layer
+    Routed(crate::link::router::RoutedStream),
     #[cfg(test)]
     Mock(tokio_mock::mock::test::io::MockIO),
 }
 
+/// physical layer of a single connection, along with the capture state of the connection
+pub(crate) struct PhysLayer {
+    io: PhysIo,
+    local: Option<SocketAddr>,
+    remote: Option<SocketAddr>,
+    capture: CaptureSlot,
+    sent: u64,
+    received: u64,
+}
+
 impl std::fmt::Debug for PhysLayer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            PhysLayer::Tcp(_) => f.write_str("Tcp"),
-            PhysLayer::Serial(_) => f.write_str("Serial"),
+        match self.io {
+            PhysIo::Tcp(_) => f.write_str("Tcp"),
+            PhysIo::Serial(_) => f.write_str("Serial"),
+            PhysIo::Custom(_) => f.write_str("Custom"),
+            PhysIo::Routed(_) => f.write_str("Routed"),
             #[cfg(test)]
-            PhysLayer::Mock(_) => f.write_str("Mock"),
+            PhysIo::Mock(_) => f.write_str("Mock"),
         }
     }
 }
 
 impl PhysLayer {
+    fn new(io: PhysIo, local: Option<SocketAddr>, remote: Option<SocketAddr>) -> Self {
+        Self {
+            io,
+            local,
+            remote,
+            capture: CaptureSlot::default(),
+            sent: 0,
+            received: 0,
+        }
+    }
+
+    pub(crate) fn tcp(stream: crate::tokio::net::TcpStream) -> Self {
+        let local = stream.local_addr().ok();
+        let remote = stream.peer_addr().ok();
+        Self::new(PhysIo::Tcp(stream), local, remote)
+    }
+
+    pub(crate) fn serial(port: crate::serial::Port) -> Self {
+        Self::new(PhysIo::Serial(port), None, None)
+    }
+
+    pub(crate) fn custom(stream: Box<dyn crate::stream::PhysStream>) -> Self {
+        Self::new(PhysIo::Custom(stream), None, None)
+    }
+
+    /// The router logs and captures the physical layer shared by the routed outstations, so
+    /// the routed stream itself is never logged or captured
+    pub(crate) fn routed(stream: crate::link::router::RoutedStream) -> Self {
+        Self::new(PhysIo::Routed(stream), None, None)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn mock(io: tokio_mock::mock::test::io::MockIO) -> Self {
+        Self::new(PhysIo::Mock(io), None, None)
+    }
+
+    /// share the capture sink installed on the master or outstation using this physical layer
+    pub(crate) fn set_capture(&mut self, capture: CaptureSlot) {
+        match &self.io {
+            PhysIo::Routed(x) => x.set_capture(capture),
+            _ => self.capture = capture,
+        }
+    }
+
+    fn is_routed(&self) -> bool {
+        matches!(self.io, PhysIo::Routed(_))
+    }
+
+    /// returns true once if the physical layer detected a gap that delimits frames before the last read
+    pub(crate) fn take_frame_gap(&mut self) -> bool {
+        match &mut self.io {
+            PhysIo::Serial(x) => x.take_gap(),
+            _ => false,
+        }
+    }
+
     pub(crate) async fn read(
         &mut self,
         buffer: &mut [u8],
         level: PhysDecodeLevel,
     ) -> Result<usize, std::io::Error> {
-        let length = match self {
-            Self::Tcp(x) => x.read(buffer).await?,
-            Self::Serial(x) => x.read(buffer).await?,
+        let length = match &mut self.io {
+            PhysIo::Tcp(x) => x.read(buffer).await?,
+            PhysIo::Serial(x) => x.read(buffer).await?,
+            PhysIo::Custom(x) => x.read(buffer).await?,
+            PhysIo::Routed(x) => x.read(buffer).await?,
             #[cfg(test)]
-            Self::Mock(x) => x.read(buffer).await?,
+            PhysIo::Mock(x) => x.read(buffer).await?,
         };
 
-        if level.enabled() {
-            if let Some(x) = buffer.get(0..length) {
+        if let Some(x) = buffer.get(0..length) {
+            if level.enabled() && !self.is_routed() {
                 tracing::info!("PHYS RX - {}", PhysDisplay::new(level, x))
             }
+            self.capture(CaptureDirection::Rx, x);
         }
 
         Ok(length)
@@ -47,15 +125,41 @@ impl PhysLayer {
         data: &[u8],
         level: PhysDecodeLevel,
     ) -> Result<(), std::io::Error> {
-        if level.enabled() {
+        if level.enabled() && !self.is_routed() {
             tracing::info!("PHYS TX - {}", PhysDisplay::new(level, data));
         }
 
-        match self {
-            Self::Tcp(x) => x.write_all(data).await,
-            Self::Serial(x) => x.write_all(data).await,
+        match &mut self.io {
+            PhysIo::Tcp(x) => x.write_all(data).await?,
+            PhysIo::Serial(x) => x.write(data).await?,
+            PhysIo::Custom(x) => x.write_all(data).await?,
+            PhysIo::Routed(x) => x.write_all(data).await?,
             #[cfg(test)]
-            Self::Mock(x) => x.write_all(data).await,
+            PhysIo::Mock(x) => x.write_all(data).await?,
+        }
+
+        self.capture(CaptureDirection::Tx, data);
+        Ok(())
+    }
+
+    fn capture(&mut self, direction: CaptureDirection, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        self.capture.record(&CaptureRecord {
+            timestamp: SystemTime::now(),
+            direction,
+            local: self.local,
+            remote: self.remote,
+            sent: self.sent,
+            received: self.received,
+            data,
+        });
+
+        match direction {
+            CaptureDirection::Rx => self.received += data.len() as u64,
+            CaptureDirection::Tx => self.sent += data.len() as u64,
         }
     }
 }
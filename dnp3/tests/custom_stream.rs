@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::DuplexStream;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+
+use dnp3::app::*;
+use dnp3::link::*;
+use dnp3::master::*;
+use dnp3::outstation::database::*;
+use dnp3::outstation::*;
+use dnp3::stream::*;
+
+const RETRY_DELAY: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn outstation_address() -> EndpointAddress {
+    EndpointAddress::from(10).unwrap()
+}
+
+fn master_address() -> EndpointAddress {
+    EndpointAddress::from(1).unwrap()
+}
+
+struct StateListener {
+    tx: UnboundedSender<StreamState>,
+}
+
+impl Listener<StreamState> for StateListener {
+    fn update(&mut self, value: StreamState) {
+        let _ = self.tx.send(value);
+    }
+}
+
+async fn next_open(rx: &mut UnboundedReceiver<StreamState>) {
+    loop {
+        let state = tokio::time::timeout(TIMEOUT, rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if state == StreamState::Open {
+            return;
+        }
+    }
+}
+
+#[tokio::test]
+async fn outstation_accepts_a_new_stream_after_the_master_reconnects() {
+    // the master "connects" by handing one side of a new in-memory stream to the outstation
+    let (connect_tx, connect_rx) = tokio::sync::mpsc::unbounded_channel::<DuplexStream>();
+    let master_factory = move || {
+        let (local, remote) = tokio::io::duplex(4096);
+        let result = connect_tx
+            .send(remote)
+            .map(|_| local)
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        async move { result }
+    };
+
+    // the outstation accepts each of these streams in turn
+    let connect_rx = Arc::new(Mutex::new(connect_rx));
+    let accept = move || {
+        let connect_rx = connect_rx.clone();
+        async move {
+            connect_rx
+                .lock()
+                .await
+                .recv()
+                .await
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::ConnectionAborted))
+        }
+    };
+
+    let (state_tx, mut state_rx) = tokio::sync::mpsc::unbounded_channel();
+    let outstation = spawn_outstation_custom(
+        accept,
+        RETRY_DELAY,
+        LinkErrorMode::Close,
+        OutstationConfig::new(outstation_address(), master_address()),
+        EventBufferConfig::all_types(10),
+        DefaultOutstationApplication::create(),
+        DefaultOutstationInformation::create(),
+        DefaultControlHandler::create(),
+        Box::new(StateListener { tx: state_tx }),
+    );
+
+    outstation.transaction(|db| {
+        db.add(
+            0,
+            Some(EventClass::Class1),
+            BinaryConfig::new(
+                StaticBinaryVariation::Group1Var2,
+                EventBinaryVariation::Group2Var2,
+            ),
+        );
+    });
+
+    let mut master = spawn_master_custom(
+        MasterChannelConfig::new(master_address()),
+        LinkErrorMode::Close,
+        master_factory,
+        RETRY_DELAY,
+        NullListener::create(),
+    );
+
+    let mut association = master
+        .add_association(
+            outstation_address(),
+            AssociationConfig::quiet(),
+            NullReadHandler::boxed(),
+            DefaultAssociationHandler::boxed(),
+        )
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        master.enable().await.unwrap();
+        next_open(&mut state_rx).await;
+
+        let result = tokio::time::timeout(
+            TIMEOUT,
+            association.read(ReadRequest::class_scan(Classes::all())),
+        )
+        .await
+        .unwrap();
+        assert!(result.is_ok());
+
+        // closes the stream, which the outstation must replace with the next accepted one
+        master.disable().await.unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+use dnp3::app::measurement::*;
+use dnp3::app::*;
+use dnp3::link::*;
+use dnp3::master::*;
+use dnp3::outstation::database::*;
+use dnp3::outstation::*;
+use dnp3::stream::*;
+
+const RETRY_DELAY: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn outstation_address() -> EndpointAddress {
+    EndpointAddress::from(10).unwrap()
+}
+
+fn master_address() -> EndpointAddress {
+    EndpointAddress::from(1).unwrap()
+}
+
+// forwards received binary values to the test
+struct BinaryHandler {
+    tx: UnboundedSender<(bool, u16)>,
+}
+
+impl ReadHandler for BinaryHandler {
+    fn begin_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn end_fragment(&mut self, _read_type: ReadType, _header: ResponseHeader) {}
+
+    fn handle_binary(&mut self, _info: HeaderInfo, iter: &mut dyn Iterator<Item = (Binary, u16)>) {
+        for (v, i) in iter {
+            let _ = self.tx.send((v.value, i));
+        }
+    }
+
+    fn handle_double_bit_binary(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (DoubleBitBinary, u16)>,
+    ) {
+    }
+
+    fn handle_binary_output_status(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (BinaryOutputStatus, u16)>,
+    ) {
+    }
+
+    fn handle_counter(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (Counter, u16)>,
+    ) {
+    }
+
+    fn handle_frozen_counter(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (FrozenCounter, u16)>,
+    ) {
+    }
+
+    fn handle_analog(&mut self, _info: HeaderInfo, _iter: &mut dyn Iterator<Item = (Analog, u16)>) {
+    }
+
+    fn handle_analog_output_status(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &mut dyn Iterator<Item = (AnalogOutputStatus, u16)>,
+    ) {
+    }
+
+    fn handle_octet_string<'a>(
+        &mut self,
+        _info: HeaderInfo,
+        _iter: &'a mut dyn Iterator<Item = (Bytes<'a>, u16)>,
+    ) {
+    }
+}
+
+struct Pair {
+    // have to hold onto this to keep master alive
+    _master: MasterChannel,
+    association: AssociationHandle,
+    outstation: OutstationHandle,
+    rx: UnboundedReceiver<(bool, u16)>,
+}
+
+impl Pair {
+    async fn spawn(config: LoopbackConfig) -> Self {
+        let (master_stream, outstation_stream) = loopback(config);
+
+        let outstation = spawn_outstation_custom(
+            single_stream(outstation_stream),
+            RETRY_DELAY,
+            LinkErrorMode::Close,
+            OutstationConfig::new(outstation_address(), master_address()),
+            EventBufferConfig::all_types(10),
+            DefaultOutstationApplication::create(),
+            DefaultOutstationInformation::create(),
+            DefaultControlHandler::create(),
+            NullListener::create(),
+        );
+
+        outstation.transaction(|db| {
+            db.add(
+                0,
+                Some(EventClass::Class1),
+                BinaryConfig::new(
+                    StaticBinaryVariation::Group1Var2,
+                    EventBinaryVariation::Group2Var2,
+                ),
+            );
+        });
+
+        let mut master = spawn_master_custom(
+            MasterChannelConfig::new(master_address()),
+            LinkErrorMode::Close,
+            single_stream(master_stream),
+            RETRY_DELAY,
+            NullListener::create(),
+        );
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let association = master
+            .add_association(
+                outstation_address(),
+                AssociationConfig::quiet(),
+                Box::new(BinaryHandler { tx }),
+                DefaultAssociationHandler::boxed(),
+            )
+            .await
+            .unwrap();
+
+        master.enable().await.unwrap();
+
+        Self {
+            _master: master,
+            association,
+            outstation,
+            rx,
+        }
+    }
+
+    async fn read(&mut self, classes: Classes) -> Vec<(bool, u16)> {
+        tokio::time::timeout(
+            TIMEOUT,
+            self.association.read(ReadRequest::class_scan(classes)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let mut values = Vec::new();
+        while let Ok(x) = self.rx.try_recv() {
+            values.push(x);
+        }
+        values
+    }
+
+    fn update(&mut self, value: bool) {
+        self.outstation.transaction(|db| {
+            db.update(
+                0,
+                &Binary::new(value, Flags::ONLINE, Time::synchronized(0)),
+                UpdateOptions::default(),
+            );
+        });
+    }
+}
+
+async fn exchanges_static_and_event_data(config: LoopbackConfig) {
+    let mut pair = Pair::spawn(config).await;
+
+    pair.update(true);
+    assert_eq!(
+        pair.read(Classes::new(true, EventClasses::none())).await,
+        vec![(true, 0)]
+    );
+
+    // both changes were recorded as events
+    pair.update(false);
+    assert_eq!(
+        pair.read(Classes::new(false, EventClasses::all())).await,
+        vec![(true, 0), (false, 0)]
+    );
+}
+
+#[tokio::test]
+async fn master_and_outstation_exchange_data_over_loopback() {
+    exchanges_static_and_event_data(LoopbackConfig::default()).await;
+}
+
+#[tokio::test]
+async fn master_and_outstation_exchange_data_over_loopback_with_latency() {
+    let config = LoopbackConfig {
+        latency: Some(Duration::from_millis(20)),
+        ..LoopbackConfig::default()
+    };
+    exchanges_static_and_event_data(config).await;
+}
@@ -268,6 +268,8 @@ pub unsafe fn master_channel_operate(
                 CommandResponseError::ObjectCountMismatch => ffi::CommandResult::HeaderMismatch,
                 CommandResponseError::ObjectValueMismatch => ffi::CommandResult::HeaderMismatch,
             },
+            // only produced when operating a selection, which isn't exposed in the bindings
+            Err(CommandError::SelectionLost) => ffi::CommandResult::BadStatus,
         };
 
         callback.on_complete(result);
@@ -555,6 +557,7 @@ fn convert_config(
         response_timeout: Timeout::from_duration(config.response_timeout()).unwrap(),
         tx_buffer_size: config.tx_buffer_size() as usize,
         rx_buffer_size: config.rx_buffer_size() as usize,
+        link_confirm: None,
     })
 }
 
@@ -588,6 +591,7 @@ impl From<ffi::SerialPortSettings> for dnp3::serial::SerialSettings {
                 ffi::StopBits::One => StopBits::One,
                 ffi::StopBits::Two => StopBits::Two,
             },
+            timing: dnp3::serial::SerialTiming::default(),
         }
     }
 }
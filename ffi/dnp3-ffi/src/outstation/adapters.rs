@@ -10,7 +10,11 @@ impl OutstationApplication for ffi::OutstationApplication {
         ffi::OutstationApplication::get_processing_delay_ms(self).unwrap_or(0)
     }
 
-    fn write_absolute_time(&mut self, time: Timestamp) -> WriteTimeResult {
+    fn write_absolute_time(
+        &mut self,
+        time: Timestamp,
+        _context: &RequestContext,
+    ) -> WriteTimeResult {
         ffi::OutstationApplication::write_absolute_time(self, time.raw_value())
             .map(|res| res.into())
             .unwrap_or(WriteTimeResult::NotSupported)
@@ -38,6 +42,7 @@ impl OutstationApplication for ffi::OutstationApplication {
         &mut self,
         indices: FreezeIndices,
         freeze_type: FreezeType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> FreezeResult {
         match indices {
@@ -143,11 +148,11 @@ impl OutstationInformation for ffi::OutstationInformation {
 }
 
 impl ControlHandler for ffi::ControlHandler {
-    fn begin_fragment(&mut self) {
+    fn begin_fragment(&mut self, _context: &RequestContext) {
         ffi::ControlHandler::begin_fragment(self);
     }
 
-    fn end_fragment(&mut self) {
+    fn end_fragment(&mut self, _context: &RequestContext) {
         ffi::ControlHandler::end_fragment(self);
     }
 }
@@ -176,6 +181,7 @@ impl ControlSupport<Group12Var1> for ffi::ControlHandler {
         &mut self,
         control: Group12Var1,
         index: u16,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::select_g12v1(self, control.into(), index, database as *mut _)
@@ -188,6 +194,7 @@ impl ControlSupport<Group12Var1> for ffi::ControlHandler {
         control: Group12Var1,
         index: u16,
         op_type: OperateType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::operate_g12v1(
@@ -207,6 +214,7 @@ impl ControlSupport<Group41Var1> for ffi::ControlHandler {
         &mut self,
         control: Group41Var1,
         index: u16,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::select_g41v1(self, control.value, index, database as *mut _)
@@ -219,6 +227,7 @@ impl ControlSupport<Group41Var1> for ffi::ControlHandler {
         control: Group41Var1,
         index: u16,
         op_type: OperateType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::operate_g41v1(
@@ -238,6 +247,7 @@ impl ControlSupport<Group41Var2> for ffi::ControlHandler {
         &mut self,
         control: Group41Var2,
         index: u16,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::select_g41v2(self, control.value, index, database as *mut _)
@@ -250,6 +260,7 @@ impl ControlSupport<Group41Var2> for ffi::ControlHandler {
         control: Group41Var2,
         index: u16,
         op_type: OperateType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::operate_g41v2(
@@ -269,6 +280,7 @@ impl ControlSupport<Group41Var3> for ffi::ControlHandler {
         &mut self,
         control: Group41Var3,
         index: u16,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::select_g41v3(self, control.value, index, database as *mut _)
@@ -281,6 +293,7 @@ impl ControlSupport<Group41Var3> for ffi::ControlHandler {
         control: Group41Var3,
         index: u16,
         op_type: OperateType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::operate_g41v3(
@@ -300,6 +313,7 @@ impl ControlSupport<Group41Var4> for ffi::ControlHandler {
         &mut self,
         control: Group41Var4,
         index: u16,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::select_g41v4(self, control.value, index, database as *mut _)
@@ -312,6 +326,7 @@ impl ControlSupport<Group41Var4> for ffi::ControlHandler {
         control: Group41Var4,
         index: u16,
         op_type: OperateType,
+        _context: &RequestContext,
         database: &mut Database,
     ) -> CommandStatus {
         ffi::ControlHandler::operate_g41v4(
@@ -267,6 +267,7 @@ impl From<ffi::BinaryConfig> for BinaryConfig {
                 ffi::EventBinaryVariation::Group2Var2 => EventBinaryVariation::Group2Var2,
                 ffi::EventBinaryVariation::Group2Var3 => EventBinaryVariation::Group2Var3,
             },
+            chatter_filter: None,
         }
     }
 }
@@ -303,6 +304,7 @@ impl From<ffi::DoubleBitBinaryConfig> for DoubleBitBinaryConfig {
                     EventDoubleBitBinaryVariation::Group4Var3
                 }
             },
+            chatter_filter: None,
         }
     }
 }
@@ -459,6 +461,8 @@ impl From<ffi::AnalogConfig> for AnalogConfig {
                 ffi::EventAnalogVariation::Group32Var8 => EventAnalogVariation::Group32Var8,
             },
             deadband: from.deadband(),
+            deadband_mode: DeadbandMode::Absolute,
+            max_report_interval: None,
         }
     }
 }
@@ -517,6 +521,8 @@ impl From<ffi::AnalogOutputStatusConfig> for AnalogOutputStatusConfig {
                 }
             },
             deadband: from.deadband(),
+            deadband_mode: DeadbandMode::Absolute,
+            max_report_interval: None,
         }
     }
 }
@@ -7,7 +7,7 @@ use dnp3::app::Listener;
 use dnp3::link::{EndpointAddress, LinkErrorMode};
 use dnp3::outstation::database::{ClassZeroConfig, EventBufferConfig};
 use dnp3::outstation::{BufferSize, ConnectionState, Feature, Features, OutstationConfig};
-use dnp3::outstation::{BufferSizeError, OutstationHandle};
+use dnp3::outstation::{BufferSizeError, OutstationHandle, UnsolicitedHoldConfig};
 use dnp3::tcp::{FilterError, ServerHandle};
 pub use struct_constructors::*;
 
@@ -205,13 +205,17 @@ fn convert_outstation_config(
         decode_level: config.decode_level().clone().into(),
         confirm_timeout: config.confirm_timeout(),
         select_timeout: config.select_timeout(),
+        operate_timeout: OutstationConfig::DEFAULT_OPERATE_TIMEOUT,
         features: config.features().into(),
         max_unsolicited_retries: Some(config.max_unsolicited_retries() as usize),
         unsolicited_retry_delay: config.unsolicited_retry_delay(),
+        unsolicited_hold: UnsolicitedHoldConfig::default(),
         keep_alive_timeout,
         class_zero: config.class_zero.into(),
         max_read_request_headers: Some(config.max_read_request_headers),
         max_controls_per_request: Some(config.max_controls_per_request),
+        clock: None,
+        link_confirm: None,
     })
 }
 